@@ -0,0 +1,73 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{AdapterReport, FormatReport, Report};
+
+/** The differences between two reports, one line each, e.g. from a report saved on one machine
+ * to a report saved on another. Adapters are matched up by `AdapterReport::key`, and lines start
+ * with `-` for what only `old` has and `+` for what only `new` has.
+ */
+pub fn diff(old: &Report, new: &Report) -> Vec<String> {
+    let mut lines = Vec::new();
+    for adapter in &old.adapters {
+        if !new.adapters.iter().any(|other| other.key() == adapter.key()) {
+            lines.push(format!("- adapter {}", adapter.key()));
+        }
+    }
+    for adapter in &new.adapters {
+        match old.adapters.iter().find(|other| other.key() == adapter.key()) {
+            Some(old_adapter) => diff_adapters(old_adapter, adapter, &mut lines),
+            None => lines.push(format!("+ adapter {}", adapter.key())),
+        }
+    }
+    lines
+}
+
+fn diff_adapters(old: &AdapterReport, new: &AdapterReport, lines: &mut Vec<String>) {
+    let key = new.key();
+    let mut changed = |what: &str, old: &dyn ToString, new: &dyn ToString| {
+        let (old, new) = (old.to_string(), new.to_string());
+        if old != new {
+            lines.push(format!("{key}: {what} {old} -> {new}"));
+        }
+    };
+    changed("device type", &old.device_type, &new.device_type);
+    changed("driver", &old.driver, &new.driver);
+    changed("driver info", &old.driver_info, &new.driver_info);
+    changed("shader model", &old.downlevel.shader_model, &new.downlevel.shader_model);
+    changed("WebGPU compliant", &old.downlevel.webgpu_compliant, &new.downlevel.webgpu_compliant);
+
+    let names: BTreeSet<&String> = old.limits.keys().chain(new.limits.keys()).collect();
+    for name in names {
+        let value = |limits: &BTreeMap<String, u64>| {
+            limits.get(name).map_or("-".to_owned(), u64::to_string)
+        };
+        changed(name, &value(&old.limits), &value(&new.limits));
+    }
+
+    diff_names(&key, "feature", &old.features, &new.features, lines);
+    diff_names(&key, "downlevel flag", &old.downlevel.flags, &new.downlevel.flags, lines);
+
+    for format in &new.formats {
+        let old_format = old.formats.iter().find(|other| other.format == format.format);
+        diff_format(&key, old_format, format, lines);
+    }
+}
+
+fn diff_names(key: &str, what: &str, old: &[String], new: &[String], lines: &mut Vec<String>) {
+    for name in old.iter().filter(|name| !new.contains(name)) {
+        lines.push(format!("{key}: - {what} {name}"));
+    }
+    for name in new.iter().filter(|name| !old.contains(name)) {
+        lines.push(format!("{key}: + {what} {name}"));
+    }
+}
+
+// Formats only one of the reports knows about, e.g. from another wgpu version, are left out
+fn diff_format(key: &str, old: Option<&FormatReport>, new: &FormatReport, lines: &mut Vec<String>) {
+    let Some(old) = old else {
+        return;
+    };
+    let key = format!("{key}: {}", new.format);
+    diff_names(&key, "usage", &old.usages, &new.usages, lines);
+    diff_names(&key, "flag", &old.flags, &new.flags, lines);
+}
@@ -0,0 +1,13 @@
+/*
+   What the adapters on this machine can do.
+
+   `Report::collect` asks every adapter wgpu finds, on every backend, for its info, limits,
+   features, downlevel capabilities and what it supports of each texture format. Reports print as
+   tables, and are saved as JSON so that reports from different machines can be compared with
+   `diff`.
+   */
+pub mod diff;
+pub mod report;
+
+pub use diff::diff;
+pub use report::*;
@@ -1,39 +1,52 @@
-use log;
+use std::process::exit;
 
-async fn run() {
-    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
-   let adapter = {
-        let instance = wgpu::Instance::default();
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            log::info!("Available adapters:");
-            for a in instance.enumerate_adapters(wgpu::Backends::all()) {
-                log::info!("    {:?}", a.get_info())
-            }
-        }
-        instance
-            .request_adapter(&wgpu::RequestAdapterOptions::default())
-            .await
-            .unwrap()
-   };
+use hello::{diff, Report};
+
+const USAGE: &str = "\
+Usage: hello [--json]              Report on every adapter, as tables or as JSON
+       hello diff <old> <new>      Compare two reports saved with --json";
 
-   log::info!("Selected adapter: {:?}", adapter.get_info())
+fn load(path: &str) -> Report {
+    let json = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Error in reading the report {path}: {e}");
+        exit(2);
+    });
+    Report::from_json(&json).unwrap_or_else(|e| {
+        eprintln!("Error in parsing the report {path}: {e}");
+        exit(2);
+    })
 }
 
 fn main() {
+    // wgpu logs problems with adapters it skips, shown with RUST_LOG=warn
+    env_logger::init();
 
-    // Log to stdout
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        env_logger::init();
-        pollster::block_on(run());
-    }
-    #[cfg(target_arch = "wasm32")]
-    {
-        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
-        console_log::init().expect("could not initialize logger");
-        wasm_bindgen_futures::spawn_local(run());
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] | ["--json"] => {
+            let report = pollster::block_on(Report::collect(&wgpu::Instance::default()));
+            if args.is_empty() {
+                print!("{report}");
+            } else {
+                println!("{}", report.to_json());
+            }
+        }
+        ["diff", old, new] => {
+            let lines = diff(&load(old), &load(new));
+            if lines.is_empty() {
+                println!("No differences");
+            }
+            for line in &lines {
+                println!("{line}");
+            }
+            // Like diff(1), so scripts can tell whether anything changed
+            exit(if lines.is_empty() { 0 } else { 1 });
+        }
+        ["help" | "-h" | "--help"] => println!("{USAGE}"),
+        _ => {
+            eprintln!("{USAGE}");
+            exit(2);
+        }
     }
 }
-
@@ -0,0 +1,254 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+
+/** The adapters found on a machine.
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    pub adapters: Vec<AdapterReport>,
+}
+
+/** Everything an adapter reports about itself. Flags and enums are stored by name so that saved
+ * reports stay readable, and comparable across wgpu versions.
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AdapterReport {
+    pub name: String,
+    pub backend: String,
+    pub device_type: String,
+    pub vendor: u32,
+    pub device: u32,
+    pub driver: String,
+    pub driver_info: String,
+    /** Whether it is the adapter picked by default, which is the one the examples run on.
+     */
+    pub selected: bool,
+    pub limits: BTreeMap<String, u64>,
+    pub features: Vec<String>,
+    pub downlevel: DownlevelReport,
+    pub formats: Vec<FormatReport>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DownlevelReport {
+    pub shader_model: String,
+    pub webgpu_compliant: bool,
+    pub flags: Vec<String>,
+}
+
+/** What can be done with textures of a format.
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FormatReport {
+    pub format: String,
+    pub usages: Vec<String>,
+    pub flags: Vec<String>,
+}
+
+// Destructuring makes this stop compiling when wgpu adds a limit, rather than leave it out
+macro_rules! limits {
+    ($limits:expr, $($name:ident),* $(,)?) => {{
+        let wgpu::Limits { $($name),* } = $limits;
+        BTreeMap::from([$((stringify!($name).to_owned(), u64::from($name))),*])
+    }};
+}
+
+fn flag_names<'a>(names: impl Iterator<Item = (&'a str, impl Sized)>) -> Vec<String> {
+    names.map(|(name, _)| name.to_owned()).collect()
+}
+
+impl Report {
+    /** Reports on every adapter `instance` can find.
+     */
+    pub async fn collect(instance: &wgpu::Instance) -> Self {
+        let selected = instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+            .map(|adapter| adapter.get_info());
+        let adapters = instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .iter()
+            .map(|adapter| AdapterReport::new(adapter, selected.as_ref() == Some(&adapter.get_info())))
+            .collect();
+        Report { adapters }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Error in serializing the report")
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+impl AdapterReport {
+    pub fn new(adapter: &wgpu::Adapter, selected: bool) -> Self {
+        let info = adapter.get_info();
+        let downlevel = adapter.get_downlevel_capabilities();
+        let limits = limits!(
+            adapter.limits(),
+            max_texture_dimension_1d,
+            max_texture_dimension_2d,
+            max_texture_dimension_3d,
+            max_texture_array_layers,
+            max_bind_groups,
+            max_bindings_per_bind_group,
+            max_dynamic_uniform_buffers_per_pipeline_layout,
+            max_dynamic_storage_buffers_per_pipeline_layout,
+            max_sampled_textures_per_shader_stage,
+            max_samplers_per_shader_stage,
+            max_storage_buffers_per_shader_stage,
+            max_storage_textures_per_shader_stage,
+            max_uniform_buffers_per_shader_stage,
+            max_uniform_buffer_binding_size,
+            max_storage_buffer_binding_size,
+            max_vertex_buffers,
+            max_buffer_size,
+            max_vertex_attributes,
+            max_vertex_buffer_array_stride,
+            min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment,
+            max_inter_stage_shader_components,
+            max_compute_workgroup_storage_size,
+            max_compute_invocations_per_workgroup,
+            max_compute_workgroup_size_x,
+            max_compute_workgroup_size_y,
+            max_compute_workgroup_size_z,
+            max_compute_workgroups_per_dimension,
+            max_push_constant_size,
+            max_non_sampler_bindings,
+        );
+        let formats = texture_formats()
+            .into_iter()
+            .map(|format| {
+                let features = adapter.get_texture_format_features(format);
+                FormatReport {
+                    format: format!("{format:?}"),
+                    usages: flag_names(features.allowed_usages.iter_names()),
+                    flags: flag_names(features.flags.iter_names()),
+                }
+            })
+            .collect();
+
+        AdapterReport {
+            name: info.name,
+            backend: format!("{:?}", info.backend),
+            device_type: format!("{:?}", info.device_type),
+            vendor: info.vendor,
+            device: info.device,
+            driver: info.driver,
+            driver_info: info.driver_info,
+            selected,
+            limits,
+            features: flag_names(adapter.features().iter_names()),
+            downlevel: DownlevelReport {
+                shader_model: format!("{:?}", downlevel.shader_model),
+                webgpu_compliant: downlevel.is_webgpu_compliant(),
+                flags: flag_names(downlevel.flags.iter_names()),
+            },
+            formats,
+        }
+    }
+
+    /** How the adapter is told apart from others in a report: the same GPU can show up once per
+     * backend.
+     */
+    pub fn key(&self) -> String {
+        format!("{} ({})", self.name, self.backend)
+    }
+}
+
+/** Every texture format wgpu knows about, compressed ones included.
+ */
+pub fn texture_formats() -> Vec<TextureFormat> {
+    use TextureFormat::*;
+    let mut formats = vec![
+        R8Unorm, R8Snorm, R8Uint, R8Sint, R16Uint, R16Sint, R16Unorm, R16Snorm, R16Float,
+        Rg8Unorm, Rg8Snorm, Rg8Uint, Rg8Sint, R32Uint, R32Sint, R32Float, Rg16Uint, Rg16Sint,
+        Rg16Unorm, Rg16Snorm, Rg16Float, Rgba8Unorm, Rgba8UnormSrgb, Rgba8Snorm, Rgba8Uint,
+        Rgba8Sint, Bgra8Unorm, Bgra8UnormSrgb, Rgb9e5Ufloat, Rgb10a2Uint, Rgb10a2Unorm,
+        Rg11b10Float, Rg32Uint, Rg32Sint, Rg32Float, Rgba16Uint, Rgba16Sint, Rgba16Unorm,
+        Rgba16Snorm, Rgba16Float, Rgba32Uint, Rgba32Sint, Rgba32Float, Stencil8, Depth16Unorm,
+        Depth24Plus, Depth24PlusStencil8, Depth32Float, Depth32FloatStencil8, NV12,
+        Bc1RgbaUnorm, Bc1RgbaUnormSrgb, Bc2RgbaUnorm, Bc2RgbaUnormSrgb, Bc3RgbaUnorm,
+        Bc3RgbaUnormSrgb, Bc4RUnorm, Bc4RSnorm, Bc5RgUnorm, Bc5RgSnorm, Bc6hRgbUfloat,
+        Bc6hRgbFloat, Bc7RgbaUnorm, Bc7RgbaUnormSrgb, Etc2Rgb8Unorm, Etc2Rgb8UnormSrgb,
+        Etc2Rgb8A1Unorm, Etc2Rgb8A1UnormSrgb, Etc2Rgba8Unorm, Etc2Rgba8UnormSrgb, EacR11Unorm,
+        EacR11Snorm, EacRg11Unorm, EacRg11Snorm,
+    ];
+    use AstcBlock::*;
+    for block in [B4x4, B5x4, B5x5, B6x5, B6x6, B8x5, B8x6, B8x8, B10x5, B10x6, B10x8, B10x10, B12x10, B12x12] {
+        for channel in [AstcChannel::Unorm, AstcChannel::UnormSrgb, AstcChannel::Hdr] {
+            formats.push(Astc { block, channel });
+        }
+    }
+    formats
+}
+
+// Lines up a list of names under a heading, or a dash when there are none
+fn write_names(f: &mut fmt::Formatter, names: &[String]) -> fmt::Result {
+    if names.is_empty() {
+        return writeln!(f, "  -");
+    }
+    for name in names {
+        writeln!(f, "  {name}")?;
+    }
+    Ok(())
+}
+
+fn join(names: &[String]) -> String {
+    if names.is_empty() { "-".to_owned() } else { names.join(" ") }
+}
+
+impl fmt::Display for AdapterReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}{}", self.key(), if self.selected { " [selected]" } else { "" })?;
+        writeln!(f, "  {}, vendor {:#06x}, device {:#06x}", self.device_type, self.vendor, self.device)?;
+        if !self.driver.is_empty() || !self.driver_info.is_empty() {
+            writeln!(f, "  Driver: {} {}", self.driver, self.driver_info)?;
+        }
+
+        writeln!(f, "\nLimits")?;
+        let width = self.limits.keys().map(String::len).max().unwrap_or(0);
+        for (name, value) in &self.limits {
+            writeln!(f, "  {name:width$}  {value}")?;
+        }
+
+        writeln!(f, "\nFeatures")?;
+        write_names(f, &self.features)?;
+
+        writeln!(
+            f,
+            "\nDownlevel capabilities: shader model {}, {}",
+            self.downlevel.shader_model,
+            if self.downlevel.webgpu_compliant { "WebGPU compliant" } else { "not WebGPU compliant" }
+        )?;
+        write_names(f, &self.downlevel.flags)?;
+
+        writeln!(f, "\nTexture formats")?;
+        let width = self.formats.iter().map(|format| format.format.len()).max().unwrap_or(0);
+        writeln!(f, "  {:width$}  Usages / Flags", "Format")?;
+        for format in &self.formats {
+            writeln!(f, "  {:width$}  {} / {}", format.format, join(&format.usages), join(&format.flags))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.adapters.is_empty() {
+            return writeln!(f, "No adapters found");
+        }
+        for (i, adapter) in self.adapters.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{adapter}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use hello::{diff, texture_formats, AdapterReport, DownlevelReport, FormatReport, Report};
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn adapter(backend: &str) -> AdapterReport {
+    AdapterReport {
+        name: "Test GPU".to_owned(),
+        backend: backend.to_owned(),
+        device_type: "DiscreteGpu".to_owned(),
+        limits: BTreeMap::from([("max_bind_groups".to_owned(), 4), ("max_buffer_size".to_owned(), 1 << 28)]),
+        features: names(&["DEPTH_CLIP_CONTROL"]),
+        downlevel: DownlevelReport { shader_model: "Sm5".to_owned(), webgpu_compliant: true, flags: names(&["COMPUTE_SHADERS"]) },
+        formats: vec![FormatReport {
+            format: "Rgba8Unorm".to_owned(),
+            usages: names(&["COPY_SRC", "RENDER_ATTACHMENT"]),
+            flags: names(&["FILTERABLE"]),
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn round_trips_through_json() {
+    let report = Report { adapters: vec![adapter("Vulkan"), adapter("Gl")] };
+    assert_eq!(Report::from_json(&report.to_json()).unwrap(), report);
+}
+
+#[test]
+fn prints_every_section() {
+    let text = Report { adapters: vec![adapter("Vulkan")] }.to_string();
+    for expected in ["Test GPU (Vulkan)", "max_bind_groups", "DEPTH_CLIP_CONTROL", "shader model Sm5", "COMPUTE_SHADERS", "Rgba8Unorm"] {
+        assert!(text.contains(expected), "{expected} missing from\n{text}");
+    }
+    assert_eq!(Report::default().to_string(), "No adapters found\n");
+}
+
+#[test]
+fn finds_no_differences_in_the_same_report() {
+    let report = Report { adapters: vec![adapter("Vulkan")] };
+    assert!(diff(&report, &report).is_empty());
+}
+
+#[test]
+fn lists_differences() {
+    let old = Report { adapters: vec![adapter("Vulkan"), adapter("Gl")] };
+    let mut changed = adapter("Vulkan");
+    changed.limits.insert("max_bind_groups".to_owned(), 8);
+    changed.features = names(&["TIMESTAMP_QUERY"]);
+    changed.formats[0].usages.pop();
+    let new = Report { adapters: vec![changed, adapter("Metal")] };
+
+    assert_eq!(diff(&old, &new), [
+        "- adapter Test GPU (Gl)",
+        "Test GPU (Vulkan): max_bind_groups 4 -> 8",
+        "Test GPU (Vulkan): - feature DEPTH_CLIP_CONTROL",
+        "Test GPU (Vulkan): + feature TIMESTAMP_QUERY",
+        "Test GPU (Vulkan): Rgba8Unorm: - usage RENDER_ATTACHMENT",
+        "+ adapter Test GPU (Metal)",
+    ]);
+}
+
+#[test]
+fn reports_on_every_adapter() {
+    let instance = wgpu::Instance::default();
+    if pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).is_none() {
+        eprintln!("No adapter available, skipping");
+        return;
+    }
+    let report = pollster::block_on(Report::collect(&instance));
+    assert_eq!(report.adapters.iter().filter(|adapter| adapter.selected).count(), 1);
+    for adapter in &report.adapters {
+        assert_eq!(adapter.formats.len(), texture_formats().len());
+        assert!(adapter.limits["max_texture_dimension_2d"] >= 2048);
+    }
+}
@@ -0,0 +1,95 @@
+use framework::{app::{App, Frame}, WgpuContext};
+
+
+pub struct HelloTriangle {
+    render_pipeline: wgpu::RenderPipeline,
+}
+
+impl App for HelloTriangle {
+    const NAME: &'static str = "hello-triangle";
+    const DESCRIPTION: &'static str = "A single triangle, drawn with as little setup as possible";
+    const TITLE: &'static str = "Hello triangle";
+    const SIZE: (u32, u32) = (800, 600);
+
+    fn new(context: &WgpuContext, _args: &[String]) -> Self {
+        let device = &context.device;
+
+        // Load shaders. include_str! loads the contents of the pass file, while Cow::Borrowed does
+        // a clone-on-write procedure, essentially allowing lazy-loading for the passed file into
+        // memory. This line of code effectively loads the shader into memory without compilation.
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("shader.wgsl"))),
+        });
+
+        // Creates a pipeline layout abstracted from the architecture of the underlying device.
+        let pipeline_layout = device.create_pipeline_layout(
+            &wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            });
+
+        // The swapchain is a queue specifying which image is going to be drawn to the screen. The
+        // surface is agnostic to the adapter so combining the two gets the capabilites of the
+        // swapchain, and the context picks the format frames are drawn in from them. Headless,
+        // there is no swapchain and frames are drawn into a texture of the context's format.
+        let swapchain_format = context.swapchain_format();
+
+        // Establish the pipeline for the main shader by specifying the layout, vertex shader (with the
+        // files entry point), fragment shader (with the file's entry point), the targeted window, and
+        // various other properties that need not be covered here.
+        let render_pipeline = device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",  // Entry point within the specified file
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(swapchain_format.into())],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None
+            });
+
+        HelloTriangle { render_pipeline }
+    }
+
+    fn render(&mut self, context: &WgpuContext, frame: &mut Frame) {
+        // Initialize an encoder for the device which converts our semantics into
+        // something meaningful
+        let mut encoder =
+            context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: None,
+            });
+
+        // Enclose in block so memory is immediately freed
+        {
+            // Start the render pass. The color attachment is another image view but with
+            // specfic instructions on loading, storing, and resolution. In this case, the
+            // image view can either use the LoadOp::Clear to clear the screen, or LoadOp::Load
+            // to use the existing frame. Storing can either save the operation or immediately
+            // discard it.
+            let mut rpass = framework::RenderPassBuilder::new()
+                .clear(frame.view, wgpu::Color::BLUE)
+                .build(&mut encoder);
+
+            // Set the pipeline to the one created earlier
+            rpass.set_pipeline(&self.render_pipeline);
+
+            // Draw the first 3 vertices and the first instance
+            rpass.draw(0..3, 0..1);
+        }
+
+        // Add the frame to the end of the swapchain. The runner then schedules it for
+        // presentation on the owning surface.
+        context.queue.submit(Some(encoder.finish()));
+    }
+}
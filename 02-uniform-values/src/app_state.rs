@@ -1,20 +1,40 @@
 use encase::ShaderType;
 
+use crate::{fractal::{FRACTAL_COUNT, FRACTAL_JULIA}, palette::PALETTE_COUNT};
+
 const ZOOM_INCREMENT_FACTOR: f32 = 1.1;
 const CAMERA_POS_INCREMENT_FACTOR: f32 = 0.1;
+const PALETTE_CYCLE_SPEED: f32 = 0.1;
+const EXPONENT_INCREMENT: f32 = 0.25;
+// Half the width of the view at a zoom of 1.0, matching the scale applied in the shader
+const VIEW_HALF_EXTENT: f32 = 3.0;
 
 
-// Uniform to be sent to the shader
+// Uniform to be sent to the shader, laid out like the struct in app_state.wgsl
 #[derive(ShaderType)]
 pub struct AppState {
     pub cursor_pos:  glam::Vec2,
     pub zoom: f32,
     pub max_iterations: u32,
+    // Index into the palettes listed in `palette::PALETTE_NAMES`
+    pub palette: u32,
+    // Shift applied to the palette lookup, advanced over time while cycling
+    pub palette_offset: f32,
+    // Palette shift per second, 0.0 when cycling is off
+    pub palette_cycle_speed: f32,
+    // Non-zero to color with the normalized (smooth) iteration count instead of the raw one
+    pub smooth_coloring: u32,
+    // Index into the fractals listed in `fractal::FRACTAL_NAMES`
+    pub fractal: u32,
+    // Power `z` is raised to by the Multibrot set
+    pub exponent: f32,
+    // Constant added each iteration of the Julia set
+    pub julia_c: glam::Vec2,
 }
 
 impl AppState {
     pub fn as_wgsl_bytes(&self) -> encase::internal::Result<Vec<u8>> {
-        let mut buffer = encase::UniformBuffer::new(vec![]); 
+        let mut buffer = encase::UniformBuffer::new(vec![]);
         buffer.write(self)?;
         Ok(buffer.into_inner())
     }
@@ -27,14 +47,90 @@ impl AppState {
         self.zoom += ZOOM_INCREMENT_FACTOR * amount * self.zoom.powf(1.02);
         self.zoom = self.zoom.max(1.1);
     }
+
+    /** The point on the complex plane under `ndc`, a position in normalized device coordinates.
+     */
+    pub fn point_at(&self, ndc: glam::Vec2) -> glam::Vec2 {
+        ndc * VIEW_HALF_EXTENT / self.zoom + self.cursor_pos
+    }
+
+    /** Zooms while keeping the point under `ndc` fixed on screen.
+     */
+    pub fn zoom_at(&mut self, amount: f32, ndc: glam::Vec2) {
+        let anchor = self.point_at(ndc);
+        self.zoom(amount);
+        self.cursor_pos = anchor - ndc * VIEW_HALF_EXTENT / self.zoom;
+    }
+
+    /** Moves the view so that the content follows a drag of `ndc_delta` across the screen.
+     */
+    pub fn pan(&mut self, ndc_delta: glam::Vec2) {
+        self.cursor_pos -= ndc_delta * VIEW_HALF_EXTENT / self.zoom;
+    }
+
+    pub fn cycle_fractal(&mut self, increments: i32) {
+        self.fractal = (self.fractal as i32 + increments).rem_euclid(FRACTAL_COUNT as i32) as u32;
+    }
+
+    pub fn change_exponent(&mut self, increments: i32) {
+        self.exponent = (self.exponent + EXPONENT_INCREMENT * increments as f32).max(1.0 + EXPONENT_INCREMENT);
+    }
+
+    pub fn pick_julia_constant(&mut self, ndc: glam::Vec2) {
+        if self.fractal == FRACTAL_JULIA {
+            self.julia_c = self.point_at(ndc);
+        }
+    }
+
+    pub fn cycle_palette(&mut self, increments: i32) {
+        self.palette = (self.palette as i32 + increments).rem_euclid(PALETTE_COUNT as i32) as u32;
+    }
+
+    pub fn toggle_palette_cycling(&mut self) {
+        self.palette_cycle_speed = if self.is_cycling_palette() { 0.0 } else { PALETTE_CYCLE_SPEED };
+    }
+
+    pub fn is_cycling_palette(&self) -> bool {
+        self.palette_cycle_speed != 0.0
+    }
+
+    pub fn toggle_smooth_coloring(&mut self) {
+        self.smooth_coloring = (self.smooth_coloring == 0) as u32;
+    }
+
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.palette_offset = (self.palette_offset + self.palette_cycle_speed * delta_seconds).fract();
+    }
 }
 
+// Editors for the debug UI. Indices are limited to the fractals and palettes the shader knows.
+#[cfg(feature = "egui")]
+framework::impl_inspect!(AppState {
+    cursor_pos,
+    #[log] zoom: 1.0..=1.0e6,
+    max_iterations: 1..=2000,
+    fractal: 0..=FRACTAL_COUNT - 1,
+    exponent: 1.25..=8.0,
+    julia_c,
+    palette: 0..=PALETTE_COUNT - 1,
+    palette_offset: 0.0..=1.0,
+    palette_cycle_speed: -1.0..=1.0,
+    smooth_coloring: 0..=1,
+});
+
 impl Default for AppState {
     fn default() -> Self {
         AppState {
             cursor_pos: glam::Vec2::ZERO,
             zoom: 1.0,
-            max_iterations: 50
+            max_iterations: 50,
+            palette: 0,
+            palette_offset: 0.0,
+            palette_cycle_speed: 0.0,
+            smooth_coloring: 1,
+            fractal: 0,
+            exponent: 3.0,
+            julia_c: glam::vec2(-0.8, 0.156),
         }
     }
 }
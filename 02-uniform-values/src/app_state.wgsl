@@ -0,0 +1,29 @@
+// The uniform written from `AppState` in app_state.rs and the values of its enumerated fields,
+// for any shader that reads it.
+
+struct AppState {
+	cursor_pos_x: f32,
+	cursor_pos_y: f32,
+	zoom: f32,
+	max_iterations: u32,
+	palette: u32,
+	palette_offset: f32,
+	palette_cycle_speed: f32,
+	smooth_coloring: u32,
+	fractal: u32,
+	exponent: f32,
+	julia_c_x: f32,
+	julia_c_y: f32,
+}
+
+const FRACTAL_MANDELBROT: u32 = 0u;
+const FRACTAL_JULIA: u32 = 1u;
+const FRACTAL_BURNING_SHIP: u32 = 2u;
+const FRACTAL_MULTIBROT: u32 = 3u;
+const FRACTAL_TRICORN: u32 = 4u;
+
+const PALETTE_GRAYSCALE: u32 = 0u;
+const PALETTE_FIRE: u32 = 1u;
+const PALETTE_OCEAN: u32 = 2u;
+const PALETTE_RAINBOW: u32 = 3u;
+const PALETTE_GRADIENT: u32 = 4u;
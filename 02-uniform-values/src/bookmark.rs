@@ -0,0 +1,141 @@
+use std::{fs, io, path::{Path, PathBuf}};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{app_state::AppState, fractal::FRACTAL_COUNT, palette::PALETTE_COUNT};
+
+/** The view parameters of an `AppState` worth coming back to.
+ *
+ * Plain arrays are used instead of glam types so the file stays readable when edited by hand.
+ */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Bookmark {
+    pub fractal: u32,
+    pub center: [f32; 2],
+    pub zoom: f32,
+    pub max_iterations: u32,
+    pub julia_c: [f32; 2],
+    pub exponent: f32,
+    pub palette: u32,
+}
+
+impl Bookmark {
+    pub fn from_state(state: &AppState) -> Self {
+        Bookmark {
+            fractal: state.fractal,
+            center: state.cursor_pos.into(),
+            zoom: state.zoom,
+            max_iterations: state.max_iterations,
+            julia_c: state.julia_c.into(),
+            exponent: state.exponent,
+            palette: state.palette,
+        }
+    }
+
+    /** Checks that the fractal and palette exist, since both index lists of them and the file may
+     * have been edited by hand or saved by an older version.
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        if self.fractal >= FRACTAL_COUNT {
+            return Err(format!("fractal {} doesn't exist, there are {FRACTAL_COUNT}", self.fractal));
+        }
+        if self.palette >= PALETTE_COUNT {
+            return Err(format!("palette {} doesn't exist, there are {PALETTE_COUNT}", self.palette));
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, state: &mut AppState) {
+        state.fractal = self.fractal;
+        state.cursor_pos = self.center.into();
+        state.zoom = self.zoom;
+        state.max_iterations = self.max_iterations;
+        state.julia_c = self.julia_c.into();
+        state.exponent = self.exponent;
+        state.palette = self.palette;
+    }
+}
+
+/** A list of bookmarks persisted to a single file.
+ *
+ * The file is written as JSON when its extension is `.json` and as RON otherwise.
+ */
+pub struct Bookmarks {
+    path: PathBuf,
+    entries: Vec<Bookmark>,
+    // The bookmark last saved or restored, `None` until there is one
+    current: Option<usize>,
+}
+
+impl Bookmarks {
+    /** Reads the bookmarks at `path`, starting with an empty list if the file does not exist yet.
+     * Fails with `InvalidData` if a bookmark doesn't pass `Bookmark::validate`.
+     */
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => Self::deserialize(&path, &contents)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Bookmarks { path, entries, current: None })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /** Appends `bookmark` and rewrites the file.
+     */
+    pub fn push(&mut self, bookmark: Bookmark) -> io::Result<()> {
+        self.entries.push(bookmark);
+        self.current = Some(self.entries.len() - 1);
+        fs::write(&self.path, self.serialize()?)
+    }
+
+    /** Steps to the bookmark after the one last saved or restored, wrapping around, starting
+     * with the first.
+     */
+    pub fn advance(&mut self) -> Option<&Bookmark> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let next = self.current.map_or(0, |current| (current + 1) % self.entries.len());
+        self.current = Some(next);
+        self.entries.get(next)
+    }
+
+    fn is_json(path: &Path) -> bool {
+        path.extension().is_some_and(|extension| extension == "json")
+    }
+
+    fn deserialize(path: &Path, contents: &str) -> io::Result<Vec<Bookmark>> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        let entries: Vec<Bookmark> = if Self::is_json(path) {
+            serde_json::from_str(contents).map_err(|e| invalid(e.to_string()))?
+        } else {
+            ron::from_str(contents).map_err(|e| invalid(e.to_string()))?
+        };
+        for (index, bookmark) in entries.iter().enumerate() {
+            bookmark.validate().map_err(|e| invalid(format!("bookmark {}: {e}", index + 1)))?;
+        }
+        Ok(entries)
+    }
+
+    fn serialize(&self) -> io::Result<String> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        if Self::is_json(&self.path) {
+            serde_json::to_string_pretty(&self.entries).map_err(|e| invalid(e.to_string()))
+        } else {
+            ron::ser::to_string_pretty(&self.entries, ron::ser::PrettyConfig::default()).map_err(|e| invalid(e.to_string()))
+        }
+    }
+}
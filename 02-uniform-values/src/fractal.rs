@@ -0,0 +1,6 @@
+// Names of the fractals understood by `iterate` in the shader, in index order.
+pub const FRACTAL_NAMES: [&str; 5] = ["Mandelbrot", "Julia", "Burning Ship", "Multibrot", "Tricorn"];
+pub const FRACTAL_COUNT: u32 = FRACTAL_NAMES.len() as u32;
+
+pub const FRACTAL_JULIA: u32 = 1;
+pub const FRACTAL_MULTIBROT: u32 = 3;
@@ -0,0 +1,422 @@
+// IDEA: Build a "builder" program for constructing simple pipelines that packs them automatically
+// E.g.
+// SimplePipelineBuilder::new()
+//     .add_uniform::<AppState>() * calls create_uniform_buffer and automatically adds this to the
+//                                  bind group layout and bind group
+//
+// Any bufferable object must derive from a trait which can specify its byte requirements 
+
+mod app_state;
+pub mod bookmark;
+mod fractal;
+mod palette;
+
+use std::sync::Arc;
+
+use framework::{app::{App, Frame}, shader::{HotShader, Preprocessor}, time::Clock, WgpuContext, BufferBuilder, PipelineLayoutBuilder, RenderPassBuilder};
+#[cfg(feature = "egui")]
+use framework::ui::{egui, frame_time_graph, Inspect, UiOverlay};
+use app_state::AppState;
+use bookmark::{Bookmark, Bookmarks};
+use fractal::{FRACTAL_MULTIBROT, FRACTAL_NAMES};
+use palette::{Gradient, PALETTE_NAMES};
+use wgpu::{BindGroupEntry, BindGroupLayout, Buffer, Device, FragmentState, PipelineLayout, RenderPipeline, ShaderModule, TextureFormat, VertexState};
+use winit::{dpi::PhysicalPosition, event::{ElementState, KeyEvent, MouseButton, WindowEvent}, keyboard::{Key, NamedKey}, window::Window};
+
+struct ShaderProgram {
+    pub pipeline: wgpu::RenderPipeline,
+    pub bind_group: wgpu::BindGroup,
+    pub uniform_buffer: wgpu::Buffer,
+    // Kept to rebuild the pipeline when the shader is edited
+    shader: HotShader,
+    pipeline_layout: PipelineLayout,
+    swapchain_format: TextureFormat,
+}
+
+
+impl ShaderProgram {
+
+    fn create_shader(device: &Device, window: Option<Arc<Window>>) -> HotShader {
+        // The uniform's layout lives in its own file so that other shaders can include it too
+        let preprocessor = Preprocessor::new().add_file("app_state.wgsl", include_str!("app_state.wgsl"));
+        let source = framework::shader_source!("src/shader.wgsl").preprocess(preprocessor);
+        // Redraws happen on demand, so saving the shader has to ask for one
+        match window {
+            Some(window) => HotShader::with_notify(device, source, move || window.request_redraw()),
+            None => HotShader::new(device, source),
+        }
+    }
+
+    fn create_uniform_buffer(device: &Device) -> Buffer {
+        BufferBuilder::size_of::<AppState>()
+            .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
+            .build(device)
+    }
+
+    fn create_bind_group(
+        device: &Device, 
+        uniform_buffer: &wgpu::Buffer, 
+        gradient_view: &wgpu::TextureView, 
+        gradient_sampler: &wgpu::Sampler) -> (wgpu::BindGroupLayout, wgpu::BindGroup) {
+        let layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor { 
+                label: None, 
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer { 
+                            ty: wgpu::BufferBindingType::Uniform, 
+                            has_dynamic_offset: false, 
+                            min_binding_size: None 
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture { 
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true }, 
+                            view_dimension: wgpu::TextureViewDimension::D1, 
+                            multisampled: false 
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ]
+            });
+
+        let bind_group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor { 
+                label: None, 
+                layout: &layout, 
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                            buffer: uniform_buffer,
+                            offset: 0,
+                            size: None,
+                        })
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(gradient_view),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(gradient_sampler),
+                    },
+                ]
+            });
+        (layout, bind_group)
+    }
+
+    fn create_pipeline_layout(device: &Device, bind_group_layout: BindGroupLayout) -> PipelineLayout {
+        PipelineLayoutBuilder::new()
+            .add_bind_group_layout(&bind_group_layout)
+            .build(device)
+    }
+
+    fn create_render_pipeline(
+        device: &Device, 
+        pipeline_layout: &PipelineLayout, 
+        shader_module: &ShaderModule, 
+        swapchain_format: TextureFormat) -> RenderPipeline {
+
+        device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor { 
+                label: None, 
+                layout: Some(pipeline_layout), 
+                vertex: VertexState {
+                    module: shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[]
+                }, 
+                fragment: Some(FragmentState {
+                    module: shader_module,
+                    entry_point: "fs_main",
+                    targets: &[Some(swapchain_format.into())]
+                }), 
+                primitive: wgpu::PrimitiveState::default(), 
+                depth_stencil: None, 
+                multisample: wgpu::MultisampleState::default(), 
+                multiview: None, 
+            })
+        
+    }
+
+    fn new(context: &WgpuContext, gradient: &Gradient) -> Self {
+
+        let device = &context.device;
+        
+        let uniform_buffer = ShaderProgram::create_uniform_buffer(&device);
+        let (gradient_view, gradient_sampler) = gradient.upload(device, &context.queue);
+        let (bind_group_layout, bind_group) = ShaderProgram::create_bind_group(&device, &uniform_buffer, &gradient_view, &gradient_sampler);
+        let pipeline_layout = ShaderProgram::create_pipeline_layout(&device, bind_group_layout);
+
+        // Create the shader module on the device from the passed program
+        let shader = ShaderProgram::create_shader(&device, context.window.clone());
+        let swapchain_format = context.swapchain_format();
+
+        let render_pipeline = ShaderProgram::create_render_pipeline(&device, &pipeline_layout, shader.module(), swapchain_format);
+
+        Self {
+            pipeline: render_pipeline,
+            bind_group,
+            uniform_buffer,
+            shader,
+            pipeline_layout,
+            swapchain_format,
+        }
+    }
+
+    /** Rebuilds the pipeline if `shader.wgsl` or a file it includes was edited, returning true if it was.
+     */
+    fn reload(&mut self, device: &Device) -> bool {
+        let (layout, format) = (&self.pipeline_layout, self.swapchain_format);
+        match self.shader.reload(device, |module| ShaderProgram::create_render_pipeline(device, layout, module, format)) {
+            Some(pipeline) => {
+                self.pipeline = pipeline;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn update_title(window: &Window, state: &AppState) {
+    let fractal = match state.fractal {
+        FRACTAL_MULTIBROT => format!("{} (d = {})", FRACTAL_NAMES[state.fractal as usize], state.exponent),
+        _ => FRACTAL_NAMES[state.fractal as usize].to_string(),
+    };
+    let cycling = if state.is_cycling_palette() { ", cycling" } else { "" };
+    window.set_title(&format!("Working with uniforms: {} ({} palette{})", fractal, PALETTE_NAMES[state.palette as usize], cycling));
+}
+
+// Converts a cursor position in pixels to normalized device coordinates with y pointing up
+fn to_ndc(position: PhysicalPosition<f64>, context: &WgpuContext) -> glam::Vec2 {
+    let width = context.surface_config.width.max(1) as f32;
+    let height = context.surface_config.height.max(1) as f32;
+    glam::vec2(2.0 * position.x as f32 / width - 1.0, 1.0 - 2.0 * position.y as f32 / height)
+}
+
+pub struct UniformValues {
+    state: AppState,
+    shader_program: ShaderProgram,
+    // Only with a window, since egui takes its input from it
+    #[cfg(feature = "egui")]
+    ui: Option<UiOverlay>,
+    bookmarks: Bookmarks,
+    clock: Clock,
+    // Frames are only drawn when something changes, so measuring needs them drawn back to back
+    redraw_continuously: bool,
+
+    // Left button drags the view, right button picks the Julia constant
+    cursor_position: PhysicalPosition<f64>,
+    dragging: bool,
+    picking_julia_constant: bool,
+}
+
+impl UniformValues {
+    fn update_title(&self, context: &WgpuContext) {
+        if let Some(window) = &context.window {
+            update_title(window, &self.state);
+        }
+    }
+}
+
+impl App for UniformValues {
+    const NAME: &'static str = "uniform-values";
+    const DESCRIPTION: &'static str = "Fractals explored by panning and zooming, driven by a uniform buffer and a debug UI";
+    const TITLE: &'static str = "Working with uniforms";
+
+    fn new(context: &WgpuContext, args: &[String]) -> Self {
+        // An optional image to use as the "Gradient" palette, e.g. `cargo run -p uniform-values -- gradient.png`
+        let gradient = match args.first() {
+            Some(path) => Gradient::load(path)
+                .unwrap_or_else(|e| panic!("Error in loading the gradient image {path}: {e}")),
+            None => Gradient::default(),
+        };
+
+        // Views saved with B and restored with N, stored as RON unless the path ends in `.json`
+        let bookmarks_path = std::env::var("FRACTAL_BOOKMARKS").unwrap_or_else(|_| "bookmarks.ron".to_string());
+        let bookmarks = Bookmarks::load(&bookmarks_path)
+            .unwrap_or_else(|e| panic!("Error in loading the bookmarks in {bookmarks_path}: {e}"));
+
+        let app = UniformValues {
+            state: AppState::default(),
+            shader_program: ShaderProgram::new(context, &gradient),
+            #[cfg(feature = "egui")]
+            ui: context.window.is_some().then(|| UiOverlay::new(context)),
+            bookmarks,
+            clock: Clock::new(),
+            redraw_continuously: false,
+            cursor_position: PhysicalPosition::new(0.0, 0.0),
+            dragging: false,
+            picking_julia_constant: false,
+        };
+        app.update_title(context);
+        app
+    }
+
+    fn window_event(&mut self, context: &WgpuContext, event: &WindowEvent) {
+        // Input used by the debug UI, e.g. dragging a slider, doesn't reach the fractal
+        #[cfg(feature = "egui")]
+        let consumed = match (&mut self.ui, &context.window) {
+            (Some(ui), Some(window)) => ui.handle_event(window, event),
+            _ => false,
+        };
+        #[cfg(not(feature = "egui"))]
+        let consumed = false;
+        let state = &mut self.state;
+
+        match event {
+            WindowEvent::MouseWheel { delta, .. } if !consumed => {
+                let change = match delta {
+                    winit::event::MouseScrollDelta::LineDelta(_x, y) => *y,
+                    winit::event::MouseScrollDelta::PixelDelta(pos) => { pos.y as f32 }
+                } / 20.0;
+
+                state.zoom_at(change, to_ndc(self.cursor_position, context));
+                context.request_redraw();
+            }
+            WindowEvent::MouseInput { state: button_state, button, .. } if !consumed => {
+                let pressed = *button_state == ElementState::Pressed;
+                match button {
+                    MouseButton::Left => self.dragging = pressed,
+                    MouseButton::Right => {
+                        self.picking_julia_constant = pressed;
+                        if pressed {
+                            state.pick_julia_constant(to_ndc(self.cursor_position, context));
+                            context.request_redraw();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let ndc = to_ndc(*position, context);
+                if self.dragging {
+                    state.pan(ndc - to_ndc(self.cursor_position, context));
+                    context.request_redraw();
+                }
+                if self.picking_julia_constant {
+                    state.pick_julia_constant(ndc);
+                    context.request_redraw();
+                }
+                self.cursor_position = *position;
+            }
+            WindowEvent::KeyboardInput { event: KeyEvent { logical_key, text, .. }, .. } if !consumed => {
+
+                // Escape is left to the runner, which closes the window
+                if let Key::Named(key) = logical_key {
+                    match key {
+                        NamedKey::ArrowUp => state.translate_view(1, 1),
+                        NamedKey::ArrowDown => state.translate_view(-1, 1),
+                        NamedKey::ArrowLeft => state.translate_view(1, 0),
+                        NamedKey::ArrowRight => state.translate_view(-1, 0),
+                        _ => {}
+                    }
+                }
+
+                if let Some(text) = text {
+                    let bookmarks = &mut self.bookmarks;
+                    match text.as_str() {
+                        "u" => state.max_iterations += 3,
+                        "v" => state.max_iterations -= 3,
+                        "p" => state.cycle_palette(1),
+                        "o" => state.cycle_palette(-1),
+                        "c" => state.toggle_palette_cycling(),
+                        "s" => state.toggle_smooth_coloring(),
+                        "f" => state.cycle_fractal(1),
+                        "F" => state.cycle_fractal(-1),
+                        "+" | "=" => state.change_exponent(1),
+                        "-" => state.change_exponent(-1),
+                        "b" => match bookmarks.push(Bookmark::from_state(state)) {
+                            Ok(()) => println!("Saved bookmark {} to {}", bookmarks.len(), bookmarks.path().display()),
+                            Err(e) => eprintln!("Error in saving bookmark to {}: {e}", bookmarks.path().display()),
+                        },
+                        "n" => match bookmarks.advance() {
+                            Some(bookmark) => bookmark.apply(state),
+                            None => eprintln!("No bookmarks saved in {}", bookmarks.path().display()),
+                        },
+                        _ => {}
+                    }
+                }
+
+                self.update_title(context);
+
+                context.request_redraw();
+
+            }
+            _ => {},
+        }
+    }
+
+    fn render(&mut self, context: &WgpuContext, frame: &mut Frame) {
+        let state = &mut self.state;
+        let shader_program = &mut self.shader_program;
+        shader_program.reload(&context.device);
+
+        self.clock.tick();
+        state.update(self.clock.delta());
+
+        // Build the actual render pass
+        context.queue
+            .write_buffer(
+                &shader_program.uniform_buffer, 
+                0, 
+                &state.as_wgsl_bytes().expect("Error in translating AppState to wgsl bytes."));
+
+        let mut encoder = context.command_encoder();
+
+        {
+            let mut rpass = RenderPassBuilder::new()
+                .clear(frame.view, wgpu::Color::BLUE)
+                .build(&mut encoder);
+            rpass.set_pipeline(&shader_program.pipeline);
+            rpass.set_bind_group(0, &shader_program.bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+        }
+
+        // Whether the debug UI edited the uniforms, and whether it is still animating
+        #[cfg(feature = "egui")]
+        let (changed, ui_animating) = match &mut self.ui {
+            Some(ui) => {
+                let mut changed = false;
+                let (clock, redraw_continuously) = (&self.clock, &mut self.redraw_continuously);
+                let animating = ui.render(context, &mut encoder, frame.view, |ctx| {
+                    egui::Window::new("Fractal").show(ctx, |ui| {
+                        ui.label(format!("{} fractal, {} palette", FRACTAL_NAMES[state.fractal as usize], PALETTE_NAMES[state.palette as usize]));
+                        changed |= state.inspect(ui, "Uniforms");
+                        ui.collapsing("Frame timing", |ui| {
+                            ui.checkbox(redraw_continuously, "Redraw continuously");
+                            frame_time_graph(ui, clock.history());
+                        });
+                    });
+                });
+                (changed, animating)
+            }
+            None => (false, false),
+        };
+        #[cfg(not(feature = "egui"))]
+        let (changed, ui_animating) = (false, false);
+
+        context.queue.submit(Some(encoder.finish()));
+
+        if changed {
+            self.update_title(context);
+        }
+
+        // Keep animating while the palette is cycling, and show edits made in the UI
+        if self.state.is_cycling_palette() || changed || ui_animating || self.redraw_continuously {
+            context.request_redraw();
+        }
+    }
+}
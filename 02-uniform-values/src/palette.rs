@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use framework::{SamplerBuilder, TextureBuilder};
+
+// Names of the palettes understood by `color_palette` in the shader, in index order. The last
+// entry samples the gradient texture rather than computing a color procedurally.
+pub const PALETTE_NAMES: [&str; 5] = ["Grayscale", "Fire", "Ocean", "Rainbow", "Gradient"];
+pub const PALETTE_COUNT: u32 = PALETTE_NAMES.len() as u32;
+
+// Stops for the gradient used when no file is given, roughly the classic "Ultra Fractal" ramp.
+const DEFAULT_GRADIENT_STOPS: [[u8; 4]; 5] = [
+    [0, 7, 100, 255],
+    [32, 107, 203, 255],
+    [237, 255, 255, 255],
+    [255, 170, 0, 255],
+    [0, 2, 0, 255],
+];
+const DEFAULT_GRADIENT_WIDTH: usize = 256;
+
+/** A 1D color ramp sampled by the "Gradient" palette.
+ *
+ * Gradients are loaded from any image the `image` crate can decode; the middle row of the image
+ * is used, so both a 1-pixel-tall strip and a larger swatch work.
+ */
+pub struct Gradient {
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Gradient {
+    pub fn load(path: impl AsRef<Path>) -> image::ImageResult<Self> {
+        let image = image::open(path)?.into_rgba8();
+        let row = image.height() / 2;
+        let pixels = (0..image.width())
+            .map(|x| image.get_pixel(x, row).0)
+            .collect();
+        Ok(Gradient { pixels })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.pixels.len() as u32
+    }
+
+    /** Uploads the gradient as a repeating 1D texture so that cycling can simply offset the
+     * lookup coordinate.
+     */
+    pub fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> (wgpu::TextureView, wgpu::Sampler) {
+        let texture = TextureBuilder::new(self.width(), 1)
+            .label("Palette gradient")
+            .dimension(wgpu::TextureDimension::D1)
+            .build_with_data(device, queue, bytemuck::cast_slice(&self.pixels));
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = SamplerBuilder::new()
+            .label("Palette gradient sampler")
+            .address_mode_u(wgpu::AddressMode::Repeat)
+            .mag_filter(wgpu::FilterMode::Linear)
+            .min_filter(wgpu::FilterMode::Linear)
+            .build(device);
+        (view, sampler)
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        let segments = DEFAULT_GRADIENT_STOPS.len() - 1;
+        let pixels = (0..DEFAULT_GRADIENT_WIDTH)
+            .map(|x| {
+                let t = x as f32 / (DEFAULT_GRADIENT_WIDTH - 1) as f32 * segments as f32;
+                let i = (t as usize).min(segments - 1);
+                let f = t - i as f32;
+                let (a, b) = (DEFAULT_GRADIENT_STOPS[i], DEFAULT_GRADIENT_STOPS[i + 1]);
+                std::array::from_fn(|c| (a[c] as f32 + (b[c] as f32 - a[c] as f32) * f).round() as u8)
+            })
+            .collect();
+        Gradient { pixels }
+    }
+}
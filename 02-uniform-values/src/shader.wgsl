@@ -1,54 +1,119 @@
-struct AppState {
-	cursor_pos_x: f32,
-	cursor_pos_y: f32,
-	zoom: f32,
-	max_iterations: u32
-}
+#include "app_state.wgsl"
+#include "framework/fullscreen.wgsl"
 
+// Escaping at a large radius rather than 2.0 keeps the smooth iteration count continuous
+const ESCAPE_RADIUS: f32 = 256.0;
 
 @group(0)
 @binding(0)
 var<uniform> state: AppState;
 
-struct VertexOutput {
-	@builtin(position) position: vec4f,
-	@location(0) coord: vec2f,
-}
+@group(0)
+@binding(1)
+var gradient_texture: texture_1d<f32>;
+
+@group(0)
+@binding(2)
+var gradient_sampler: sampler;
 
 @vertex
-fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
-	var p = vec2f(0.0, 0.0);
-	if (index == 0u) {
-		p = vec2f(-1, 1);
-	} else if (index == 1u) {
-		p = vec2f(3.0, 1.0);
-	} else {
-		p = vec2f(-1.0, -3.0);
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+	return fullscreen_vertex(index);
+}
+
+// Cosine palette, see https://iquilezles.org/articles/palettes/
+fn cosine_palette(t: f32, a: vec3f, b: vec3f, c: vec3f, d: vec3f) -> vec3f {
+	return a + b * cos(6.28318 * (c * t + d));
+}
+
+fn color_palette(t: f32) -> vec3f {
+	let shifted = fract(t + state.palette_offset);
+	switch state.palette {
+		case PALETTE_FIRE: {
+			return clamp(vec3f(3.0 * shifted, 3.0 * shifted - 1.0, 3.0 * shifted - 2.0), vec3f(0.0), vec3f(1.0));
+		}
+		case PALETTE_OCEAN: {
+			return cosine_palette(shifted, vec3f(0.0, 0.3, 0.5), vec3f(0.0, 0.3, 0.5), vec3f(1.0), vec3f(0.0, 0.1, 0.2));
+		}
+		case PALETTE_RAINBOW: {
+			return cosine_palette(shifted, vec3f(0.5), vec3f(0.5), vec3f(1.0), vec3f(0.0, 0.33, 0.67));
+		}
+		case PALETTE_GRADIENT: {
+			return textureSample(gradient_texture, gradient_sampler, shifted).rgb;
+		}
+		default: {
+			return vec3f(shifted);
+		}
+	}
+}
+
+// z^2 for the complex number z = x + iy
+fn complex_square(z: vec2f) -> vec2f {
+	return vec2f(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y);
+}
+
+// z^n for real n, computed in polar form
+fn complex_pow(z: vec2f, n: f32) -> vec2f {
+	let r = pow(length(z), n);
+	let theta = atan2(z.y, z.x) * n;
+	return r * vec2f(cos(theta), sin(theta));
+}
+
+// One step of the selected fractal's recurrence
+fn iterate(z: vec2f, c: vec2f) -> vec2f {
+	switch state.fractal {
+		case FRACTAL_BURNING_SHIP: {
+			return complex_square(abs(z)) + c;
+		}
+		case FRACTAL_MULTIBROT: {
+			return complex_pow(z, state.exponent) + c;
+		}
+		case FRACTAL_TRICORN: {
+			return complex_square(vec2f(z.x, -z.y)) + c;
+		}
+		default: {
+			return complex_square(z) + c;
+		}
 	}
-	var out: VertexOutput;
-	out.coord = p;
-	out.position = vec4f(p, 0.0, 1.0);
-	return out;
 }
 
 @fragment
-fn fs_main(vin: VertexOutput) -> @location(0) vec4f {
+fn fs_main(vin: FullscreenOutput) -> @location(0) vec4f {
 	let max_iterations = state.max_iterations;
 	var final_iteration = max_iterations;
 
-	let c = (vin.coord * 3.0 / state.zoom) + vec2(state.cursor_pos_x, state.cursor_pos_y);
-	var current_z = c;
-	var next_z: vec2f;
+	let point = (uv_to_ndc(vin.uv) * 3.0 / state.zoom) + vec2(state.cursor_pos_x, state.cursor_pos_y);
+
+	// The Julia set starts every orbit at the point and adds a fixed constant, the others add
+	// the point itself.
+	var c = point;
+	var degree = 2.0;
+	if state.fractal == FRACTAL_JULIA {
+		c = vec2(state.julia_c_x, state.julia_c_y);
+	} else if state.fractal == FRACTAL_MULTIBROT {
+		degree = state.exponent;
+	}
+
+	var current_z = point;
 	for (var i = 0u; i < max_iterations; i++) {
-		next_z.x = (current_z.x * current_z.x - current_z.y * current_z.y) + c.x;
-		next_z.y = (2.0 * current_z.x * current_z.y) + c.y;
-		current_z = next_z;
-		if length(current_z) > 4.0 {
+		current_z = iterate(current_z, c);
+		if length(current_z) > ESCAPE_RADIUS {
 			final_iteration = i;
 			break;
 		}
 	}
-	let value = f32(final_iteration) / f32(max_iterations);
 
-	return vec4(value, value, value, 1.0);
+	// Texture sampling must happen in uniform control flow, so the palette is looked up before
+	// points inside the set are masked out.
+	var value = f32(final_iteration);
+	if state.smooth_coloring != 0u {
+		// Normalized iteration count: subtract how far past the escape radius the orbit got
+		value = value + 1.0 - log(log(length(current_z))) / log(degree);
+	}
+	let color = color_palette(value / f32(max_iterations));
+
+	if final_iteration == max_iterations {
+		return vec4(0.0, 0.0, 0.0, 1.0);
+	}
+	return vec4(color, 1.0);
 }
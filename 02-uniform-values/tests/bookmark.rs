@@ -0,0 +1,51 @@
+use std::{io, path::PathBuf};
+
+use uniform_values::bookmark::{Bookmark, Bookmarks};
+
+fn bookmark(fractal: u32, palette: u32) -> Bookmark {
+    Bookmark { fractal, center: [0.0, 0.0], zoom: 1.0, max_iterations: 100, julia_c: [0.0, 0.0], exponent: 2.0, palette }
+}
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("uniform-values-{}-{name}", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn loads_saved_bookmarks() {
+    let json = serde_json::to_string(&[bookmark(1, 2), bookmark(4, 4)]).unwrap();
+    let path = temp_file("valid.json", &json);
+    let mut bookmarks = Bookmarks::load(&path).unwrap();
+
+    assert_eq!(bookmarks.len(), 2);
+    // Restoring starts from the first bookmark and wraps around
+    assert_eq!(bookmarks.advance().unwrap().fractal, 1);
+    assert_eq!(bookmarks.advance().unwrap().fractal, 4);
+    assert_eq!(bookmarks.advance().unwrap().fractal, 1);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn rejects_bookmarks_with_a_bad_index() {
+    let json = serde_json::to_string(&[bookmark(0, 0), bookmark(7, 0)]).unwrap();
+    let path = temp_file("bad-fractal.json", &json);
+    let error = Bookmarks::load(&path).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(error.to_string().starts_with("bookmark 2: fractal 7"), "{error}");
+    std::fs::remove_file(path).unwrap();
+
+    let ron = ron::to_string(&vec![bookmark(0, 99)]).unwrap();
+    let path = temp_file("bad-palette.ron", &ron);
+    let error = Bookmarks::load(&path).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("palette 99"), "{error}");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn validates_indices() {
+    assert!(bookmark(4, 4).validate().is_ok());
+    assert!(bookmark(5, 0).validate().is_err());
+    assert!(bookmark(0, 5).validate().is_err());
+}
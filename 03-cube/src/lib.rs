@@ -0,0 +1,330 @@
+// Improvements: Build a texture atlas dynamically via creating texel lookup
+//
+use encase::ShaderType;
+use framework::{app::{App, Frame}, camera::{Camera, CameraController, OrbitController}, mesh::{GpuMesh, MeshVertex}, pipeline::{LayoutId, PipelineCache, RenderPipelineKey, ShaderId}, skybox::Skybox, texture::{ColorSpace, Texture}, time::{Clock, FpsTitle}, transform::{Transform, TransformInstance}, WgpuContext, BufferBuilder, RenderPassBuilder};
+#[cfg(feature = "egui")]
+use framework::ui::{egui, frame_time_graph, Inspect, UiOverlay};
+use winit::{dpi::PhysicalSize, event::{ElementState, KeyEvent, WindowEvent}, keyboard::Key};
+
+// Cubes are laid out on a GRID_SIZE x GRID_SIZE grid, each drawn as one instance
+const GRID_SIZE: u32 = 3;
+const GRID_SPACING: f32 = 3.0;
+const TEXTURE_SIZE: u32 = 256;
+const SKYBOX_FACE_SIZE: u32 = 512;
+const TITLE: &str = "Drag to orbit, scroll to zoom, P to pause";
+
+// Fills the texture with the Mandelbrot set, brighter where points take longer to escape
+fn create_texels(size: u32) -> Vec<u8> {
+    const MAX_ITERATIONS: u32 = 64;
+    (0..size * size)
+        .flat_map(|index| {
+            let c = glam::vec2(
+                3.0 * (index % size) as f32 / (size - 1) as f32 - 2.0,
+                2.4 * (index / size) as f32 / (size - 1) as f32 - 1.2,
+            );
+            let mut z = glam::Vec2::ZERO;
+            let mut iterations = 0;
+            while iterations < MAX_ITERATIONS && z.length_squared() < 4.0 {
+                z = glam::vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+                iterations += 1;
+            }
+            let value = (255 * iterations / MAX_ITERATIONS) as u8;
+            [value, value, value, 255]
+        })
+        .collect()
+}
+
+// Places each cube on the grid, spinning at its own rate around its own axis
+fn cube_transforms(time: f32) -> Vec<Transform> {
+    let offset = (GRID_SIZE - 1) as f32 * GRID_SPACING / 2.0;
+    (0..GRID_SIZE * GRID_SIZE)
+        .map(|i| {
+            let (x, z) = ((i % GRID_SIZE) as f32, (i / GRID_SIZE) as f32);
+            let axis = glam::vec3(1.0 + x, 1.0, 1.0 + z).normalize();
+            let speed = 0.5 + 0.25 * i as f32;
+            Transform::from_translation(glam::vec3(x * GRID_SPACING - offset, 0.0, z * GRID_SPACING - offset))
+                .with_rotation(glam::Quat::from_axis_angle(axis, time * speed))
+                .with_scale(glam::Vec3::splat(0.5))
+        })
+        .collect()
+}
+
+fn cube_instances(time: f32) -> Vec<TransformInstance> {
+    cube_transforms(time).iter().map(Transform::instance).collect()
+}
+
+struct Shader {
+    bind_group: wgpu::BindGroup,
+    // Kept alive for the bind group
+    _texture: Texture,
+    mesh: GpuMesh,
+    instance_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+    // Created for whichever surface format the frame is drawn to, and rebuilt when the shader
+    // is edited
+    pipelines: PipelineCache,
+    shader: ShaderId,
+    pipeline_layout: LayoutId,
+    // Drawn behind the cubes when an equirectangular HDR image is passed on the command line
+    skybox: Option<Skybox>,
+}
+
+impl Shader {
+
+    fn new(wgpu_context: &WgpuContext, camera: &Camera, skybox_path: Option<&String>) -> Self {
+        // Pre-Initialize shortcuts
+
+        let device = &wgpu_context.device;
+        let format = wgpu_context.swapchain_format();
+
+        // register the shader, which is compiled when the first pipeline needs it
+
+        let mut pipelines = PipelineCache::new();
+        let shader = pipelines.add_shader(framework::shader_source!("src/shader.wgsl"));
+
+        let texture = Texture::from_rgba8(
+            device,
+            &wgpu_context.queue,
+            TEXTURE_SIZE,
+            TEXTURE_SIZE,
+            &create_texels(TEXTURE_SIZE),
+            ColorSpace::Srgb,
+            Some("Cube texture"));
+
+        // Construct the pipeline by building the various layout requirements
+
+        let layout_entries = texture.layout_entries(1, wgpu::ShaderStages::FRAGMENT);
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    count: None,
+                    ty: wgpu::BindingType::Buffer { 
+                        has_dynamic_offset: false, 
+                        ty: wgpu::BufferBindingType::Uniform,
+                        min_binding_size: Some(framework::camera::CameraUniform::min_size()),
+                    }
+                },
+                layout_entries[0],
+                layout_entries[1],
+            ]
+        });
+
+        let uniform_buffer = BufferBuilder::slice_of(&camera.uniform().as_wgsl_bytes().expect("Error in translating CameraUniform to wgsl bytes."))
+            .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
+            .build(device);
+
+        let texture_entries = texture.bind_group_entries(1);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                texture_entries[0].clone(),
+                texture_entries[1].clone(),
+            ]
+        });
+
+        let pipeline_layout = pipelines.add_layout(framework::PipelineLayoutBuilder::new()
+            .add_bind_group_layout(&layout)
+            .build(device));
+
+
+        // Build the initial buffers
+        let mesh = framework::mesh::cube(2.0).upload(device);
+
+        let instance_buffer = BufferBuilder::slice_of(&cube_instances(0.0))
+            .usage(wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST)
+            .build(device);
+
+
+        let skybox = skybox_path.map(|path| {
+            let bytes = std::fs::read(path).expect("Error in reading the skybox image.");
+            let cubemap = framework::cubemap::from_equirectangular(device, &wgpu_context.queue, &bytes, SKYBOX_FACE_SIZE)
+                .expect("Error in decoding the skybox image.");
+            Skybox::new(device, &cubemap, format, Some(WgpuContext::DEPTH_FORMAT))
+        });
+
+        Shader { bind_group, _texture: texture, uniform_buffer, mesh, instance_buffer, pipelines, shader, pipeline_layout, skybox }
+    }
+
+    fn pipeline_key(&self, format: wgpu::TextureFormat) -> RenderPipelineKey {
+        RenderPipelineKey::new(self.shader)
+            .layout(self.pipeline_layout)
+            .vertex_buffer(MeshVertex::layout())
+            .vertex_buffer(TransformInstance::layout())
+            .target(format)
+            .primitive(wgpu::PrimitiveState {
+                topology: self.mesh.topology,
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            })
+            .depth_stencil(wgpu::DepthStencilState {
+                format: WgpuContext::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+    }
+}
+
+
+
+// Animation settings edited in the debug UI, applied to the clock every frame
+struct Settings {
+    speed: f64,
+    paused: bool,
+}
+
+#[cfg(feature = "egui")]
+framework::impl_inspect!(Settings {
+    #[log] speed: 0.0625..=16.0,
+    paused,
+});
+
+fn surface_size(context: &WgpuContext) -> PhysicalSize<u32> {
+    PhysicalSize::new(context.surface_config.width, context.surface_config.height)
+}
+
+pub struct Cube {
+    camera: Camera,
+    controller: OrbitController,
+    shader: Shader,
+    depth_view: wgpu::TextureView,
+    // Pausing or slowing down the cubes leaves the camera moving
+    clock: Clock,
+    settings: Settings,
+    // Only with a window, since egui takes its input from it
+    #[cfg(feature = "egui")]
+    ui: Option<UiOverlay>,
+    title: FpsTitle,
+}
+
+impl App for Cube {
+    const NAME: &'static str = "cube";
+    const DESCRIPTION: &'static str = "Textured, instanced cubes around an orbiting camera, optionally in front of an HDR skybox";
+    const TITLE: &'static str = TITLE;
+
+    fn new(context: &WgpuContext, args: &[String]) -> Self {
+        let size = surface_size(context);
+        let camera = Camera::perspective(
+            glam::vec3(0.0, 2.0, 6.0), 
+            glam::Vec3::ZERO, 
+            std::f32::consts::FRAC_PI_4, 
+            size.width.max(1) as f32 / size.height.max(1) as f32);
+        let controller = OrbitController::new(&camera);
+        // An equirectangular HDR image to use as the skybox, e.g. `cargo run -p cube -- sky.hdr`
+        let shader = Shader::new(context, &camera, args.first());
+        let depth_view = context.create_depth_view();
+
+        Cube {
+            camera,
+            controller,
+            shader,
+            depth_view,
+            clock: Clock::new(),
+            settings: Settings { speed: 1.0, paused: false },
+            #[cfg(feature = "egui")]
+            ui: context.window.is_some().then(|| UiOverlay::new(context)),
+            title: FpsTitle::new(TITLE),
+        }
+    }
+
+    fn resize(&mut self, context: &WgpuContext) {
+        self.camera.resize(surface_size(context));
+        self.depth_view = context.create_depth_view();
+    }
+
+    // The context is only needed to hand the event to the debug UI
+    #[cfg_attr(not(feature = "egui"), allow(unused_variables))]
+    fn window_event(&mut self, context: &WgpuContext, event: &WindowEvent) {
+        // Input used by the debug UI, e.g. dragging a slider, doesn't move the camera
+        #[cfg(feature = "egui")]
+        {
+            let consumed = match (&mut self.ui, &context.window) {
+                (Some(ui), Some(window)) => ui.handle_event(window, event),
+                _ => false,
+            };
+            if consumed {
+                return;
+            }
+        }
+        self.controller.handle_event(event);
+        if let WindowEvent::KeyboardInput { event: KeyEvent { logical_key: Key::Character(key), state: ElementState::Pressed, .. }, .. } = event {
+            if key.as_str() == "p" {
+                self.settings.paused = !self.settings.paused;
+            }
+        }
+    }
+
+    fn render(&mut self, context: &WgpuContext, frame: &mut Frame) {
+        let shader = &mut self.shader;
+        let format = context.swapchain_format();
+        shader.pipelines.reload(&context.device);
+        shader.pipelines.set_surface_format(format);
+        let pipeline_key = shader.pipeline_key(format);
+        shader.pipelines.prepare(&context.device, &pipeline_key);
+
+        self.clock.set_scale(self.settings.speed);
+        if self.settings.paused {
+            self.clock.pause();
+        } else {
+            self.clock.resume();
+        }
+        self.clock.tick();
+        if let Some(window) = &context.window {
+            self.title.update(window, self.clock.history());
+        }
+        self.controller.update(&mut self.camera, self.clock.real_delta().as_secs_f32());
+
+        let time = self.clock.elapsed();
+        context.queue.write_buffer(&shader.uniform_buffer, 0, &self.camera.uniform().as_wgsl_bytes().expect("Error in translating CameraUniform to wgsl bytes."));
+        context.queue.write_buffer(&shader.instance_buffer, 0, bytemuck::cast_slice(&cube_instances(time)));
+        if let Some(skybox) = &shader.skybox {
+            skybox.update(&context.queue, &self.camera);
+        }
+
+        let mut encoder = context.command_encoder();
+        {
+            let mut rpass = RenderPassBuilder::new()
+                .clear(frame.view, wgpu::Color::BLUE)
+                .depth(&self.depth_view, 1.0)
+                .build(&mut encoder);
+
+            rpass.push_debug_group("Setting pipeline");
+            rpass.set_pipeline(shader.pipelines.get(&pipeline_key).unwrap());
+            rpass.set_bind_group(0, &shader.bind_group, &[]);
+            rpass.set_vertex_buffer(1, shader.instance_buffer.slice(..));
+            rpass.pop_debug_group();
+            rpass.push_debug_group("Preparing to draw");
+            shader.mesh.draw(&mut rpass, 0..GRID_SIZE * GRID_SIZE);
+            rpass.pop_debug_group();
+            if let Some(skybox) = &shader.skybox {
+                skybox.draw(&mut rpass);
+            }
+        }
+
+        #[cfg(feature = "egui")]
+        if let Some(ui) = &mut self.ui {
+            let (settings, controller, clock) = (&mut self.settings, &mut self.controller, &self.clock);
+            ui.render(context, &mut encoder, frame.view, |ctx| {
+                egui::Window::new("Cube").show(ctx, |ui| {
+                    settings.inspect(ui, "Animation");
+                    controller.inspect(ui, "Camera");
+                    ui.collapsing("Frame timing", |ui| frame_time_graph(ui, clock.history()));
+                });
+            });
+        }
+        context.queue.submit(Some(encoder.finish()));
+
+        // For shader updates
+        context.request_redraw();
+    }
+}
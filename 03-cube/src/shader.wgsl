@@ -1,47 +1,49 @@
 struct VertexOutput {
 	@builtin(position) position: vec4<f32>,
-	@location(0) color: vec4<f32>
+	@location(0) color: vec4<f32>,
+	@location(1) uv: vec2<f32>,
+}
+
+struct Camera {
+	view_projection: mat4x4<f32>,
+	view: mat4x4<f32>,
+	projection: mat4x4<f32>,
+	position: vec3<f32>,
+}
+
+struct InstanceInput {
+	@location(8) model_0: vec4<f32>,
+	@location(9) model_1: vec4<f32>,
+	@location(10) model_2: vec4<f32>,
+	@location(11) model_3: vec4<f32>,
+	@location(12) normal_0: vec3<f32>,
+	@location(13) normal_1: vec3<f32>,
+	@location(14) normal_2: vec3<f32>,
 }
 
 
 @group(0)
 @binding(0)
-var<uniform> time: f32;
-
+var<uniform> camera: Camera;
 
-@vertex
-fn vs_main(@location(0) position: vec4<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
-
-	var s = sin(time);
-	var c = cos(time);
-	var yaw_matrix = mat4x4(
-		vec4(c, -s, 0, 0),
-		vec4(s, c, 0, 0),
-		vec4(0, 0, 1, 0),
-		vec4(0, 0, 0, 1),
-	);
-
-	var pitch_matrix = mat4x4(
-		vec4(c, 0, s, 0),
-		vec4(0, 1, 0, 0),
-		vec4(-s, 0, c, 0),
-		vec4(0, 0, 0, 1),
-	);
-
-	var roll_matrix = mat4x4(
-		vec4(1, 0, 0, 0),
-		vec4(0, c, -s, 0),
-		vec4(0, s, c, 0),
-		vec4(0, 0, 0, 1),
-	);
+@group(0)
+@binding(1)
+var cube_texture: texture_2d<f32>;
 
+@group(0)
+@binding(2)
+var cube_sampler: sampler;
 
-	var output: VertexOutput;
-	output.position = yaw_matrix * pitch_matrix * roll_matrix * position;
-	output.color = color;
 
-	output.position.w = 4.0;
+@vertex
+fn vs_main(@location(0) position: vec3<f32>, @location(1) normal: vec3<f32>, @location(3) uv: vec2<f32>, instance: InstanceInput) -> VertexOutput {
+	let model = mat4x4(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
 
+	// Color each face by the direction it faces in the cube's own space
+	var output: VertexOutput;
+	output.position = camera.view_projection * model * vec4(position, 1.0);
+	output.color = vec4(normal * 0.5 + 0.5, 1.0);
+	output.uv = uv;
 
 	return output;
 }
@@ -49,5 +51,7 @@ fn vs_main(@location(0) position: vec4<f32>, @location(1) color: vec4<f32>) -> V
 
 @fragment
 fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
-	return vertex.color;
+	// Tint the generated texture with the face color
+	let texel = textureSample(cube_texture, cube_sampler, vertex.uv);
+	return vertex.color * (0.25 + 0.75 * texel.r);
 }
@@ -0,0 +1,149 @@
+pub mod simulation;
+
+use std::time::{Duration, Instant};
+
+use framework::{app::{App, Frame}, profiler::GpuProfiler, shapes::CircleRenderer, text::{TextRenderer, TextStyle}, time::{Clock, FixedTimestep}, RenderPassBuilder, WgpuContext};
+use glam::vec2;
+use simulation::{Particle, Rng, Simulation, SimulationParams};
+use winit::{dpi::PhysicalPosition, event::{ElementState, MouseButton, WindowEvent}};
+
+const INITIAL_PARTICLES: usize = 200;
+// Pixels per second squared, pointing down the screen
+const GRAVITY: f32 = 980.0;
+const RESTITUTION: f32 = 0.8;
+// Short steps, so that fast circles don't tunnel through each other
+const STEPS_PER_SECOND: f64 = 240.0;
+// Longest frame simulated in one go, so a stalled window doesn't launch everything
+const MAX_FRAME_TIME: Duration = Duration::from_micros(33_333);
+const LABEL_SIZE: f32 = 18.0;
+
+
+fn create_simulation(context: &WgpuContext, rng: &mut Rng) -> Simulation {
+    let bounds = vec2(context.surface_config.width as f32, context.surface_config.height as f32);
+    let particles: Vec<Particle> = (0..INITIAL_PARTICLES)
+        .map(|_| {
+            let center = vec2(rng.range(0.0, bounds.x), rng.range(0.0, bounds.y * 0.5));
+            rng.particle(center)
+        })
+        .collect();
+
+    let params = SimulationParams {
+        gravity: vec2(0.0, GRAVITY),
+        bounds,
+        delta_time: 0.0,
+        restitution: RESTITUTION,
+        count: 0,
+    };
+    Simulation::new(&context.device, &particles, params)
+}
+
+fn create_circle_renderer(context: &WgpuContext) -> CircleRenderer {
+    // Reads the circles straight out of the simulation's particles, skipping their velocities
+    CircleRenderer::with_stride(
+        &context.device,
+        context.swapchain_format(),
+        context.surface_config.width,
+        context.surface_config.height,
+        std::mem::size_of::<Particle>() as wgpu::BufferAddress)
+}
+
+
+pub struct Circles {
+    rng: Rng,
+    simulation: Simulation,
+    circles: CircleRenderer,
+    text: TextRenderer,
+    cursor_position: PhysicalPosition<f64>,
+    clock: Clock,
+    timestep: FixedTimestep,
+    profiler: GpuProfiler,
+}
+
+impl App for Circles {
+    const NAME: &'static str = "circles";
+    const DESCRIPTION: &'static str = "Circles bouncing off each other, simulated in a compute shader; click to drop more";
+    const TITLE: &'static str = "Click to drop more circles";
+
+    fn new(context: &WgpuContext, _args: &[String]) -> Self {
+        let mut rng = Rng::new(0x9e37_79b9);
+        let simulation = create_simulation(context, &mut rng);
+        Circles {
+            rng,
+            simulation,
+            circles: create_circle_renderer(context),
+            text: TextRenderer::new(&context.device, context.swapchain_format(), context.surface_config.width, context.surface_config.height),
+            cursor_position: PhysicalPosition::new(0.0, 0.0),
+            clock: Clock::new().with_max_delta(MAX_FRAME_TIME),
+            timestep: FixedTimestep::per_second(STEPS_PER_SECOND),
+            profiler: GpuProfiler::new(&context.device, &context.queue),
+        }
+    }
+
+    fn resize(&mut self, context: &WgpuContext) {
+        let (width, height) = (context.surface_config.width, context.surface_config.height);
+        self.circles.resize(&context.queue, width, height);
+        self.text.resize(&context.queue, width, height);
+        self.simulation.params.bounds = vec2(width as f32, height as f32);
+    }
+
+    fn window_event(&mut self, context: &WgpuContext, event: &WindowEvent) {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = *position;
+            }
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+                let center = vec2(self.cursor_position.x as f32, self.cursor_position.y as f32);
+                self.simulation.spawn(&context.queue, self.rng.particle(center));
+            }
+            _ => {}
+        }
+    }
+
+    fn render(&mut self, context: &WgpuContext, frame: &mut Frame) {
+        let (simulation, profiler, text) = (&mut self.simulation, &mut self.profiler, &mut self.text);
+
+        self.clock.tick();
+        let steps = self.timestep.update(self.clock.delta_duration());
+
+        let encode_start = Instant::now();
+        let mut encoder = context.command_encoder();
+
+        let scope = profiler.begin_scope("Simulation");
+        simulation.step_timed(&context.queue, &mut encoder, self.timestep.step_seconds(), steps, profiler.compute_timestamp_writes(scope));
+        profiler.end_scope(scope);
+
+        let mut label = format!("{} circles\nClick to drop more", simulation.count());
+        for timing in profiler.results() {
+            label += &format!("\n{timing}");
+        }
+        text.push_text(&label, vec2(12.0, 8.0), &TextStyle::new(LABEL_SIZE), [1.0, 1.0, 1.0, 1.0]);
+        text.prepare(&context.device, &context.queue);
+
+        let scope = profiler.begin_scope("Drawing");
+        {
+            let mut rpass = RenderPassBuilder::new()
+                .clear(frame.view, wgpu::Color::BLACK)
+                .timestamp_writes(profiler.render_timestamp_writes(scope))
+                .build(&mut encoder);
+
+            rpass.push_debug_group("Drawing circles");
+            self.circles.draw_buffer(&mut rpass, simulation.buffer().slice(..), simulation.count());
+            rpass.pop_debug_group();
+
+            rpass.push_debug_group("Drawing labels");
+            text.draw(&mut rpass);
+            rpass.pop_debug_group();
+        }
+        profiler.end_scope(scope);
+        profiler.resolve(&mut encoder);
+        let command_buffer = encoder.finish();
+        frame.trace.record_cpu("Encode", encode_start, Instant::now());
+
+        frame.trace.scope("Submit", || context.queue.submit(Some(command_buffer)));
+        profiler.end_frame(&context.device);
+        frame.trace.record_profiler(profiler);
+
+        // For shader updates
+        context.request_redraw();
+    }
+}
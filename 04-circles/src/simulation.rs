@@ -0,0 +1,275 @@
+/*
+   A simulation of circles falling under gravity and bouncing off the walls and each other, run
+   in a compute shader.
+
+   Particles live in a pair of storage buffers that are read from and written to alternately.
+   The layout of a `Particle` starts with a `Circle`, so whichever buffer holds the latest state
+   is also bound directly as the instance buffer of the `CircleRenderer`.
+   */
+use bytemuck::{Pod, Zeroable};
+use encase::ShaderType;
+use framework::{shapes::Circle, BufferBuilder};
+use glam::Vec2;
+
+// Particles are allocated up front so spawning never has to reallocate the buffers
+pub const MAX_PARTICLES: u32 = 4096;
+const WORKGROUP_SIZE: u32 = 64;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Particle {
+    pub circle: Circle,
+    pub velocity: [f32; 2],
+    // Pads the struct to the 16 byte alignment WGSL gives it
+    _padding: [f32; 2],
+}
+
+impl Particle {
+    pub fn new(circle: Circle, velocity: Vec2) -> Self {
+        Particle { circle, velocity: velocity.into(), _padding: [0.0; 2] }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.circle.center.into()
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity.into()
+    }
+}
+
+#[derive(ShaderType, Clone, Copy, Debug)]
+pub struct SimulationParams {
+    // Acceleration in pixels per second squared
+    pub gravity: Vec2,
+    // Size of the box the particles bounce around in, in pixels
+    pub bounds: Vec2,
+    pub delta_time: f32,
+    // Fraction of speed kept on every bounce
+    pub restitution: f32,
+    pub count: u32,
+}
+
+impl SimulationParams {
+    pub fn as_wgsl_bytes(&self) -> encase::internal::Result<Vec<u8>> {
+        let mut buffer = encase::UniformBuffer::new(vec![]);
+        buffer.write(self)?;
+        Ok(buffer.into_inner())
+    }
+}
+
+/** Advances `particles` by one step exactly as `simulation.wgsl` does, as a reference for the
+ * GPU version.
+ */
+pub fn step_cpu(particles: &mut [Particle], params: &SimulationParams) {
+    let source = particles.to_vec();
+    for (index, particle) in particles.iter_mut().enumerate() {
+        let current = source[index];
+        let mass = current.circle.radius * current.circle.radius;
+        let mut position = current.center();
+        let mut velocity = current.velocity();
+
+        for (other_index, other) in source.iter().enumerate() {
+            if other_index == index {
+                continue;
+            }
+            let offset = current.center() - other.center();
+            let distance = offset.length();
+            let overlap = current.circle.radius + other.circle.radius - distance;
+            if overlap <= 0.0 || distance <= 0.0 {
+                continue;
+            }
+            let normal = offset / distance;
+            let other_mass = other.circle.radius * other.circle.radius;
+            let share = other_mass / (mass + other_mass);
+            position += normal * overlap * share;
+            let approach = (current.velocity() - other.velocity()).dot(normal);
+            if approach < 0.0 {
+                velocity -= (1.0 + params.restitution) * share * approach * normal;
+            }
+        }
+
+        velocity += params.gravity * params.delta_time;
+        position += velocity * params.delta_time;
+
+        let radius = current.circle.radius;
+        let high = params.bounds - radius;
+        if position.x < radius {
+            position.x = radius;
+            velocity.x = velocity.x.abs() * params.restitution;
+        } else if position.x > high.x {
+            position.x = high.x;
+            velocity.x = -velocity.x.abs() * params.restitution;
+        }
+        if position.y < radius {
+            position.y = radius;
+            velocity.y = velocity.y.abs() * params.restitution;
+        } else if position.y > high.y {
+            position.y = high.y;
+            velocity.y = -velocity.y.abs() * params.restitution;
+        }
+
+        particle.circle.center = position.into();
+        particle.velocity = velocity.into();
+    }
+}
+
+/** A small xorshift generator, enough to scatter spawned particles.
+ */
+pub struct Rng(u32);
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Rng(seed.max(1))
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /** A circle with a random size and color at `center`, moving in a random direction.
+     */
+    pub fn particle(&mut self, center: Vec2) -> Particle {
+        let color = [self.range(0.2, 1.0), self.range(0.2, 1.0), self.range(0.2, 1.0), 1.0];
+        let circle = Circle::filled(center, self.range(6.0, 18.0), color).with_stroke(1.5, [1.0, 1.0, 1.0, 1.0]);
+        let velocity = Vec2::new(self.range(-200.0, 200.0), self.range(-200.0, 0.0));
+        Particle::new(circle, velocity)
+    }
+}
+
+pub struct Simulation {
+    pub params: SimulationParams,
+    pipeline: wgpu::ComputePipeline,
+    params_buffer: wgpu::Buffer,
+    buffers: [wgpu::Buffer; 2],
+    // `bind_groups[i]` reads `buffers[i]` and writes the other one
+    bind_groups: [wgpu::BindGroup; 2],
+    // Index of the buffer holding the latest state
+    current: usize,
+}
+
+impl Simulation {
+    pub fn new(device: &wgpu::Device, particles: &[Particle], params: SimulationParams) -> Self {
+        assert!(particles.len() <= MAX_PARTICLES as usize, "Error in creating simulation: too many particles");
+        let module = device.create_shader_module(wgpu::include_wgsl!("simulation.wgsl"));
+
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Storage { read_only }, has_dynamic_offset: false, min_binding_size: None },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Simulation bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: Some(SimulationParams::min_size()) },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+            ],
+        });
+
+        let params = SimulationParams { count: particles.len() as u32, ..params };
+        let params_buffer = BufferBuilder::slice_of(&params.as_wgsl_bytes().expect("Error in translating SimulationParams to wgsl bytes."))
+            .label("Simulation params")
+            .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
+            .build(device);
+
+        let mut contents = particles.to_vec();
+        contents.resize(MAX_PARTICLES as usize, Particle::zeroed());
+        let create_buffer = |label| {
+            BufferBuilder::slice_of(&contents)
+                .label(label)
+                .usage(wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC)
+                .build(device)
+        };
+        let buffers = [create_buffer("Particles A"), create_buffer("Particles B")];
+
+        let create_bind_group = |source: &wgpu::Buffer, destination: &wgpu::Buffer| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Simulation bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: source.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: destination.as_entire_binding() },
+                ],
+            })
+        };
+        let bind_groups = [create_bind_group(&buffers[0], &buffers[1]), create_bind_group(&buffers[1], &buffers[0])];
+
+        let layout = framework::PipelineLayoutBuilder::new()
+            .label("Simulation pipeline layout")
+            .add_bind_group_layout(&bind_group_layout)
+            .build(device);
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Simulation pipeline"),
+            layout: Some(&layout),
+            module: &module,
+            entry_point: "cs_main",
+        });
+
+        Simulation { params, pipeline, params_buffer, buffers, bind_groups, current: 0 }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.params.count
+    }
+
+    /** The buffer holding the latest state, laid out as `Particle`s.
+     */
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffers[self.current]
+    }
+
+    /** Adds a particle, returning false if the simulation is full.
+     */
+    pub fn spawn(&mut self, queue: &wgpu::Queue, particle: Particle) -> bool {
+        if self.params.count >= MAX_PARTICLES {
+            return false;
+        }
+        let offset = (self.params.count as usize * std::mem::size_of::<Particle>()) as wgpu::BufferAddress;
+        queue.write_buffer(self.buffer(), offset, bytemuck::bytes_of(&particle));
+        self.params.count += 1;
+        true
+    }
+
+    /** Records `steps` steps of `delta_time` seconds each.
+     */
+    pub fn step(&mut self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, delta_time: f32, steps: u32) {
+        self.step_timed(queue, encoder, delta_time, steps, None);
+    }
+
+    /** Like `step`, with the compute pass writing `timestamp_writes`, e.g. from a `GpuProfiler`.
+     */
+    pub fn step_timed(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        delta_time: f32,
+        steps: u32,
+        timestamp_writes: Option<wgpu::ComputePassTimestampWrites>,
+    ) {
+        self.params.delta_time = delta_time;
+        queue.write_buffer(&self.params_buffer, 0, &self.params.as_wgsl_bytes().expect("Error in translating SimulationParams to wgsl bytes."));
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("Simulation"), timestamp_writes });
+        cpass.set_pipeline(&self.pipeline);
+        for _ in 0..steps {
+            cpass.set_bind_group(0, &self.bind_groups[self.current], &[]);
+            cpass.dispatch_workgroups(self.params.count.div_ceil(WORKGROUP_SIZE), 1, 1);
+            self.current = 1 - self.current;
+        }
+    }
+}
@@ -0,0 +1,93 @@
+// One step of the bouncing circles simulation. Every invocation reads the whole previous state
+// and writes one particle of the next, so no particle sees a half-updated neighbour. Must stay
+// in step with `step_cpu` in simulation.rs.
+
+struct Particle {
+	center: vec2<f32>,
+	radius: f32,
+	stroke_width: f32,
+	fill_color: vec4<f32>,
+	stroke_color: vec4<f32>,
+	velocity: vec2<f32>,
+}
+
+struct Params {
+	gravity: vec2<f32>,
+	bounds: vec2<f32>,
+	delta_time: f32,
+	restitution: f32,
+	count: u32,
+}
+
+@group(0)
+@binding(0)
+var<uniform> params: Params;
+
+@group(0)
+@binding(1)
+var<storage, read> source: array<Particle>;
+
+@group(0)
+@binding(2)
+var<storage, read_write> destination: array<Particle>;
+
+@compute
+@workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+	let index = id.x;
+	if index >= params.count {
+		return;
+	}
+
+	var particle = source[index];
+	let mass = particle.radius * particle.radius;
+	var position = particle.center;
+	var velocity = particle.velocity;
+
+	// Push apart from every overlapping circle and bounce off it, each circle taking the share
+	// of the correction its partner's mass calls for
+	for (var other_index = 0u; other_index < params.count; other_index++) {
+		if other_index == index {
+			continue;
+		}
+		let other = source[other_index];
+		let offset = particle.center - other.center;
+		let distance = length(offset);
+		let overlap = particle.radius + other.radius - distance;
+		if overlap <= 0.0 || distance <= 0.0 {
+			continue;
+		}
+		let normal = offset / distance;
+		let other_mass = other.radius * other.radius;
+		let share = other_mass / (mass + other_mass);
+		position += normal * overlap * share;
+		let approach = dot(particle.velocity - other.velocity, normal);
+		if approach < 0.0 {
+			velocity -= (1.0 + params.restitution) * share * approach * normal;
+		}
+	}
+
+	velocity += params.gravity * params.delta_time;
+	position += velocity * params.delta_time;
+
+	let low = vec2(particle.radius);
+	let high = params.bounds - particle.radius;
+	if position.x < low.x {
+		position.x = low.x;
+		velocity.x = abs(velocity.x) * params.restitution;
+	} else if position.x > high.x {
+		position.x = high.x;
+		velocity.x = -abs(velocity.x) * params.restitution;
+	}
+	if position.y < low.y {
+		position.y = low.y;
+		velocity.y = abs(velocity.y) * params.restitution;
+	} else if position.y > high.y {
+		position.y = high.y;
+		velocity.y = -abs(velocity.y) * params.restitution;
+	}
+
+	particle.center = position;
+	particle.velocity = velocity;
+	destination[index] = particle;
+}
@@ -0,0 +1,145 @@
+use bytemuck::Zeroable;
+use circles::simulation::{step_cpu, Particle, Rng, Simulation, SimulationParams, MAX_PARTICLES};
+use framework::{offscreen_device, shapes::Circle};
+use glam::{vec2, Vec2};
+
+const STEPS: u32 = 8;
+const DELTA_TIME: f32 = 1.0 / 240.0;
+
+fn params(count: usize) -> SimulationParams {
+    SimulationParams {
+        gravity: vec2(0.0, 980.0),
+        bounds: vec2(400.0, 300.0),
+        delta_time: DELTA_TIME,
+        restitution: 0.8,
+        count: count as u32,
+    }
+}
+
+fn particle(center: Vec2, radius: f32, velocity: Vec2) -> Particle {
+    Particle::new(Circle::filled(center, radius, [1.0; 4]), velocity)
+}
+
+// Crowded enough that many of the particles start out overlapping
+fn scattered(count: usize) -> Vec<Particle> {
+    let mut rng = Rng::new(7);
+    (0..count)
+        .map(|_| {
+            let center = vec2(rng.range(0.0, 400.0), rng.range(0.0, 300.0));
+            rng.particle(center)
+        })
+        .collect()
+}
+
+fn read_particles(device: &wgpu::Device, queue: &wgpu::Queue, buffer: &wgpu::Buffer, count: usize) -> Vec<Particle> {
+    let size = (count * std::mem::size_of::<Particle>()) as wgpu::BufferAddress;
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, size);
+    queue.submit(Some(encoder.finish()));
+
+    staging.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let particles = bytemuck::cast_slice(&staging.slice(..).get_mapped_range()).to_vec();
+    particles
+}
+
+#[test]
+fn particle_matches_wgsl_layout() {
+    // A `vec4` member gives the WGSL struct 16 byte alignment, so its size rounds up to 64
+    assert_eq!(std::mem::size_of::<Particle>(), 64);
+}
+
+#[test]
+fn free_particle_falls_under_gravity() {
+    let mut particles = [particle(vec2(200.0, 100.0), 10.0, Vec2::ZERO)];
+    let params = params(1);
+    for _ in 0..STEPS {
+        step_cpu(&mut particles, &params);
+    }
+
+    let expected_velocity = params.gravity.y * DELTA_TIME * STEPS as f32;
+    assert!((particles[0].velocity().y - expected_velocity).abs() < 1e-3);
+    assert!(particles[0].center().y > 100.0);
+    assert_eq!(particles[0].center().x, 200.0);
+}
+
+#[test]
+fn walls_keep_particles_inside_and_bounce() {
+    let mut particles = [particle(vec2(395.0, 150.0), 10.0, vec2(500.0, 0.0))];
+    step_cpu(&mut particles, &params(1));
+
+    assert_eq!(particles[0].center().x, 390.0);
+    assert!((particles[0].velocity().x + 500.0 * 0.8).abs() < 1e-3);
+}
+
+#[test]
+fn overlapping_particles_separate_by_mass() {
+    // Equal radii, so each takes half of the 10 pixel overlap
+    let mut particles = [particle(vec2(100.0, 150.0), 10.0, vec2(50.0, 0.0)), particle(vec2(110.0, 150.0), 10.0, vec2(-50.0, 0.0))];
+    let params = SimulationParams { gravity: Vec2::ZERO, ..params(2) };
+    step_cpu(&mut particles, &params);
+
+    let gap = particles[1].center().x - particles[0].center().x;
+    assert!(gap >= 20.0 - 1e-3, "circles still overlap, gap {gap}");
+    assert!(particles[0].velocity().x < 0.0 && particles[1].velocity().x > 0.0);
+    // Momentum is conserved between equal masses
+    assert!((particles[0].velocity().x + particles[1].velocity().x).abs() < 1e-3);
+}
+
+#[test]
+fn gpu_steps_match_cpu_reference() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+
+    let mut expected = scattered(100);
+    let params = params(expected.len());
+    let mut simulation = Simulation::new(&device, &expected, params);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    simulation.step(&queue, &mut encoder, DELTA_TIME, STEPS);
+    queue.submit(Some(encoder.finish()));
+    for _ in 0..STEPS {
+        step_cpu(&mut expected, &params);
+    }
+
+    let actual = read_particles(&device, &queue, simulation.buffer(), expected.len());
+    for (index, (actual, expected)) in actual.iter().zip(&expected).enumerate() {
+        assert!(actual.center().abs_diff_eq(expected.center(), 1e-2), "particle {index}: {:?} != {:?}", actual.center(), expected.center());
+        assert!(actual.velocity().abs_diff_eq(expected.velocity(), 1e-1), "particle {index}: {:?} != {:?}", actual.velocity(), expected.velocity());
+        assert_eq!(actual.circle.radius, expected.circle.radius);
+    }
+}
+
+#[test]
+fn spawned_particles_join_the_simulation() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+
+    let mut simulation = Simulation::new(&device, &[], params(0));
+    let spawned = particle(vec2(200.0, 100.0), 10.0, Vec2::ZERO);
+    assert!(simulation.spawn(&queue, spawned));
+    assert_eq!(simulation.count(), 1);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    simulation.step(&queue, &mut encoder, DELTA_TIME, 1);
+    queue.submit(Some(encoder.finish()));
+
+    let mut expected = [spawned];
+    step_cpu(&mut expected, &simulation.params);
+    let actual = read_particles(&device, &queue, simulation.buffer(), 1);
+    assert!(actual[0].center().abs_diff_eq(expected[0].center(), 1e-3));
+
+    let full = vec![Particle::zeroed(); MAX_PARTICLES as usize];
+    let mut simulation = Simulation::new(&device, &full, params(full.len()));
+    assert!(!simulation.spawn(&queue, spawned));
+}
@@ -0,0 +1,295 @@
+/*
+   Running examples.
+
+   An example implements `App`: it creates its resources from a `WgpuContext`, reacts to window
+   events, and draws frames into whatever view it is given. `run` then takes care of the rest
+   (the window, or an offscreen target with `--headless`), so every example starts the same way
+   and can be run from the `wgpu-examples` launcher as well as on its own:
+
+   ```ignore
+   fn main() {
+       framework::app::run::<cube::Cube>();
+   }
+   ```
+
+   While an app runs, Escape closes it and F12 starts or stops recording a `ChromeTrace`, which
+   also starts from launch when `CHROME_TRACE` is set. Acquiring and presenting frames are
+   recorded by the runner; apps record their own scopes through `Frame::trace`.
+   */
+use std::{path::Path, sync::Arc};
+
+use winit::{
+    dpi::LogicalSize,
+    event::{ElementState, Event, KeyEvent, WindowEvent},
+    event_loop::EventLoop,
+    keyboard::{Key, NamedKey},
+    window::{Window, WindowBuilder},
+};
+
+use crate::{args::{RunOptions, OPTIONS_HELP}, profiler::ChromeTrace, WgpuContext};
+
+/** An example that can be run in a window or headless.
+ */
+pub trait App: Sized + 'static {
+    /** What the example is called on the command line, e.g. `wgpu-examples cube`.
+     */
+    const NAME: &'static str;
+    /** A line on what the example shows, for `wgpu-examples list`.
+     */
+    const DESCRIPTION: &'static str;
+    /** The window's title, until the app changes it.
+     */
+    const TITLE: &'static str = Self::NAME;
+    /** The window's logical size, and the size of headless frames in pixels.
+     */
+    const SIZE: (u32, u32) = (900, 900);
+
+    /** Creates the app's resources. `args` are the command line arguments meant for the app,
+     * e.g. an image to load.
+     */
+    fn new(context: &WgpuContext, args: &[String]) -> Self;
+
+    /** Called after the context has been resized to the window's new size.
+     */
+    fn resize(&mut self, _context: &WgpuContext) {}
+
+    /** Called with every event for the app's window except redraws, before the runner acts on
+     * it.
+     */
+    fn window_event(&mut self, _context: &WgpuContext, _event: &WindowEvent) {}
+
+    /** Draws a frame into `frame.view` and submits it. The runner presents it afterwards.
+     * Frames are only drawn when asked for, so apps that animate call
+     * `context.request_redraw()` for the next one.
+     */
+    fn render(&mut self, context: &WgpuContext, frame: &mut Frame);
+}
+
+/** The frame being drawn.
+ */
+pub struct Frame<'a> {
+    /** The view to draw into, in `context.swapchain_format()`.
+     */
+    pub view: &'a wgpu::TextureView,
+    /** Records scopes when a trace is being recorded, and does nothing otherwise.
+     */
+    pub trace: &'a mut ChromeTrace,
+}
+
+/** An app's name and description along with a way to run it, for listing apps without
+ * creating them.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct AppEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub run: fn(RunOptions),
+}
+
+impl AppEntry {
+    pub const fn of<A: App>() -> Self {
+        AppEntry { name: A::NAME, description: A::DESCRIPTION, run: run_with::<A> }
+    }
+}
+
+/** Runs `A` with the options on the command line, printing usage and exiting if they can't be
+ * parsed. Meant to be all an example's `main` does.
+ */
+pub fn run<A: App>() {
+    match RunOptions::parse(std::env::args().skip(1)) {
+        Ok(options) => run_with::<A>(options),
+        Err(error) => {
+            eprintln!("{error}\n\nUsage: {} [options] [arguments]\n\n{OPTIONS_HELP}", A::NAME);
+            std::process::exit(2);
+        }
+    }
+}
+
+/** Runs `A` in a window until it is closed, or headless if `options` ask for it.
+ */
+pub fn run_with<A: App>(options: RunOptions) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        // Lets a launcher set up logging itself
+        let _ = env_logger::builder().format_timestamp_nanos().try_init();
+        if options.headless {
+            run_headless::<A>(&options);
+            return;
+        }
+    }
+
+    let event_loop = EventLoop::new().expect("Error in creating the event loop");
+    #[allow(unused_mut)]
+    let mut builder = WindowBuilder::new()
+        .with_title(A::TITLE)
+        .with_inner_size(LogicalSize::new(A::SIZE.0, A::SIZE.1));
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use wasm_bindgen::JsCast;
+        use winit::platform::web::WindowBuilderExtWebSys;
+        let canvas = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .get_element_by_id("canvas")
+            .unwrap()
+            .dyn_into::<web_sys::HtmlCanvasElement>()
+            .unwrap();
+        builder = builder.with_canvas(Some(canvas));
+    }
+    let window = Arc::new(builder.build(&event_loop).expect("Error in creating the window"));
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        pollster::block_on(run_window::<A>(event_loop, window, options));
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+        console_log::init().expect("could not initialize logger");
+
+        let document = web_sys::window()
+            .and_then(|win| win.document())
+            .expect("Failed to get document.");
+        let body = document.body().unwrap();
+        let description = document
+            .create_element("p")
+            .expect("Failed to create the description as element.");
+        description.set_inner_html(A::DESCRIPTION);
+        body.append_child(&description)
+            .expect("Failed to append the description to body.");
+
+        wasm_bindgen_futures::spawn_local(run_window::<A>(event_loop, window, options));
+    }
+}
+
+async fn run_window<A: App>(event_loop: EventLoop<()>, window: Arc<Window>, options: RunOptions) {
+    let window_id = window.id();
+    let mut context = Some(WgpuContext::from_window(window).await);
+    let mut app = Some(A::new(context.as_ref().unwrap(), &options.args));
+    let mut trace = ChromeTrace::from_env();
+    let mut frames = 0;
+
+    event_loop.run(move |event, target| {
+        match event {
+            Event::LoopExiting => {
+                if let Err(error) = trace.stop() {
+                    eprintln!("Error in writing the trace to {}: {error}", trace.path().display());
+                }
+                app = None;
+                context = None;
+            }
+            Event::WindowEvent { window_id: id, event } if id == window_id => {
+                let context = context.as_mut().unwrap();
+                let app = app.as_mut().unwrap();
+                if event != WindowEvent::RedrawRequested {
+                    app.window_event(context, &event);
+                }
+
+                match event {
+                    WindowEvent::CloseRequested => {
+                        target.exit();
+                    }
+                    WindowEvent::KeyboardInput { event: KeyEvent { logical_key: Key::Named(key), state: ElementState::Pressed, .. }, .. } => {
+                        match key {
+                            NamedKey::Escape => target.exit(),
+                            NamedKey::F12 => {
+                                if let Err(error) = trace.toggle() {
+                                    eprintln!("Error in writing the trace to {}: {error}", trace.path().display());
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    WindowEvent::Resized(new_size) => {
+                        context.resize(new_size);
+                        app.resize(context);
+                    }
+                    WindowEvent::RedrawRequested => {
+                        let (surface_texture, view) = trace.scope("Acquire", || context.frame_view(&wgpu::TextureViewDescriptor::default()));
+                        app.render(context, &mut Frame { view: &view, trace: &mut trace });
+                        trace.scope("Present", || surface_texture.present());
+
+                        frames += 1;
+                        match options.frames {
+                            Some(limit) if frames >= limit => target.exit(),
+                            // Apps that only draw on demand still have to get through the frames
+                            Some(_) => context.request_redraw(),
+                            None => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }).expect("Error in running the event loop");
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn run_headless<A: App>(options: &RunOptions) {
+    let context = pollster::block_on(WgpuContext::headless(A::SIZE.0, A::SIZE.1));
+    let mut app = A::new(&context, &options.args);
+    let mut trace = ChromeTrace::from_env();
+
+    let config = &context.surface_config;
+    let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Headless frame"),
+        size: wgpu::Extent3d { width: config.width, height: config.height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: config.usage,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    for _ in 0..options.frames.unwrap_or(1) {
+        app.render(&context, &mut Frame { view: &view, trace: &mut trace });
+    }
+    if let Some(path) = &options.screenshot {
+        save_screenshot(&context, &texture, path);
+        log::info!("Saved a screenshot of {} to {}", A::NAME, path.display());
+    }
+}
+
+// Saves a headless frame, which is always in `WgpuContext::HEADLESS_FORMAT`
+#[cfg(not(target_arch = "wasm32"))]
+fn save_screenshot(context: &WgpuContext, texture: &wgpu::Texture, path: &Path) {
+    let (width, height) = (texture.width(), texture.height());
+    // Rows of a copy have to be aligned, so they are padded and then cut back down
+    let row_size = width * 4;
+    let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Screenshot buffer"),
+        size: padded_row_size as wgpu::BufferAddress * height as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = context.command_encoder();
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(padded_row_size), rows_per_image: None },
+        },
+        texture.size(),
+    );
+    context.queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.expect("Error in reading back the screenshot"));
+    context.device.poll(wgpu::Maintain::Wait);
+    let pixels: Vec<u8> = slice
+        .get_mapped_range()
+        .chunks(padded_row_size as usize)
+        .flat_map(|row| &row[..row_size as usize])
+        .copied()
+        .collect();
+
+    image::save_buffer(path, &pixels, width, height, image::ColorType::Rgba8)
+        .unwrap_or_else(|error| panic!("Error in saving the screenshot to {}: {error}", path.display()));
+}
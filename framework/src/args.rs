@@ -0,0 +1,143 @@
+/*
+   Command line options understood by every example.
+
+   `--trace <dir>` (or `--trace=<dir>`, or the `WGPU_TRACE` environment variable) records a wgpu
+   API trace of the device into `<dir>`, for replaying with wgpu's player or attaching to a bug
+   report. Recording needs the `trace` feature, which each example forwards to wgpu:
+
+   ```sh
+   cargo run -p cube --features trace -- --trace trace
+   ```
+
+   Examples run through `framework::app` also understand `RunOptions`: `--headless` draws
+   offscreen instead of opening a window, `--frames <n>` stops after n frames, and
+   `--screenshot <png>` saves the last headless frame. Examples that take arguments of their own
+   get them without any of these options in the way.
+   */
+use std::path::PathBuf;
+
+pub const TRACE_OPTION: &str = "--trace";
+pub const TRACE_ENV: &str = "WGPU_TRACE";
+
+/** The options every example understands, for usage messages.
+ */
+pub const OPTIONS_HELP: &str = "\
+Options:
+  --headless           Draw offscreen instead of opening a window
+  --frames <n>         Stop after drawing n frames (one when headless)
+  --screenshot <png>   Save the last frame drawn headless
+  --trace <dir>        Record a wgpu API trace into dir, with the `trace` feature
+  --                   Pass everything after it to the example";
+
+/** The directory to record an API trace into, if one was asked for and the `trace` feature is
+ * enabled. The directory is created if it doesn't exist yet.
+ */
+pub fn trace_path() -> Option<PathBuf> {
+    let path = parse_trace_path(std::env::args().skip(1)).or_else(|| {
+        std::env::var_os(TRACE_ENV).filter(|path| !path.is_empty()).map(PathBuf::from)
+    })?;
+    if cfg!(feature = "trace") {
+        std::fs::create_dir_all(&path).expect("Error in creating the API trace directory");
+        log::info!("Recording an API trace into {}", path.display());
+        Some(path)
+    } else {
+        log::warn!("Not recording an API trace into {}: built without the `trace` feature", path.display());
+        None
+    }
+}
+
+/** The trace directory given by `--trace` in `args`, which shouldn't include the program name.
+ * Arguments after `--` belong to the example and aren't looked at.
+ */
+pub fn parse_trace_path<I: IntoIterator<Item = String>>(args: I) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == TRACE_OPTION {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix(TRACE_OPTION).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/** The example's own command line arguments, without the program name and the framework's
+ * options.
+ */
+pub fn example_args() -> Vec<String> {
+    strip_framework_options(std::env::args().skip(1))
+}
+
+/** `args` without the framework's options and their values. `--` and everything after it is kept
+ * as it is.
+ */
+pub fn strip_framework_options<I: IntoIterator<Item = String>>(args: I) -> Vec<String> {
+    let mut args = args.into_iter();
+    let mut remaining = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            remaining.push(arg);
+            remaining.extend(args.by_ref());
+        } else if arg == TRACE_OPTION {
+            args.next();
+        } else if !arg.starts_with(&format!("{TRACE_OPTION}=")) {
+            remaining.push(arg);
+        }
+    }
+    remaining
+}
+
+/** How to run an example, parsed from its command line.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunOptions {
+    pub headless: bool,
+    /** Frames to draw before exiting. Windows stay open until closed without it, while headless
+     * runs draw a single frame.
+     */
+    pub frames: Option<u32>,
+    pub screenshot: Option<PathBuf>,
+    /** The arguments meant for the example itself.
+     */
+    pub args: Vec<String>,
+}
+
+impl RunOptions {
+    /** Parses `args`, which shouldn't include the program name. Arguments that aren't options,
+     * and everything after `--`, are left for the example.
+     */
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = strip_framework_options(args).into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_owned())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next()).ok_or_else(|| format!("{name} needs a value"));
+            match name {
+                "--" => {
+                    options.args.extend(args.by_ref());
+                }
+                "--headless" => options.headless = true,
+                "--frames" => {
+                    let frames = value()?;
+                    match frames.parse() {
+                        Ok(0) | Err(_) => return Err(format!("--frames needs a positive number of frames, not {frames:?}")),
+                        Ok(frames) => options.frames = Some(frames),
+                    }
+                }
+                "--screenshot" => options.screenshot = Some(PathBuf::from(value()?)),
+                _ => options.args.push(arg),
+            }
+        }
+        if options.screenshot.is_some() && !options.headless {
+            return Err("--screenshot only works with --headless".to_owned());
+        }
+        Ok(options)
+    }
+}
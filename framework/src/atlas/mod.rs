@@ -0,0 +1,289 @@
+/*
+   A texture atlas that packs many small images into one growable GPU texture, so they can be
+   drawn with a single bind group.
+
+   Packing happens on the CPU with `ShelfPacker`. The atlas keeps a copy of every entry's texels,
+   which lets it grow or repack without reading the texture back, and only writes the regions
+   that changed since the last `upload`. Each region includes the entry's padding, cleared, so
+   an entry placed where a removed one was doesn't pick up its texels when filtered.
+   */
+pub mod packer;
+
+pub use packer::*;
+
+use std::collections::HashMap;
+
+use glam::Vec2;
+
+use crate::TextureBuilder;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AtlasId(u32);
+
+/** Texture coordinates of an entry, `min` at the top left.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UvRect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+struct AtlasEntry {
+    rect: AtlasRect,
+    texels: Vec<u8>,
+}
+
+pub struct TextureAtlas {
+    packer: ShelfPacker,
+    format: wgpu::TextureFormat,
+    bytes_per_texel: u32,
+    // Empty texels kept around each entry so filtering doesn't bleed between neighbours
+    padding: u32,
+    max_size: u32,
+    entries: HashMap<AtlasId, AtlasEntry>,
+    next_id: u32,
+    // Set by removals, which can leave free space too scattered to use until a repack
+    fragmented: bool,
+    dirty: Vec<AtlasId>,
+    texture: Option<wgpu::Texture>,
+    view: Option<wgpu::TextureView>,
+}
+
+impl TextureAtlas {
+    /** Creates an empty `size` x `size` atlas. `format` must be uncompressed, e.g. `R8Unorm` for
+     * glyph coverage or `Rgba8UnormSrgb` for sprites.
+     */
+    pub fn new(size: u32, format: wgpu::TextureFormat) -> Self {
+        let bytes_per_texel = format.block_copy_size(None).expect("Error in creating atlas: the format has no single texel size");
+        assert_eq!(format.block_dimensions(), (1, 1), "Error in creating atlas: compressed formats can't be packed");
+        TextureAtlas {
+            packer: ShelfPacker::new(size, size),
+            format,
+            bytes_per_texel,
+            padding: 1,
+            max_size: 8192,
+            entries: HashMap::new(),
+            next_id: 0,
+            fragmented: false,
+            dirty: Vec::new(),
+            texture: None,
+            view: None,
+        }
+    }
+
+    /** Sets the largest size the atlas may grow to, typically `Limits::max_texture_dimension_2d`.
+     */
+    pub fn with_max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.packer.width(), self.packer.height())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /** Adds an image of tightly packed texels in the atlas format, growing the atlas if there is
+     * no room. Returns `None` if it still doesn't fit at the maximum size.
+     */
+    pub fn insert(&mut self, width: u32, height: u32, texels: &[u8]) -> Option<AtlasId> {
+        assert_eq!(texels.len(), (width * height * self.bytes_per_texel) as usize, "Error in inserting into atlas: texel data doesn't match the size");
+        let rect = self.allocate(width, height)?;
+        let id = AtlasId(self.next_id);
+        self.next_id += 1;
+        self.entries.insert(id, AtlasEntry { rect, texels: texels.to_vec() });
+        self.dirty.push(id);
+        Some(id)
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<AtlasRect> {
+        let (padded_width, padded_height) = (width + 2 * self.padding, height + 2 * self.padding);
+        loop {
+            if let Some(rect) = self.packer.allocate(padded_width, padded_height) {
+                return Some(AtlasRect::new(rect.x + self.padding, rect.y + self.padding, width, height));
+            }
+            if self.fragmented && self.repack() {
+                continue;
+            }
+            if !self.grow() {
+                return None;
+            }
+        }
+    }
+
+    // Doubles the shorter side, returning false once the maximum size is reached
+    fn grow(&mut self) -> bool {
+        let (width, height) = self.size();
+        let (width, height) = if width <= height { (width * 2, height) } else { (width, height * 2) };
+        if width > self.max_size || height > self.max_size {
+            return false;
+        }
+        self.packer.grow(width, height);
+        // The texture is recreated on the next upload, so everything has to be written again
+        self.dirty = self.entries.keys().copied().collect();
+        true
+    }
+
+    pub fn remove(&mut self, id: AtlasId) -> bool {
+        let Some(entry) = self.entries.remove(&id) else {
+            return false;
+        };
+        self.packer.deallocate(self.padded(entry.rect));
+        self.dirty.retain(|&dirty| dirty != id);
+        self.fragmented = true;
+        true
+    }
+
+    fn padded(&self, rect: AtlasRect) -> AtlasRect {
+        AtlasRect::new(rect.x - self.padding, rect.y - self.padding, rect.width + 2 * self.padding, rect.height + 2 * self.padding)
+    }
+
+    /** Packs every entry again from scratch, tallest first, which reclaims space fragmented by
+     * removals. Entries keep their ids but usually move, so UVs must be looked up again. Returns
+     * false, leaving the atlas unchanged, if the entries no longer fit.
+     */
+    pub fn repack(&mut self) -> bool {
+        let mut ids: Vec<AtlasId> = self.entries.keys().copied().collect();
+        ids.sort_by_key(|id| (std::cmp::Reverse(self.entries[id].rect.height), *id));
+
+        let mut packer = ShelfPacker::new(self.packer.width(), self.packer.height());
+        let mut rects = Vec::with_capacity(ids.len());
+        for id in &ids {
+            let rect = self.padded(self.entries[id].rect);
+            match packer.allocate(rect.width, rect.height) {
+                Some(packed) => rects.push(AtlasRect::new(packed.x + self.padding, packed.y + self.padding, self.entries[id].rect.width, self.entries[id].rect.height)),
+                None => return false,
+            }
+        }
+
+        self.packer = packer;
+        for (id, rect) in ids.iter().zip(rects) {
+            self.entries.get_mut(id).unwrap().rect = rect;
+        }
+        self.dirty = ids;
+        self.fragmented = false;
+        // Stale texels would show through the padding of entries that moved
+        self.texture = None;
+        self.view = None;
+        true
+    }
+
+    /** Where an entry sits in the atlas, in texels.
+     */
+    pub fn rect(&self, id: AtlasId) -> Option<AtlasRect> {
+        self.entries.get(&id).map(|entry| entry.rect)
+    }
+
+    /** Where an entry sits in the atlas, in texture coordinates. These change when the atlas
+     * grows or is repacked.
+     */
+    pub fn uv_rect(&self, id: AtlasId) -> Option<UvRect> {
+        let rect = self.rect(id)?;
+        let size = Vec2::new(self.packer.width() as f32, self.packer.height() as f32);
+        Some(UvRect {
+            min: Vec2::new(rect.x as f32, rect.y as f32) / size,
+            max: Vec2::new(rect.right() as f32, rect.bottom() as f32) / size,
+        })
+    }
+
+    /** The regions the next `upload` will write, padding included, or the whole atlas if the
+     * texture has to be recreated first.
+     */
+    pub fn dirty_regions(&self) -> Vec<AtlasRect> {
+        if self.needs_texture() {
+            let (width, height) = self.size();
+            return vec![AtlasRect::new(0, 0, width, height)];
+        }
+        self.dirty.iter().map(|id| self.padded(self.entries[id].rect)).collect()
+    }
+
+    fn needs_texture(&self) -> bool {
+        match &self.texture {
+            Some(texture) => (texture.width(), texture.height()) != self.size(),
+            None => true,
+        }
+    }
+
+    /** Writes changed entries, surrounded by cleared padding, to the GPU. Returns true if the
+     * texture was (re)created, in which case bind groups using the old view must be rebuilt.
+     */
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        let recreated = self.needs_texture();
+        if recreated {
+            let (width, height) = self.size();
+            let texture = TextureBuilder::new(width, height)
+                .label("Texture atlas")
+                .format(self.format)
+                // Copyable so its contents can be read back and inspected
+                .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC)
+                .build(device);
+            self.view = Some(texture.create_view(&wgpu::TextureViewDescriptor::default()));
+            self.texture = Some(texture);
+            self.dirty = self.entries.keys().copied().collect();
+        }
+
+        let texture = self.texture.as_ref().unwrap();
+        for id in std::mem::take(&mut self.dirty) {
+            let entry = &self.entries[&id];
+            let rect = self.padded(entry.rect);
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: rect.x, y: rect.y, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &self.padded_texels(entry),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(rect.width * self.bytes_per_texel),
+                    rows_per_image: None,
+                },
+                wgpu::Extent3d { width: rect.width, height: rect.height, depth_or_array_layers: 1 },
+            );
+        }
+        recreated
+    }
+
+    // The entry's texels in the middle of its padded rect, with zeroes all around
+    fn padded_texels(&self, entry: &AtlasEntry) -> Vec<u8> {
+        let bytes_per_texel = self.bytes_per_texel as usize;
+        let padding = self.padding as usize;
+        let row_size = entry.rect.width as usize * bytes_per_texel;
+        let padded_row_size = row_size + 2 * padding * bytes_per_texel;
+        let mut texels = vec![0; padded_row_size * (entry.rect.height as usize + 2 * padding)];
+        if row_size > 0 {
+            for (row, source) in entry.texels.chunks_exact(row_size).enumerate() {
+                let start = (row + padding) * padded_row_size + padding * bytes_per_texel;
+                texels[start..start + row_size].copy_from_slice(source);
+            }
+        }
+        texels
+    }
+
+    /** The atlas texture, available after the first `upload`.
+     */
+    pub fn texture(&self) -> Option<&wgpu::Texture> {
+        self.texture.as_ref()
+    }
+
+    pub fn view(&self) -> Option<&wgpu::TextureView> {
+        self.view.as_ref()
+    }
+}
@@ -0,0 +1,159 @@
+/** A rectangle in texels, with its origin at the top left.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AtlasRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl AtlasRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        AtlasRect { x, y, width, height }
+    }
+
+    pub fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    pub fn intersects(&self, other: &AtlasRect) -> bool {
+        self.x < other.right() && other.x < self.right() && self.y < other.bottom() && other.y < self.bottom()
+    }
+}
+
+// A row of the atlas; entries are placed side by side along it
+#[derive(Clone, Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    // Unused horizontal spans as (x, width), sorted by x and never adjacent
+    free: Vec<(u32, u32)>,
+}
+
+impl Shelf {
+    fn is_empty(&self, width: u32) -> bool {
+        self.free == [(0, width)]
+    }
+
+    fn release(&mut self, x: u32, width: u32) {
+        let index = self.free.partition_point(|&(free_x, _)| free_x < x);
+        self.free.insert(index, (x, width));
+        // Merge with the following span, then the preceding one
+        if index + 1 < self.free.len() && x + width == self.free[index + 1].0 {
+            self.free[index].1 += self.free.remove(index + 1).1;
+        }
+        if index > 0 && self.free[index - 1].0 + self.free[index - 1].1 == x {
+            self.free[index - 1].1 += self.free.remove(index).1;
+        }
+    }
+}
+
+/** Shelf packing: the area is split into horizontal shelves, each as tall as the first entry
+ * placed on it, and entries fill shelves left to right.
+ *
+ * Shelf packing wastes some space above entries shorter than their shelf, but allocation and
+ * removal are cheap and freed space is reused, which suits atlases whose contents change at
+ * runtime such as glyph caches. Entries taller than any shelf with room start a new shelf.
+ */
+#[derive(Clone, Debug)]
+pub struct ShelfPacker {
+    width: u32,
+    height: u32,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        ShelfPacker { width, height, shelves: Vec::new() }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shelves.is_empty()
+    }
+
+    /** Finds room for a `width` x `height` rectangle, or returns `None` if the area is full.
+     */
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<AtlasRect> {
+        if width == 0 || height == 0 || width > self.width || height > self.height {
+            return None;
+        }
+
+        // Best fit: the shelf that wastes the least height
+        let mut best: Option<(usize, usize, u32)> = None;
+        for (shelf_index, shelf) in self.shelves.iter().enumerate() {
+            if shelf.height < height {
+                continue;
+            }
+            let waste = shelf.height - height;
+            if best.is_some_and(|(_, _, best_waste)| best_waste <= waste) {
+                continue;
+            }
+            if let Some(span_index) = shelf.free.iter().position(|&(_, free_width)| free_width >= width) {
+                best = Some((shelf_index, span_index, waste));
+            }
+        }
+
+        if let Some((shelf_index, span_index, _)) = best {
+            let shelf = &mut self.shelves[shelf_index];
+            let (x, free_width) = shelf.free[span_index];
+            if free_width == width {
+                shelf.free.remove(span_index);
+            } else {
+                shelf.free[span_index] = (x + width, free_width - width);
+            }
+            return Some(AtlasRect::new(x, shelf.y, width, height));
+        }
+
+        let y = self.shelves.last().map_or(0, |shelf| shelf.y + shelf.height);
+        if y + height > self.height {
+            return None;
+        }
+        let free = if width < self.width { vec![(width, self.width - width)] } else { Vec::new() };
+        self.shelves.push(Shelf { y, height, free });
+        Some(AtlasRect::new(0, y, width, height))
+    }
+
+    /** Returns a rectangle from `allocate` to the free space. Shelves left empty at the bottom of
+     * the area are removed so their height can be reused by taller entries.
+     */
+    pub fn deallocate(&mut self, rect: AtlasRect) {
+        let Some(shelf) = self.shelves.iter_mut().find(|shelf| shelf.y == rect.y) else {
+            return;
+        };
+        shelf.release(rect.x, rect.width);
+
+        while self.shelves.last().is_some_and(|shelf| shelf.is_empty(self.width)) {
+            self.shelves.pop();
+        }
+    }
+
+    /** Enlarges the area, keeping every existing allocation where it is.
+     */
+    pub fn grow(&mut self, width: u32, height: u32) {
+        assert!(width >= self.width && height >= self.height, "Error in growing atlas: the packer can't shrink");
+        if width > self.width {
+            for shelf in &mut self.shelves {
+                shelf.release(self.width, width - self.width);
+            }
+        }
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn clear(&mut self) {
+        self.shelves.clear();
+    }
+}
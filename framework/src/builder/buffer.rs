@@ -12,6 +12,15 @@ impl BufferBuilder {
 
     }
 
+    pub fn sized<'a>(size: wgpu::BufferAddress) -> SizedBufferBuilder<'a> {
+        SizedBufferBuilder { 
+            label: None, 
+            size, 
+            usage: None, 
+            mapped_at_creation: None 
+        }
+    }
+
     pub fn bytes_of<'a, Type: bytemuck::Pod>(contents: &'a Type) -> ContentsBufferBuilder<'a> {
         ContentsBufferBuilder {
             contents: bytemuck::bytes_of(contents),
@@ -1,7 +1,9 @@
 pub mod buffer;
 pub mod pipelinelayout;
 pub mod renderpass;
+pub mod texture;
 
 pub use buffer::*;
 pub use pipelinelayout::*;
 pub use renderpass::*;
+pub use texture::*;
@@ -1,12 +1,16 @@
 pub struct RenderPassBuilder<'tex> {
-    color_attachments: Vec<Option<wgpu::RenderPassColorAttachment<'tex>>>
+    color_attachments: Vec<Option<wgpu::RenderPassColorAttachment<'tex>>>,
+    depth_stencil_attachment: Option<wgpu::RenderPassDepthStencilAttachment<'tex>>,
+    timestamp_writes: Option<wgpu::RenderPassTimestampWrites<'tex>>,
 }
 
 
 impl<'tex> RenderPassBuilder<'tex> {
     pub fn new() -> Self {
         Self { 
-            color_attachments: Vec::new()
+            color_attachments: Vec::new(),
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
         }
     }
 
@@ -24,12 +28,48 @@ impl<'tex> RenderPassBuilder<'tex> {
         self
     }
 
+    /** Draws over what is already in `view`, for passes that overlay earlier ones.
+     */
+    pub fn load(mut self, view: &'tex wgpu::TextureView) -> Self {
+        let attachment = wgpu::RenderPassColorAttachment::<'tex> {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            }
+        };
+
+        self.color_attachments.push(Some(attachment));
+        self
+    }
+
+    pub fn depth(mut self, view: &'tex wgpu::TextureView, clear: f32) -> Self {
+        self.depth_stencil_attachment = Some(wgpu::RenderPassDepthStencilAttachment {
+            view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(clear),
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: None,
+        });
+        self
+    }
+
+    /** Writes timestamps as the pass begins and ends, e.g. from `GpuProfiler::render_timestamp_writes`.
+     * Passing `None` leaves the pass untimed.
+     */
+    pub fn timestamp_writes(mut self, timestamp_writes: impl Into<Option<wgpu::RenderPassTimestampWrites<'tex>>>) -> Self {
+        self.timestamp_writes = timestamp_writes.into();
+        self
+    }
+
     pub fn build(self, encoder: &'tex mut wgpu::CommandEncoder) -> wgpu::RenderPass {
             encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &self.color_attachments,
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
+                depth_stencil_attachment: self.depth_stencil_attachment,
+                timestamp_writes: self.timestamp_writes,
                 occlusion_query_set: None
             })
     }
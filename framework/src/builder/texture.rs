@@ -0,0 +1,263 @@
+pub struct TextureBuilder<'a> {
+    label: Option<&'a str>,
+    size: wgpu::Extent3d,
+    format: Option<wgpu::TextureFormat>,
+    usage: Option<wgpu::TextureUsages>,
+    mip_level_count: Option<u32>,
+    sample_count: Option<u32>,
+    dimension: Option<wgpu::TextureDimension>,
+    view_formats: &'a [wgpu::TextureFormat],
+}
+
+impl<'a> TextureBuilder<'a> {
+    /** A single layer 2D texture, sampled as `Rgba8UnormSrgb` unless a format is given.
+     */
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::sized(wgpu::Extent3d { width, height, depth_or_array_layers: 1 })
+    }
+
+    pub fn sized(size: wgpu::Extent3d) -> Self {
+        Self {
+            label: None,
+            size,
+            format: None,
+            usage: None,
+            mip_level_count: None,
+            sample_count: None,
+            dimension: None,
+            view_formats: &[],
+        }
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /** Sets the number of array layers, or the depth for a 3D texture.
+     */
+    pub fn array_layers(mut self, layers: u32) -> Self {
+        self.size.depth_or_array_layers = layers;
+        self
+    }
+
+    pub fn format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn usage(mut self, usage: wgpu::TextureUsages) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    pub fn mip_level_count(mut self, mip_level_count: u32) -> Self {
+        self.mip_level_count = Some(mip_level_count);
+        self
+    }
+
+    /** Allocates every mip level down to 1x1. Only the first is filled by `build_with_data`, see
+     * `mipmap::MipmapGenerator` for the rest.
+     */
+    pub fn full_mip_chain(mut self) -> Self {
+        self.mip_level_count = Some(crate::mipmap::mip_level_count(self.size.width, self.size.height));
+        self
+    }
+
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = Some(sample_count);
+        self
+    }
+
+    pub fn dimension(mut self, dimension: wgpu::TextureDimension) -> Self {
+        self.dimension = Some(dimension);
+        self
+    }
+
+    /** Other formats views of the texture may use, which must differ from the texture's own
+     * format only in being sRGB or not.
+     */
+    pub fn view_formats(mut self, view_formats: &'a [wgpu::TextureFormat]) -> Self {
+        self.view_formats = view_formats;
+        self
+    }
+
+    pub fn build(self, device: &wgpu::Device) -> wgpu::Texture {
+        device.create_texture(
+            &wgpu::TextureDescriptor {
+                label: self.label,
+                size: self.size,
+                mip_level_count: self.mip_level_count.unwrap_or(1),
+                sample_count: self.sample_count.unwrap_or(1),
+                dimension: self.dimension.unwrap_or(wgpu::TextureDimension::D2),
+                format: self.format.unwrap_or(wgpu::TextureFormat::Rgba8UnormSrgb),
+                usage: self.usage.unwrap_or(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST),
+                view_formats: self.view_formats,
+            }
+            )
+    }
+
+    /** Builds the texture and fills its first mip level with `data`, tightly packed rows of
+     * texels.
+     */
+    pub fn build_with_data(self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8]) -> wgpu::Texture {
+        let texture = self.build(device);
+        let size = texture.size();
+        let block_size = texture.format().block_copy_size(None).expect("Error in uploading texture data: the format has no single block size");
+        let (block_width, block_height) = texture.format().block_dimensions();
+        queue.write_texture(
+            texture.as_image_copy(),
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(size.width.div_ceil(block_width) * block_size),
+                rows_per_image: Some(size.height.div_ceil(block_height)),
+            },
+            size,
+        );
+        texture
+    }
+}
+
+pub struct SamplerBuilder<'a> {
+    label: Option<&'a str>,
+    address_mode_u: wgpu::AddressMode,
+    address_mode_v: wgpu::AddressMode,
+    address_mode_w: wgpu::AddressMode,
+    mag_filter: wgpu::FilterMode,
+    min_filter: wgpu::FilterMode,
+    mipmap_filter: wgpu::FilterMode,
+    lod_clamp: Option<(f32, f32)>,
+    compare: Option<wgpu::CompareFunction>,
+    anisotropy_clamp: Option<u16>,
+    border_color: Option<wgpu::SamplerBorderColor>,
+}
+
+impl<'a> SamplerBuilder<'a> {
+    /** A clamped, nearest-filtered sampler, the same as `wgpu::SamplerDescriptor::default()`.
+     */
+    pub fn new() -> Self {
+        Self {
+            label: None,
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_clamp: None,
+            compare: None,
+            anisotropy_clamp: None,
+            border_color: None,
+        }
+    }
+
+    /** A repeating sampler with linear filtering, including between mip levels.
+     */
+    pub fn linear() -> Self {
+        Self::new()
+            .address_mode(wgpu::AddressMode::Repeat)
+            .filter(wgpu::FilterMode::Linear)
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /** Sets the address mode along all three axes.
+     */
+    pub fn address_mode(self, mode: wgpu::AddressMode) -> Self {
+        self.address_mode_u(mode)
+            .address_mode_v(mode)
+            .address_mode_w(mode)
+    }
+
+    pub fn address_mode_u(mut self, mode: wgpu::AddressMode) -> Self {
+        self.address_mode_u = mode;
+        self
+    }
+
+    pub fn address_mode_v(mut self, mode: wgpu::AddressMode) -> Self {
+        self.address_mode_v = mode;
+        self
+    }
+
+    pub fn address_mode_w(mut self, mode: wgpu::AddressMode) -> Self {
+        self.address_mode_w = mode;
+        self
+    }
+
+    /** Sets the magnification, minification and mipmap filters together.
+     */
+    pub fn filter(self, filter: wgpu::FilterMode) -> Self {
+        self.mag_filter(filter)
+            .min_filter(filter)
+            .mipmap_filter(filter)
+    }
+
+    pub fn mag_filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.mag_filter = filter;
+        self
+    }
+
+    pub fn min_filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn mipmap_filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.mipmap_filter = filter;
+        self
+    }
+
+    pub fn lod_clamp(mut self, min: f32, max: f32) -> Self {
+        self.lod_clamp = Some((min, max));
+        self
+    }
+
+    /** Makes this a comparison sampler, as used for shadow maps.
+     */
+    pub fn compare(mut self, compare: wgpu::CompareFunction) -> Self {
+        self.compare = Some(compare);
+        self
+    }
+
+    /** Enables anisotropic filtering, which wgpu only allows when every filter is linear.
+     */
+    pub fn anisotropy_clamp(mut self, anisotropy_clamp: u16) -> Self {
+        self.anisotropy_clamp = Some(anisotropy_clamp);
+        self
+    }
+
+    pub fn border_color(mut self, border_color: wgpu::SamplerBorderColor) -> Self {
+        self.border_color = Some(border_color);
+        self
+    }
+
+    pub fn build(self, device: &wgpu::Device) -> wgpu::Sampler {
+        let (lod_min_clamp, lod_max_clamp) = self.lod_clamp.unwrap_or((0.0, 32.0));
+        device.create_sampler(
+            &wgpu::SamplerDescriptor {
+                label: self.label,
+                address_mode_u: self.address_mode_u,
+                address_mode_v: self.address_mode_v,
+                address_mode_w: self.address_mode_w,
+                mag_filter: self.mag_filter,
+                min_filter: self.min_filter,
+                mipmap_filter: self.mipmap_filter,
+                lod_min_clamp,
+                lod_max_clamp,
+                compare: self.compare,
+                anisotropy_clamp: self.anisotropy_clamp.unwrap_or(1),
+                border_color: self.border_color,
+            }
+            )
+    }
+}
+
+impl Default for SamplerBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
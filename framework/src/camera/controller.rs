@@ -0,0 +1,273 @@
+use glam::{Vec2, Vec3};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use super::{Camera, Projection};
+
+// Keep the camera from flipping over the poles
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+// Pixels of scrolling treated as one line by trackpads reporting `PixelDelta`
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/** Turns winit window events into camera movement.
+ *
+ * Events are fed in as they arrive and accumulated; the camera itself is only modified in
+ * `update`, which should be called once per frame with the frame's delta time.
+ */
+pub trait CameraController {
+    /// Returns `true` if the event was used to move the camera
+    fn handle_event(&mut self, event: &WindowEvent) -> bool;
+
+    fn update(&mut self, camera: &mut Camera, delta_seconds: f32);
+}
+
+// Accumulates cursor movement while a mouse button is held, along with scrolling
+#[derive(Default)]
+struct MouseDrag {
+    button: Option<MouseButton>,
+    dragging: bool,
+    last_position: Option<PhysicalPosition<f64>>,
+    delta: Vec2,
+    scroll: f32,
+}
+
+impl MouseDrag {
+    fn with_button(button: MouseButton) -> Self {
+        MouseDrag { button: Some(button), ..Default::default() }
+    }
+
+    fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseInput { state, button, .. } if Some(*button) == self.button => {
+                self.dragging = *state == ElementState::Pressed;
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let moved = match self.last_position {
+                    Some(last) if self.dragging => {
+                        self.delta += Vec2::new((position.x - last.x) as f32, (position.y - last.y) as f32);
+                        true
+                    }
+                    _ => false,
+                };
+                self.last_position = Some(*position);
+                moved
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.last_position = None;
+                false
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+                };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Returns and resets the drag and scroll accumulated since the last call
+    fn take(&mut self) -> (Vec2, f32) {
+        let taken = (self.delta, self.scroll);
+        self.delta = Vec2::ZERO;
+        self.scroll = 0.0;
+        taken
+    }
+}
+
+/** Rotates the camera around its target by dragging with the left mouse button and moves it
+ * closer or further away with the scroll wheel.
+ */
+pub struct OrbitController {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    // Radians per pixel dragged
+    pub rotate_speed: f32,
+    // Fraction of the distance covered per line scrolled
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    drag: MouseDrag,
+}
+
+impl OrbitController {
+    pub fn new(camera: &Camera) -> Self {
+        let offset = camera.eye - camera.target;
+        let distance = offset.length().max(f32::EPSILON);
+        OrbitController {
+            yaw: offset.x.atan2(offset.z),
+            pitch: (offset.y / distance).clamp(-1.0, 1.0).asin(),
+            distance,
+            rotate_speed: 0.01,
+            zoom_speed: 0.1,
+            min_distance: 0.1,
+            drag: MouseDrag::with_button(MouseButton::Left),
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        self.drag.handle_event(event)
+    }
+
+    fn update(&mut self, camera: &mut Camera, _delta_seconds: f32) {
+        let (delta, scroll) = self.drag.take();
+        self.yaw -= delta.x * self.rotate_speed;
+        self.pitch = (self.pitch + delta.y * self.rotate_speed).clamp(-MAX_PITCH, MAX_PITCH);
+        self.distance = (self.distance * (1.0 - scroll * self.zoom_speed)).max(self.min_distance);
+
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let offset = Vec3::new(sin_yaw * cos_pitch, sin_pitch, cos_yaw * cos_pitch) * self.distance;
+        camera.eye = camera.target + offset;
+    }
+}
+
+// Editors for the debug UI, the camera follows on the next `update`. Yaw keeps growing as the
+// camera is dragged around, so it isn't limited to a slider's range.
+#[cfg(feature = "egui")]
+crate::impl_inspect!(OrbitController {
+    yaw,
+    pitch: -MAX_PITCH..=MAX_PITCH,
+    #[log] distance: 0.1..=100.0,
+    #[log] rotate_speed: 0.001..=0.1,
+    zoom_speed: 0.01..=0.5,
+});
+
+/** A first person camera: WASD moves, Q and E move down and up, and dragging with the right
+ * mouse button looks around. Scrolling changes the movement speed.
+ */
+pub struct FlyController {
+    pub yaw: f32,
+    pub pitch: f32,
+    // World units per second
+    pub speed: f32,
+    // Radians per pixel dragged
+    pub sensitivity: f32,
+    // Forward, right and up movement, each in -1..=1
+    movement: Vec3,
+    pressed: [bool; 6],
+    drag: MouseDrag,
+}
+
+impl FlyController {
+    pub fn new(camera: &Camera) -> Self {
+        let forward = camera.forward();
+        FlyController {
+            yaw: forward.x.atan2(-forward.z),
+            pitch: forward.y.clamp(-1.0, 1.0).asin(),
+            speed: 4.0,
+            sensitivity: 0.004,
+            movement: Vec3::ZERO,
+            pressed: [false; 6],
+            drag: MouseDrag::with_button(MouseButton::Right),
+        }
+    }
+
+    fn key_index(code: KeyCode) -> Option<usize> {
+        match code {
+            KeyCode::KeyW => Some(0),
+            KeyCode::KeyS => Some(1),
+            KeyCode::KeyD => Some(2),
+            KeyCode::KeyA => Some(3),
+            KeyCode::KeyE => Some(4),
+            KeyCode::KeyQ => Some(5),
+            _ => None,
+        }
+    }
+}
+
+impl CameraController for FlyController {
+    fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        if let WindowEvent::KeyboardInput { event: KeyEvent { physical_key: PhysicalKey::Code(code), state, .. }, .. } = event {
+            if let Some(index) = FlyController::key_index(*code) {
+                self.pressed[index] = *state == ElementState::Pressed;
+                let axis = |positive: usize, negative: usize| {
+                    self.pressed[positive] as i32 as f32 - self.pressed[negative] as i32 as f32
+                };
+                self.movement = Vec3::new(axis(0, 1), axis(2, 3), axis(4, 5));
+                return true;
+            }
+            return false;
+        }
+        self.drag.handle_event(event)
+    }
+
+    fn update(&mut self, camera: &mut Camera, delta_seconds: f32) {
+        let (delta, scroll) = self.drag.take();
+        self.yaw += delta.x * self.sensitivity;
+        self.pitch = (self.pitch - delta.y * self.sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        self.speed = (self.speed * 1.1f32.powf(scroll)).max(0.01);
+
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let forward = Vec3::new(sin_yaw * cos_pitch, sin_pitch, -cos_yaw * cos_pitch);
+        let right = forward.cross(camera.up).normalize_or_zero();
+
+        let velocity = forward * self.movement.x + right * self.movement.y + camera.up * self.movement.z;
+        camera.eye += velocity.normalize_or_zero() * self.speed * delta_seconds;
+        camera.target = camera.eye + forward;
+    }
+}
+
+/** Slides the camera parallel to the view plane by dragging with the left mouse button and
+ * zooms with the scroll wheel, for looking at flat scenes. Orthographic cameras zoom by
+ * shrinking the view volume, perspective ones by moving towards the target.
+ */
+pub struct PanZoomController {
+    // Fraction of the view covered per line scrolled
+    pub zoom_speed: f32,
+    viewport_height: f32,
+    drag: MouseDrag,
+}
+
+impl PanZoomController {
+    pub fn new(viewport_size: winit::dpi::PhysicalSize<u32>) -> Self {
+        PanZoomController {
+            zoom_speed: 0.1,
+            viewport_height: viewport_size.height.max(1) as f32,
+            drag: MouseDrag::with_button(MouseButton::Left),
+        }
+    }
+
+    // The height of the view in world units at the target's depth
+    fn view_height(camera: &Camera) -> f32 {
+        match camera.projection {
+            Projection::Perspective { fovy, .. } => 2.0 * (fovy / 2.0).tan() * (camera.target - camera.eye).length(),
+            Projection::Orthographic { height, .. } => height,
+        }
+    }
+}
+
+impl CameraController for PanZoomController {
+    fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        if let WindowEvent::Resized(new_size) = event {
+            self.viewport_height = new_size.height.max(1) as f32;
+            return false;
+        }
+        self.drag.handle_event(event)
+    }
+
+    fn update(&mut self, camera: &mut Camera, _delta_seconds: f32) {
+        let (delta, scroll) = self.drag.take();
+
+        // Move so the point under the cursor stays under the cursor
+        let world_per_pixel = PanZoomController::view_height(camera) / self.viewport_height;
+        let up = camera.right().cross(camera.forward());
+        let offset = (up * delta.y - camera.right() * delta.x) * world_per_pixel;
+        camera.eye += offset;
+        camera.target += offset;
+
+        let scale = (1.0 - scroll * self.zoom_speed).max(0.01);
+        match &mut camera.projection {
+            Projection::Orthographic { height, .. } => *height *= scale,
+            Projection::Perspective { .. } => camera.eye = camera.target - (camera.target - camera.eye) * scale,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+pub mod controller;
+
+pub use controller::*;
+
+use encase::ShaderType;
+use glam::{Mat4, Vec3};
+
+/** How the camera maps view space onto clip space.
+ *
+ * Both variants produce the 0..1 depth range wgpu expects.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    // `fovy` is the vertical field of view in radians
+    Perspective { fovy: f32, znear: f32, zfar: f32 },
+    // `height` is the extent of the view volume along the camera's up axis in world units
+    Orthographic { height: f32, znear: f32, zfar: f32 },
+}
+
+impl Projection {
+    pub fn matrix(&self, aspect: f32) -> Mat4 {
+        match *self {
+            Projection::Perspective { fovy, znear, zfar } => Mat4::perspective_rh(fovy, aspect, znear, zfar),
+            Projection::Orthographic { height, znear, zfar } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * aspect;
+                Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, znear, zfar)
+            }
+        }
+    }
+}
+
+/** A right-handed look-at camera.
+ *
+ * The aspect ratio should follow the surface, so call `resize` alongside `WgpuContext::resize`.
+ */
+#[derive(Clone, Debug)]
+pub struct Camera {
+    pub eye: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub projection: Projection,
+    pub aspect: f32,
+}
+
+impl Camera {
+    pub fn perspective(eye: Vec3, target: Vec3, fovy: f32, aspect: f32) -> Self {
+        Camera {
+            eye,
+            target,
+            up: Vec3::Y,
+            projection: Projection::Perspective { fovy, znear: 0.1, zfar: 100.0 },
+            aspect,
+        }
+    }
+
+    pub fn orthographic(eye: Vec3, target: Vec3, height: f32, aspect: f32) -> Self {
+        Camera {
+            eye,
+            target,
+            up: Vec3::Y,
+            projection: Projection::Orthographic { height, znear: 0.1, zfar: 100.0 },
+            aspect,
+        }
+    }
+
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.aspect = new_size.width.max(1) as f32 / new_size.height.max(1) as f32;
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        (self.target - self.eye).normalize_or_zero()
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.forward().cross(self.up).normalize_or_zero()
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_at_rh(self.eye, self.target, self.up)
+    }
+
+    pub fn projection_matrix(&self) -> Mat4 {
+        self.projection.matrix(self.aspect)
+    }
+
+    pub fn view_projection_matrix(&self) -> Mat4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    pub fn uniform(&self) -> CameraUniform {
+        let view = self.view_matrix();
+        let projection = self.projection_matrix();
+        CameraUniform {
+            view_projection: projection * view,
+            view,
+            projection,
+            position: self.eye,
+        }
+    }
+}
+
+/** The camera as seen by shaders. The matching WGSL struct is
+ *
+ * ```wgsl
+ * struct Camera {
+ *     view_projection: mat4x4<f32>,
+ *     view: mat4x4<f32>,
+ *     projection: mat4x4<f32>,
+ *     position: vec3<f32>,
+ * }
+ * ```
+ */
+#[derive(ShaderType, Clone, Copy, Debug)]
+pub struct CameraUniform {
+    pub view_projection: Mat4,
+    pub view: Mat4,
+    pub projection: Mat4,
+    pub position: Vec3,
+}
+
+impl CameraUniform {
+    pub fn as_wgsl_bytes(&self) -> encase::internal::Result<Vec<u8>> {
+        let mut buffer = encase::UniformBuffer::new(vec![]);
+        buffer.write(self)?;
+        Ok(buffer.into_inner())
+    }
+}
@@ -0,0 +1,142 @@
+/*
+   Loading cubemaps, either from six face images or from one equirectangular HDR image that is
+   projected onto the faces on the GPU.
+
+   Faces are stored as the six layers of a 2D texture in wgpu's order: +X, -X, +Y, -Y, +Z, -Z.
+   */
+use crate::{shader::Preprocessor, texture::{ColorSpace, Texture}, SamplerBuilder, TextureBuilder};
+
+/** Format of cubemaps converted from HDR images, which keeps values above 1.0 while still being
+ * filterable and renderable everywhere.
+ */
+pub const HDR_CUBEMAP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+fn cube_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+    SamplerBuilder::new()
+        .label("Cubemap sampler")
+        .filter(wgpu::FilterMode::Linear)
+        .build(device)
+}
+
+/** Builds a cubemap from six encoded PNG or JPEG images, in +X, -X, +Y, -Y, +Z, -Z order. All
+ * faces must be square and the same size.
+ */
+pub fn from_faces(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    faces: [&[u8]; 6],
+    color_space: ColorSpace,
+) -> image::ImageResult<Texture> {
+    let mut size = None;
+    let mut texels = Vec::new();
+    for face in faces {
+        let image = image::load_from_memory(face)?.into_rgba8();
+        let dimensions = image.dimensions();
+        if dimensions.0 != dimensions.1 || size.is_some_and(|size| size != dimensions) {
+            return Err(image::ImageError::Parameter(image::error::ParameterError::from_kind(
+                image::error::ParameterErrorKind::DimensionMismatch,
+            )));
+        }
+        size = Some(dimensions);
+        texels.extend_from_slice(&image);
+    }
+
+    let (width, height) = size.unwrap();
+    let texture = TextureBuilder::new(width, height)
+        .label("Cubemap")
+        .array_layers(6)
+        .format(color_space.rgba8_format())
+        .build_with_data(device, queue, &texels);
+    Ok(Texture::from_texture_with_dimension(texture, wgpu::TextureViewDimension::Cube, cube_sampler(device)))
+}
+
+/** Builds a `face_size` x `face_size` cubemap from an equirectangular image such as a Radiance
+ * `.hdr` panorama. The result is in `HDR_CUBEMAP_FORMAT`.
+ */
+pub fn from_equirectangular(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    bytes: &[u8],
+    face_size: u32,
+) -> image::ImageResult<Texture> {
+    let image = image::load_from_memory(bytes)?.into_rgba32f();
+    let source = TextureBuilder::new(image.width(), image.height())
+        .label("Equirectangular source")
+        .format(wgpu::TextureFormat::Rgba32Float)
+        .build_with_data(device, queue, bytemuck::cast_slice(image.as_raw()));
+    let source_view = source.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let texture = TextureBuilder::new(face_size, face_size)
+        .label("Cubemap")
+        .array_layers(6)
+        .format(HDR_CUBEMAP_FORMAT)
+        .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT)
+        .build(device);
+
+    let module = Preprocessor::new()
+        .process_source("cubemap.wgsl", include_str!("cubemap.wgsl"), None)
+        .expect("Error in preprocessing cubemap.wgsl")
+        .create_shader_module(device, "cubemap.wgsl");
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Equirectangular bind group layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        }],
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Equirectangular bind group"),
+        layout: &bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(&source_view),
+        }],
+    });
+    let layout = crate::PipelineLayoutBuilder::new()
+        .add_bind_group_layout(&bind_group_layout)
+        .build(device);
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Equirectangular pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &module,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: &module,
+            entry_point: "fs_main",
+            targets: &[Some(HDR_CUBEMAP_FORMAT.into())],
+        }),
+        multiview: None,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Equirectangular encoder") });
+    for face in 0..6 {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Cubemap face"),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_array_layer: face,
+            array_layer_count: Some(1),
+            ..Default::default()
+        });
+        let mut rpass = crate::RenderPassBuilder::new()
+            .clear(&view, wgpu::Color::BLACK)
+            .build(&mut encoder);
+        rpass.set_pipeline(&pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..3, face..face + 1);
+    }
+    queue.submit(Some(encoder.finish()));
+
+    Ok(Texture::from_texture_with_dimension(texture, wgpu::TextureViewDimension::Cube, cube_sampler(device)))
+}
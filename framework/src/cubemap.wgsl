@@ -0,0 +1,63 @@
+// Projects an equirectangular (latitude/longitude) image onto the six faces of a cubemap, one
+// face per draw.
+
+#include "framework/fullscreen.wgsl"
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) uv: vec2<f32>,
+	// Cube face being rendered, passed as the instance index
+	@location(1) @interpolate(flat) face: u32,
+}
+
+const PI: f32 = 3.14159265;
+
+// Float32 textures can't be filtered everywhere, so the source is loaded and filtered by hand
+@group(0)
+@binding(0)
+var source: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, @builtin(instance_index) face: u32) -> VertexOutput {
+	let uv = fullscreen_uv(index);
+	var output: VertexOutput;
+	output.position = vec4(uv_to_ndc(uv), 0.0, 1.0);
+	output.uv = uv;
+	output.face = face;
+	return output;
+}
+
+// The direction through a point on a face, in wgpu's +X, -X, +Y, -Y, +Z, -Z layer order, with
+// `st` spanning -1..1 from the face's top left
+fn face_direction(face: u32, st: vec2<f32>) -> vec3<f32> {
+	switch face {
+		case 0u: { return vec3(1.0, -st.y, -st.x); }
+		case 1u: { return vec3(-1.0, -st.y, st.x); }
+		case 2u: { return vec3(st.x, 1.0, st.y); }
+		case 3u: { return vec3(st.x, -1.0, -st.y); }
+		case 4u: { return vec3(st.x, -st.y, 1.0); }
+		default: { return vec3(-st.x, -st.y, -1.0); }
+	}
+}
+
+fn load_wrapped(texel: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+	let wrapped = vec2((texel.x + size.x) % size.x, clamp(texel.y, 0, size.y - 1));
+	return textureLoad(source, wrapped, 0);
+}
+
+fn sample_bilinear(uv: vec2<f32>) -> vec4<f32> {
+	let size = vec2<i32>(textureDimensions(source));
+	let position = uv * vec2<f32>(size) - 0.5;
+	let base = vec2<i32>(floor(position));
+	let f = fract(position);
+	let top = mix(load_wrapped(base, size), load_wrapped(base + vec2(1, 0), size), f.x);
+	let bottom = mix(load_wrapped(base + vec2(0, 1), size), load_wrapped(base + vec2(1, 1), size), f.x);
+	return mix(top, bottom, f.y);
+}
+
+@fragment
+fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
+	let direction = normalize(face_direction(vertex.face, vertex.uv * 2.0 - 1.0));
+	let uv = vec2(atan2(direction.z, direction.x) / (2.0 * PI) + 0.5, acos(direction.y) / PI);
+	return vec4(sample_bilinear(uv).rgb, 1.0);
+}
@@ -1,5 +1,24 @@
 pub mod wgpu_context;
 pub mod builder;
+pub mod app;
+pub mod args;
+pub mod atlas;
+pub mod camera;
+pub mod cubemap;
+pub mod mesh;
+pub mod mipmap;
+pub mod model;
+pub mod pipeline;
+pub mod profiler;
+pub mod shader;
+pub mod shapes;
+pub mod skybox;
+pub mod text;
+pub mod texture;
+pub mod time;
+pub mod transform;
+#[cfg(feature = "egui")]
+pub mod ui;
 
 pub use builder::*;
 pub use wgpu_context::*;
@@ -13,6 +32,7 @@ pub use wgpu_context::*;
 
    - `$context`: the `WgpuContext` to apply the render pass to
    - `$clear`: the `wgpu::Color` to clear the screen with at the beginning of the pass
+   - `depth $depth` (optional): a depth `wgpu::TextureView` to attach, cleared to the far plane
    - `$rpass in $code`: expression denotine the name of the variable to assign the render pass
    variable along with the code itself to give this variable
 
@@ -47,6 +67,23 @@ macro_rules! basic_render_pass {
             $code
         }
 
+        $context.queue.submit(Some(encoder.finish()));
+        frame.present();
+    };
+    ($context:ident, $clear:ident, depth $depth:expr, $rpass:ident in $code:expr) => {
+
+        let (frame, frame_view) = $context.frame_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = $context.command_encoder();
+
+        {
+            let mut $rpass = RenderPassBuilder::new()
+                .clear(&frame_view, wgpu::Color::$clear)
+                .depth($depth, 1.0)
+                .build(&mut encoder);
+
+            $code
+        }
+
         $context.queue.submit(Some(encoder.finish()));
         frame.present();
     };
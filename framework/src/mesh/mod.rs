@@ -0,0 +1,220 @@
+pub mod primitives;
+
+pub use primitives::*;
+
+use std::ops::Range;
+
+use glam::{Vec2, Vec3};
+
+use crate::BufferBuilder;
+
+/** Geometry on the CPU: a vertex list, indices into it, and how the indices form primitives.
+ *
+ * Indices are always kept as `u32` here; `upload` narrows them to `u16` when the vertex count
+ * allows it.
+ */
+#[derive(Clone, Debug)]
+pub struct Mesh<V> {
+    pub vertices: Vec<V>,
+    pub indices: Vec<u32>,
+    pub topology: wgpu::PrimitiveTopology,
+}
+
+impl<V> Mesh<V> {
+    pub fn new(vertices: Vec<V>, indices: Vec<u32>) -> Self {
+        Mesh { vertices, indices, topology: wgpu::PrimitiveTopology::TriangleList }
+    }
+
+    pub fn with_topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /** The smallest index format able to address every vertex.
+     */
+    pub fn index_format(&self) -> wgpu::IndexFormat {
+        index_format_for(self.vertices.len())
+    }
+
+    /** Converts every vertex, e.g. to strip attributes a pipeline doesn't use.
+     */
+    pub fn map_vertices<W>(self, f: impl FnMut(V) -> W) -> Mesh<W> {
+        Mesh {
+            vertices: self.vertices.into_iter().map(f).collect(),
+            indices: self.indices,
+            topology: self.topology,
+        }
+    }
+
+    /** Appends the geometry of `other`, offsetting its indices past this mesh's vertices.
+     */
+    pub fn append(&mut self, other: Mesh<V>) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.indices.extend(other.indices.into_iter().map(|index| index + offset));
+    }
+}
+
+impl<V: bytemuck::Pod> Mesh<V> {
+    pub fn upload(&self, device: &wgpu::Device) -> GpuMesh {
+        GpuMesh::new(device, self)
+    }
+}
+
+/** Picks `Uint16` indices whenever `vertex_count` vertices can be addressed with them, halving
+ * the size of the index buffer. Index 0xFFFF is left unused since strips treat it as a restart.
+ */
+pub fn index_format_for(vertex_count: usize) -> wgpu::IndexFormat {
+    if vertex_count <= u16::MAX as usize {
+        wgpu::IndexFormat::Uint16
+    } else {
+        wgpu::IndexFormat::Uint32
+    }
+}
+
+/** The vertex produced by the generators in this module. Shader locations are
+ *
+ * ```wgsl
+ * struct VertexInput {
+ *     @location(0) position: vec3<f32>,
+ *     @location(1) normal: vec3<f32>,
+ *     @location(2) tangent: vec4<f32>,
+ *     @location(3) uv: vec2<f32>,
+ * }
+ * ```
+ *
+ * The tangent's `w` is the handedness of the tangent frame, so the bitangent is
+ * `cross(normal, tangent.xyz) * tangent.w`.
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tangent: [f32; 4],
+    pub uv: [f32; 2],
+}
+
+impl MeshVertex {
+    pub const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x3,
+        2 => Float32x4,
+        3 => Float32x2,
+    ];
+
+    pub fn new(position: Vec3, normal: Vec3, uv: Vec2) -> Self {
+        MeshVertex {
+            position: position.into(),
+            normal: normal.into(),
+            tangent: [0.0; 4],
+            uv: uv.into(),
+        }
+    }
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/** Per-vertex normals of a triangle list, each the area-weighted average of the normals of the
+ * triangles sharing the vertex.
+ */
+pub fn smooth_normals(mesh: &Mesh<MeshVertex>) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; mesh.vertices.len()];
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+        let [pa, pb, pc] = [a, b, c].map(|i| Vec3::from(mesh.vertices[i].position));
+        let normal = (pb - pa).cross(pc - pa);
+        for i in [a, b, c] {
+            normals[i] += normal;
+        }
+    }
+    normals.into_iter().map(Vec3::normalize_or_zero).collect()
+}
+
+/** Fills in per-vertex tangents from the UV layout of a triangle list, averaging the tangents of
+ * the triangles sharing each vertex.
+ */
+pub fn compute_tangents(mesh: &mut Mesh<MeshVertex>) {
+    let mut tangents = vec![Vec3::ZERO; mesh.vertices.len()];
+    let mut bitangents = vec![Vec3::ZERO; mesh.vertices.len()];
+
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+        let [pa, pb, pc] = [a, b, c].map(|i| Vec3::from(mesh.vertices[i].position));
+        let [ua, ub, uc] = [a, b, c].map(|i| Vec2::from(mesh.vertices[i].uv));
+
+        let (edge1, edge2) = (pb - pa, pc - pa);
+        let (duv1, duv2) = (ub - ua, uc - ua);
+        let determinant = duv1.x * duv2.y - duv2.x * duv1.y;
+        if determinant.abs() < f32::EPSILON {
+            continue;
+        }
+        let r = 1.0 / determinant;
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) * r;
+        let bitangent = (edge2 * duv1.x - edge1 * duv2.x) * r;
+        for i in [a, b, c] {
+            tangents[i] += tangent;
+            bitangents[i] += bitangent;
+        }
+    }
+
+    for (vertex, (tangent, bitangent)) in mesh.vertices.iter_mut().zip(tangents.into_iter().zip(bitangents)) {
+        let normal = Vec3::from(vertex.normal);
+        // Gram-Schmidt orthogonalize against the normal, falling back to any perpendicular axis
+        let mut orthogonal = (tangent - normal * normal.dot(tangent)).normalize_or_zero();
+        if orthogonal == Vec3::ZERO {
+            orthogonal = normal.any_orthonormal_vector();
+        }
+        let handedness = if normal.cross(orthogonal).dot(bitangent) < 0.0 { -1.0 } else { 1.0 };
+        vertex.tangent = orthogonal.extend(handedness).into();
+    }
+}
+
+/** A mesh uploaded to vertex and index buffers, remembering what is needed to draw it.
+ */
+pub struct GpuMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+    pub index_format: wgpu::IndexFormat,
+    pub topology: wgpu::PrimitiveTopology,
+}
+
+impl GpuMesh {
+    pub fn new<V: bytemuck::Pod>(device: &wgpu::Device, mesh: &Mesh<V>) -> Self {
+        let vertex_buffer = BufferBuilder::vertex(&mesh.vertices)
+            .label("Mesh vertex buffer")
+            .build(device);
+
+        let index_format = mesh.index_format();
+        let index_buffer = match index_format {
+            wgpu::IndexFormat::Uint16 => {
+                let indices: Vec<u16> = mesh.indices.iter().map(|&index| index as u16).collect();
+                BufferBuilder::index(&indices).label("Mesh index buffer").build(device)
+            }
+            wgpu::IndexFormat::Uint32 => BufferBuilder::index(&mesh.indices).label("Mesh index buffer").build(device),
+        };
+
+        GpuMesh {
+            vertex_buffer,
+            index_buffer,
+            index_count: mesh.indices.len() as u32,
+            index_format,
+            topology: mesh.topology,
+        }
+    }
+
+    /** Binds the vertex buffer to slot 0 and the index buffer, then draws every index.
+     */
+    pub fn draw<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>, instances: Range<u32>) {
+        rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        rpass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+        rpass.draw_indexed(0..self.index_count, 0, instances);
+    }
+}
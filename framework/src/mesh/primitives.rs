@@ -0,0 +1,260 @@
+/*
+   Generators for common shapes. All of them are centered on the origin, use counter-clockwise
+   front faces with outward normals, and have UVs with v pointing down as in textures.
+   */
+use std::{collections::HashMap, f32::consts::{PI, TAU}};
+
+use glam::{Vec2, Vec3};
+
+use super::{compute_tangents, Mesh, MeshVertex};
+
+// Builds the mesh and fills in its tangents, which every generator wants
+fn finish(vertices: Vec<MeshVertex>, indices: Vec<u32>) -> Mesh<MeshVertex> {
+    let mut mesh = Mesh::new(vertices, indices);
+    compute_tangents(&mut mesh);
+    mesh
+}
+
+// Indices for a grid of `columns` x `rows` quads whose vertices are laid out row by row. The
+// triangles wind counter-clockwise when viewed with columns going right and rows going down.
+fn grid_indices(columns: u32, rows: u32, first_vertex: u32) -> Vec<u32> {
+    let stride = columns + 1;
+    let mut indices = Vec::with_capacity((columns * rows * 6) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            let top_left = first_vertex + row * stride + column;
+            let bottom_left = top_left + stride;
+            indices.extend_from_slice(&[
+                top_left, bottom_left, bottom_left + 1,
+                top_left, bottom_left + 1, top_left + 1,
+            ]);
+        }
+    }
+    indices
+}
+
+/** An axis-aligned cube with edges of length `size`, each face with its own four vertices so
+ * normals and UVs don't bleed across edges.
+ */
+pub fn cube(size: f32) -> Mesh<MeshVertex> {
+    // Normal, then the axes along which u and v increase; u x v points along the normal
+    let faces = [
+        (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+        (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+        (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+        (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+        (Vec3::Z, Vec3::X, Vec3::Y),
+        (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+    ];
+    let half = size / 2.0;
+
+    let mut vertices = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+    for (normal, u, v) in faces {
+        let first = vertices.len() as u32;
+        for (su, sv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let position = (normal + u * su + v * sv) * half;
+            let uv = Vec2::new((su + 1.0) / 2.0, (1.0 - sv) / 2.0);
+            vertices.push(MeshVertex::new(position, normal, uv));
+        }
+        indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    finish(vertices, indices)
+}
+
+/** A flat `size_x` by `size_z` grid in the XZ plane facing +Y, split into `subdivisions_x` by
+ * `subdivisions_z` quads.
+ */
+pub fn grid(size_x: f32, size_z: f32, subdivisions_x: u32, subdivisions_z: u32) -> Mesh<MeshVertex> {
+    let (columns, rows) = (subdivisions_x.max(1), subdivisions_z.max(1));
+    let mut vertices = Vec::with_capacity(((columns + 1) * (rows + 1)) as usize);
+    for row in 0..=rows {
+        let v = row as f32 / rows as f32;
+        for column in 0..=columns {
+            let u = column as f32 / columns as f32;
+            let position = Vec3::new((u - 0.5) * size_x, 0.0, (v - 0.5) * size_z);
+            vertices.push(MeshVertex::new(position, Vec3::Y, Vec2::new(u, v)));
+        }
+    }
+    // Rows advance along +z, which is "down" in UV space when looking from above
+    finish(vertices, grid_indices(columns, rows, 0))
+}
+
+/** A single quad version of `grid`.
+ */
+pub fn plane(size: f32) -> Mesh<MeshVertex> {
+    grid(size, size, 1, 1)
+}
+
+/** A sphere built from `sectors` slices around the Y axis and `stacks` bands from pole to pole.
+ */
+pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> Mesh<MeshVertex> {
+    let (sectors, stacks) = (sectors.max(3), stacks.max(2));
+    let mut vertices = Vec::with_capacity(((sectors + 1) * (stacks + 1)) as usize);
+    for stack in 0..=stacks {
+        let v = stack as f32 / stacks as f32;
+        let (sin_phi, cos_phi) = (v * PI).sin_cos();
+        for sector in 0..=sectors {
+            let u = sector as f32 / sectors as f32;
+            let (sin_theta, cos_theta) = (u * TAU).sin_cos();
+            let normal = Vec3::new(sin_phi * sin_theta, cos_phi, sin_phi * cos_theta);
+            vertices.push(MeshVertex::new(normal * radius, normal, Vec2::new(u, v)));
+        }
+    }
+
+    // The first and last bands are fans, so drop the triangles collapsed onto the poles
+    let indices = grid_indices(sectors, stacks, 0)
+        .chunks_exact(3)
+        .enumerate()
+        .filter(|(i, _)| {
+            let band = (*i as u32 / 2) / sectors;
+            let upper = i % 2 == 1;
+            !(band == 0 && upper || band == stacks - 1 && !upper)
+        })
+        .flat_map(|(_, triangle)| triangle.to_vec())
+        .collect();
+    finish(vertices, indices)
+}
+
+/** A sphere made by repeatedly splitting the triangles of an icosahedron, giving evenly sized
+ * triangles. Each subdivision quadruples the triangle count.
+ */
+pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh<MeshVertex> {
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut positions: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .into_iter()
+    .map(|(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+
+    let mut triangles: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Edges are shared by two triangles, so reuse the midpoint the first one created
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push(((positions[a as usize] + positions[b as usize]) / 2.0).normalize());
+                positions.len() as u32 - 1
+            })
+        };
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let vertices = positions
+        .into_iter()
+        .map(|normal| {
+            let uv = Vec2::new(0.5 + normal.x.atan2(normal.z) / TAU, normal.y.clamp(-1.0, 1.0).acos() / PI);
+            MeshVertex::new(normal * radius, normal, uv)
+        })
+        .collect();
+    finish(vertices, triangles.into_iter().flatten().collect())
+}
+
+// A ring of `segments + 1` vertices around the Y axis at `y`, the last repeating the first with
+// u = 1 so textures wrap cleanly
+fn ring(radius: f32, y: f32, segments: u32, v: f32, normal: impl Fn(Vec3) -> Vec3) -> Vec<MeshVertex> {
+    (0..=segments)
+        .map(|segment| {
+            let u = segment as f32 / segments as f32;
+            let (sin_theta, cos_theta) = (u * TAU).sin_cos();
+            let direction = Vec3::new(sin_theta, 0.0, cos_theta);
+            MeshVertex::new(direction * radius + Vec3::Y * y, normal(direction), Vec2::new(u, v))
+        })
+        .collect()
+}
+
+// A flat disc at `y` facing up or down, as a fan around a center vertex
+fn cap(radius: f32, y: f32, segments: u32, facing_up: bool, mesh: &mut (Vec<MeshVertex>, Vec<u32>)) {
+    let (vertices, indices) = mesh;
+    let normal = if facing_up { Vec3::Y } else { Vec3::NEG_Y };
+    let center = vertices.len() as u32;
+    vertices.push(MeshVertex::new(Vec3::Y * y, normal, Vec2::splat(0.5)));
+    for segment in 0..=segments {
+        let (sin_theta, cos_theta) = (segment as f32 / segments as f32 * TAU).sin_cos();
+        let uv = Vec2::new(0.5 + sin_theta / 2.0, 0.5 - cos_theta / 2.0);
+        vertices.push(MeshVertex::new(Vec3::new(sin_theta * radius, y, cos_theta * radius), normal, uv));
+    }
+    for segment in 0..segments {
+        let (a, b) = (center + 1 + segment, center + 2 + segment);
+        if facing_up {
+            indices.extend_from_slice(&[center, a, b]);
+        } else {
+            indices.extend_from_slice(&[center, b, a]);
+        }
+    }
+}
+
+/** A capped cylinder of the given `height` along the Y axis.
+ */
+pub fn cylinder(radius: f32, height: f32, segments: u32) -> Mesh<MeshVertex> {
+    let segments = segments.max(3);
+    let half = height / 2.0;
+    let mut vertices = ring(radius, half, segments, 0.0, |direction| direction);
+    vertices.extend(ring(radius, -half, segments, 1.0, |direction| direction));
+    let mut mesh = (vertices, grid_indices(segments, 1, 0));
+
+    cap(radius, half, segments, true, &mut mesh);
+    cap(radius, -half, segments, false, &mut mesh);
+    finish(mesh.0, mesh.1)
+}
+
+/** A cone of the given `height` along the Y axis with its tip at the top and a capped base.
+ */
+pub fn cone(radius: f32, height: f32, segments: u32) -> Mesh<MeshVertex> {
+    let segments = segments.max(3);
+    let half = height / 2.0;
+    // The side normal leans up by the slope of the cone
+    let normal = |direction: Vec3| (direction * height + Vec3::Y * radius).normalize();
+
+    // The tip is one vertex per segment so each slice gets its own normal
+    let mut vertices = ring(0.0, half, segments, 0.0, normal);
+    vertices.extend(ring(radius, -half, segments, 1.0, normal));
+    let indices = grid_indices(segments, 1, 0)
+        .chunks_exact(3)
+        .enumerate()
+        .filter(|(i, _)| i % 2 == 0)
+        .flat_map(|(_, triangle)| triangle.to_vec())
+        .collect();
+    let mut mesh = (vertices, indices);
+
+    cap(radius, -half, segments, false, &mut mesh);
+    finish(mesh.0, mesh.1)
+}
+
+/** A torus around the Y axis: a tube of `minor_radius` swept around a circle of `major_radius`.
+ */
+pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> Mesh<MeshVertex> {
+    let (major_segments, minor_segments) = (major_segments.max(3), minor_segments.max(3));
+    let mut vertices = Vec::with_capacity(((major_segments + 1) * (minor_segments + 1)) as usize);
+    for minor in 0..=minor_segments {
+        let v = minor as f32 / minor_segments as f32;
+        // Sweeping the tube downwards on the outside keeps the quads facing outwards
+        let (sin_phi, cos_phi) = (-v * TAU).sin_cos();
+        for major in 0..=major_segments {
+            let u = major as f32 / major_segments as f32;
+            let (sin_theta, cos_theta) = (u * TAU).sin_cos();
+            let direction = Vec3::new(sin_theta, 0.0, cos_theta);
+            let normal = direction * cos_phi + Vec3::Y * sin_phi;
+            let position = direction * major_radius + normal * minor_radius;
+            vertices.push(MeshVertex::new(position, normal, Vec2::new(u, v)));
+        }
+    }
+    finish(vertices, grid_indices(major_segments, minor_segments, 0))
+}
@@ -0,0 +1,186 @@
+/*
+   Generates mip chains on the GPU by repeatedly rendering each level into the next with a linear
+   filter.
+
+   Blit pipelines are created lazily for each texture format and then reused. sRGB textures are
+   handled by the hardware: sampling an sRGB view decodes to linear and rendering into one encodes
+   again, so the averaging happens in linear space as it should.
+
+   Texture arrays sample their source level through an array view and pick the layer in the
+   shader rather than viewing single layers, since the GL backend can't bind a single layer of an
+   array as a plain 2D texture. The shader is compiled twice for that, once with `ARRAY` defined.
+   */
+use std::collections::HashMap;
+
+use crate::{shader::Preprocessor, SamplerBuilder};
+
+/** The number of mip levels in a full chain down to 1x1 for a 2D texture of the given size.
+ */
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+pub struct MipmapGenerator {
+    module: wgpu::ShaderModule,
+    array_module: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    array_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    // Keyed by the target format and whether the texture is an array
+    pipelines: HashMap<(wgpu::TextureFormat, bool), wgpu::RenderPipeline>,
+}
+
+impl MipmapGenerator {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let module = |preprocessor: Preprocessor| {
+            preprocessor
+                .process_source("mipmap.wgsl", include_str!("mipmap.wgsl"), None)
+                .expect("Error in preprocessing mipmap.wgsl")
+                .create_shader_module(device, "mipmap.wgsl")
+        };
+
+        let bind_group_layout = |view_dimension| {
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Mipmap bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            })
+        };
+
+        let sampler = SamplerBuilder::new()
+            .label("Mipmap sampler")
+            .mag_filter(wgpu::FilterMode::Linear)
+            .min_filter(wgpu::FilterMode::Linear)
+            .build(device);
+
+        MipmapGenerator {
+            module: module(Preprocessor::new()),
+            array_module: module(Preprocessor::new().define("ARRAY", "")),
+            bind_group_layout: bind_group_layout(wgpu::TextureViewDimension::D2),
+            array_bind_group_layout: bind_group_layout(wgpu::TextureViewDimension::D2Array),
+            sampler,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    fn create_pipeline(&self, device: &wgpu::Device, format: wgpu::TextureFormat, array: bool) -> wgpu::RenderPipeline {
+        let (bind_group_layout, module) = if array {
+            (&self.array_bind_group_layout, &self.array_module)
+        } else {
+            (&self.bind_group_layout, &self.module)
+        };
+        let layout = crate::PipelineLayoutBuilder::new()
+            .label("Mipmap pipeline layout")
+            .add_bind_group_layout(bind_group_layout)
+            .build(device);
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mipmap pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module,
+                entry_point: "fs_main",
+                targets: &[Some(format.into())],
+            }),
+            multiview: None,
+        })
+    }
+
+    /** Records passes filling every mip level after the first from the one before it, for each
+     * array layer of `texture`.
+     *
+     * The texture must be 2D, have a filterable color format that can be rendered to, and be
+     * created with both `TEXTURE_BINDING` and `RENDER_ATTACHMENT` usage.
+     */
+    pub fn generate(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) {
+        assert_eq!(texture.dimension(), wgpu::TextureDimension::D2, "Error in generating mipmaps: only 2D textures are supported");
+        let format = texture.format();
+        let layers = texture.depth_or_array_layers();
+        let array = layers > 1;
+        if !self.pipelines.contains_key(&(format, array)) {
+            let pipeline = self.create_pipeline(device, format, array);
+            self.pipelines.insert((format, array), pipeline);
+        }
+        let pipeline = &self.pipelines[&(format, array)];
+        let (bind_group_layout, dimension) = if array {
+            (&self.array_bind_group_layout, wgpu::TextureViewDimension::D2Array)
+        } else {
+            (&self.bind_group_layout, wgpu::TextureViewDimension::D2)
+        };
+
+        for level in 1..texture.mip_level_count() {
+            let source = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Mipmap source view"),
+                dimension: Some(dimension),
+                base_mip_level: level - 1,
+                mip_level_count: Some(1),
+                ..Default::default()
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mipmap bind group"),
+                layout: bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            });
+
+            for layer in 0..layers {
+                let target = texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Mipmap target view"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                });
+
+                let mut rpass = crate::RenderPassBuilder::new()
+                    .clear(&target, wgpu::Color::TRANSPARENT)
+                    .build(encoder);
+                rpass.set_pipeline(pipeline);
+                rpass.set_bind_group(0, &bind_group, &[]);
+                rpass.draw(0..3, layer..layer + 1);
+            }
+        }
+    }
+
+    /** Generates the mip chain of `texture` and submits the work right away.
+     */
+    pub fn generate_and_submit(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Mipmap encoder") });
+        self.generate(device, &mut encoder, texture);
+        queue.submit(Some(encoder.finish()));
+    }
+}
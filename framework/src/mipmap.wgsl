@@ -0,0 +1,43 @@
+// Downsamples one mip level into the next. With linear filtering, sampling the center of each
+// destination texel averages the 2x2 block of source texels beneath it.
+//
+// Define ARRAY for texture arrays, which sample the layer passed as the instance index.
+
+#include "framework/fullscreen.wgsl"
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) uv: vec2<f32>,
+	@location(1) @interpolate(flat) layer: u32,
+}
+
+@group(0)
+@binding(0)
+#ifdef ARRAY
+var source_texture: texture_2d_array<f32>;
+#else
+var source_texture: texture_2d<f32>;
+#endif
+
+@group(0)
+@binding(1)
+var source_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, @builtin(instance_index) layer: u32) -> VertexOutput {
+	let uv = fullscreen_uv(index);
+	var output: VertexOutput;
+	output.position = vec4(uv_to_ndc(uv), 0.0, 1.0);
+	output.uv = uv;
+	output.layer = layer;
+	return output;
+}
+
+@fragment
+fn fs_main(vertex: VertexOutput) -> @location(0) vec4<f32> {
+#ifdef ARRAY
+	return textureSample(source_texture, source_sampler, vertex.uv, vertex.layer);
+#else
+	return textureSample(source_texture, source_sampler, vertex.uv);
+#endif
+}
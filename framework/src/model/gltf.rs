@@ -0,0 +1,564 @@
+/*
+   Importer for glTF 2.0 scenes, in `.gltf` (with embedded or external buffers and images) or
+   binary `.glb` form.
+
+   The importer keeps the document's structure: a node hierarchy with local transforms, meshes
+   made of primitives that each have their own material, PBR metallic-roughness materials, and
+   decoded images. Everything up to `upload` happens on the CPU.
+
+   Images are decoded as sRGB when materials use them for color (base color and emissive) and as
+   linear otherwise. An image used both ways is kept twice, once in each color space, along with
+   the textures that sample it.
+
+   Vertices carry a single UV set, so a material sampling several is loaded with its base color
+   texture's set, with a warning.
+   */
+use std::{fmt, path::Path};
+
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+use crate::{
+    mesh::{compute_tangents, smooth_normals, GpuMesh, Mesh, MeshVertex},
+    texture::ColorSpace,
+    transform::Transform,
+    SamplerBuilder, TextureBuilder,
+};
+
+pub use ::gltf::material::AlphaMode;
+
+#[derive(Debug)]
+pub enum GltfError {
+    Gltf(::gltf::Error),
+    // An attribute or the indices don't match the primitive's positions
+    MalformedPrimitive { mesh: usize, primitive: usize, message: String },
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Gltf(e) => write!(f, "{e}"),
+            GltfError::MalformedPrimitive { mesh, primitive, message } => {
+                write!(f, "primitive {primitive} of mesh {mesh}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+impl From<::gltf::Error> for GltfError {
+    fn from(e: ::gltf::Error) -> Self {
+        GltfError::Gltf(e)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    // Relative to the parent node
+    pub transform: Transform,
+    // Index into `GltfScene::meshes`
+    pub mesh: Option<usize>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfPrimitive {
+    pub mesh: Mesh<MeshVertex>,
+    // Index into `GltfScene::materials`, `None` for the glTF default material
+    pub material: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+/** A texture reference from a material: which texture and which UV set it's sampled with.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GltfTextureRef {
+    // Index into `GltfScene::textures`
+    pub texture: usize,
+    pub tex_coord: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_color_factor: [f32; 4],
+    pub base_color_texture: Option<GltfTextureRef>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    // Roughness in the green channel, metalness in the blue channel
+    pub metallic_roughness_texture: Option<GltfTextureRef>,
+    pub normal_texture: Option<GltfTextureRef>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<GltfTextureRef>,
+    pub occlusion_strength: f32,
+    pub emissive_factor: [f32; 3],
+    pub emissive_texture: Option<GltfTextureRef>,
+    pub alpha_mode: AlphaMode,
+    pub alpha_cutoff: f32,
+    pub double_sided: bool,
+}
+
+impl GltfMaterial {
+    /** The textures sampled as color, which hold sRGB data.
+     */
+    pub fn color_texture_refs(&self) -> impl Iterator<Item = GltfTextureRef> {
+        [self.base_color_texture, self.emissive_texture].into_iter().flatten()
+    }
+
+    /** The textures sampled as data, which hold linear values.
+     */
+    pub fn data_texture_refs(&self) -> impl Iterator<Item = GltfTextureRef> {
+        [self.metallic_roughness_texture, self.normal_texture, self.occlusion_texture].into_iter().flatten()
+    }
+
+    pub fn texture_refs(&self) -> impl Iterator<Item = GltfTextureRef> {
+        self.color_texture_refs().chain(self.data_texture_refs())
+    }
+}
+
+/** An image and a sampler, as glTF pairs them.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfTexture {
+    // Index into `GltfScene::images`
+    pub image: usize,
+    pub address_mode_u: wgpu::AddressMode,
+    pub address_mode_v: wgpu::AddressMode,
+    pub mag_filter: wgpu::FilterMode,
+    pub min_filter: wgpu::FilterMode,
+    pub mipmap_filter: wgpu::FilterMode,
+}
+
+/** A decoded image converted to RGBA8.
+ */
+#[derive(Clone, Debug)]
+pub struct GltfImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    // sRGB for images sampled as color (base color and emissive), linear for everything else
+    pub color_space: ColorSpace,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+    // Top level nodes of the default scene
+    pub roots: Vec<usize>,
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub textures: Vec<GltfTexture>,
+    pub images: Vec<GltfImage>,
+}
+
+impl GltfScene {
+    /** Loads a `.gltf` or `.glb` file, resolving external buffers and images relative to it.
+     */
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GltfError> {
+        let (document, buffers, images) = ::gltf::import(path)?;
+        Self::from_document(&document, &buffers, images)
+    }
+
+    /** Loads a `.glb` file, or a `.gltf` file whose buffers and images are all embedded, from
+     * memory.
+     */
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, GltfError> {
+        let (document, buffers, images) = ::gltf::import_slice(bytes)?;
+        Self::from_document(&document, &buffers, images)
+    }
+
+    fn from_document(
+        document: &::gltf::Document,
+        buffers: &[::gltf::buffer::Data],
+        images: Vec<::gltf::image::Data>,
+    ) -> Result<Self, GltfError> {
+        let mut nodes: Vec<GltfNode> = document
+            .nodes()
+            .map(|node| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                GltfNode {
+                    name: node.name().map(str::to_string),
+                    transform: Transform {
+                        translation: Vec3::from(translation),
+                        rotation: Quat::from_array(rotation),
+                        scale: Vec3::from(scale),
+                    },
+                    mesh: node.mesh().map(|mesh| mesh.index()),
+                    parent: None,
+                    children: node.children().map(|child| child.index()).collect(),
+                }
+            })
+            .collect();
+        for index in 0..nodes.len() {
+            for child in nodes[index].children.clone() {
+                nodes[child].parent = Some(index);
+            }
+        }
+
+        let roots = match document.default_scene().or_else(|| document.scenes().next()) {
+            Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+            None => (0..nodes.len()).filter(|&index| nodes[index].parent.is_none()).collect(),
+        };
+
+        let mut materials: Vec<GltfMaterial> = document.materials().map(|material| load_material(&material)).collect();
+        let mut textures: Vec<GltfTexture> = document.textures().map(|texture| load_texture(&texture)).collect();
+
+        // The UV set each material's primitives load. `MeshVertex` holds only one, so materials
+        // sampling several, e.g. an occlusion map on a second set, get the base color's.
+        let tex_coords: Vec<u32> = materials
+            .iter()
+            .enumerate()
+            .map(|(index, material)| {
+                let chosen = material
+                    .base_color_texture
+                    .or_else(|| material.texture_refs().next())
+                    .map_or(0, |texture| texture.tex_coord);
+                if material.texture_refs().any(|texture| texture.tex_coord != chosen) {
+                    log::warn!("Material {index} samples more than one UV set, using set {chosen} for all its textures");
+                }
+                chosen
+            })
+            .collect();
+
+        let meshes = document
+            .meshes()
+            .map(|mesh| {
+                let mut primitives = Vec::new();
+                for primitive in mesh.primitives() {
+                    let tex_coord = primitive.material().index().map_or(0, |material| tex_coords[material]);
+                    primitives.extend(load_primitive(&primitive, buffers, tex_coord).map_err(|message| {
+                        GltfError::MalformedPrimitive { mesh: mesh.index(), primitive: primitive.index(), message }
+                    })?);
+                }
+                Ok(GltfMesh { name: mesh.name().map(str::to_string), primitives })
+            })
+            .collect::<Result<Vec<GltfMesh>, GltfError>>()?;
+
+        // Whether each texture, and then each image, is sampled as color and as data
+        let mut texture_uses = vec![(false, false); textures.len()];
+        for material in &materials {
+            for texture in material.color_texture_refs() {
+                texture_uses[texture.texture].0 = true;
+            }
+            for texture in material.data_texture_refs() {
+                texture_uses[texture.texture].1 = true;
+            }
+        }
+        let mut image_uses = vec![(false, false); images.len()];
+        for (texture, (color, data)) in textures.iter().zip(&texture_uses) {
+            image_uses[texture.image].0 |= color;
+            image_uses[texture.image].1 |= data;
+        }
+
+        let mut images: Vec<GltfImage> = images
+            .into_iter()
+            .zip(&image_uses)
+            .map(|(image, &(color, data))| GltfImage {
+                width: image.width,
+                height: image.height,
+                pixels: to_rgba8(&image),
+                color_space: if color && !data { ColorSpace::Srgb } else { ColorSpace::Linear },
+            })
+            .collect();
+
+        // Images used both ways get an sRGB copy for the color uses
+        let mut srgb_images: Vec<usize> = (0..images.len()).collect();
+        for (index, &(color, data)) in image_uses.iter().enumerate() {
+            if color && data {
+                srgb_images[index] = images.len();
+                images.push(GltfImage { color_space: ColorSpace::Srgb, ..images[index].clone() });
+            }
+        }
+
+        // Textures sampled as color move to the sRGB images, and those also sampled as data are
+        // split in two, with the materials' color uses pointing at the copy
+        let mut color_textures: Vec<usize> = (0..textures.len()).collect();
+        for (index, &(color, data)) in texture_uses.iter().enumerate() {
+            let srgb_image = srgb_images[textures[index].image];
+            if color && data {
+                color_textures[index] = textures.len();
+                textures.push(GltfTexture { image: srgb_image, ..textures[index].clone() });
+            } else if color {
+                textures[index].image = srgb_image;
+            }
+        }
+        for material in &mut materials {
+            for texture in [&mut material.base_color_texture, &mut material.emissive_texture].into_iter().flatten() {
+                texture.texture = color_textures[texture.texture];
+            }
+        }
+
+        Ok(GltfScene { nodes, roots, meshes, materials, textures, images })
+    }
+
+    /** The transform from `node`'s space to the scene's, combining all of its ancestors.
+     */
+    pub fn world_transform(&self, node: usize) -> Mat4 {
+        let mut matrix = self.nodes[node].transform.model_matrix();
+        let mut parent = self.nodes[node].parent;
+        while let Some(index) = parent {
+            matrix = self.nodes[index].transform.model_matrix() * matrix;
+            parent = self.nodes[index].parent;
+        }
+        matrix
+    }
+
+    /** Every mesh reachable from the scene's roots paired with its world transform, in
+     * depth-first order. A mesh referenced by several nodes appears once per node.
+     */
+    pub fn mesh_instances(&self) -> Vec<(usize, Mat4)> {
+        let mut instances = Vec::new();
+        let mut stack: Vec<(usize, Mat4)> = self.roots.iter().rev().map(|&root| (root, Mat4::IDENTITY)).collect();
+        while let Some((index, parent)) = stack.pop() {
+            let node = &self.nodes[index];
+            let world = parent * node.transform.model_matrix();
+            if let Some(mesh) = node.mesh {
+                instances.push((mesh, world));
+            }
+            stack.extend(node.children.iter().rev().map(|&child| (child, world)));
+        }
+        instances
+    }
+
+    /** Uploads every primitive's geometry and creates a texture and sampler for each glTF
+     * texture.
+     */
+    pub fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> GpuScene {
+        let meshes = self
+            .meshes
+            .iter()
+            .map(|mesh| mesh.primitives.iter().map(|primitive| primitive.mesh.upload(device)).collect())
+            .collect();
+
+        let images: Vec<wgpu::Texture> = self.images.iter().map(|image| upload_image(device, queue, image)).collect();
+
+        let textures = self
+            .textures
+            .iter()
+            .map(|texture| {
+                let view = images[texture.image].create_view(&wgpu::TextureViewDescriptor::default());
+                let sampler = SamplerBuilder::new()
+                    .label("glTF sampler")
+                    .address_mode_u(texture.address_mode_u)
+                    .address_mode_v(texture.address_mode_v)
+                    .mag_filter(texture.mag_filter)
+                    .min_filter(texture.min_filter)
+                    .mipmap_filter(texture.mipmap_filter)
+                    .build(device);
+                GpuTexture { view, sampler }
+            })
+            .collect();
+
+        GpuScene { meshes, images, textures }
+    }
+}
+
+pub struct GpuTexture {
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+/** The GPU side of a `GltfScene`, indexed the same way.
+ */
+pub struct GpuScene {
+    // One `GpuMesh` per primitive of each mesh
+    pub meshes: Vec<Vec<GpuMesh>>,
+    pub images: Vec<wgpu::Texture>,
+    pub textures: Vec<GpuTexture>,
+}
+
+// `tex_coord` is the UV set the primitive's material samples its textures with. Primitives
+// without positions are skipped, and ones whose accessors disagree on the vertex count fail.
+fn load_primitive(primitive: &::gltf::Primitive, buffers: &[::gltf::buffer::Data], tex_coord: u32) -> Result<Option<GltfPrimitive>, String> {
+    use ::gltf::mesh::Mode;
+
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data[..]));
+    let Some(positions) = reader.read_positions() else {
+        return Ok(None);
+    };
+    let positions: Vec<Vec3> = positions.map(Vec3::from).collect();
+    let normals: Option<Vec<Vec3>> = reader.read_normals().map(|normals| normals.map(Vec3::from).collect());
+    let tangents: Option<Vec<[f32; 4]>> = reader.read_tangents().map(|tangents| tangents.collect());
+    let uvs: Option<Vec<Vec2>> = reader.read_tex_coords(tex_coord).map(|uvs| uvs.into_f32().map(Vec2::from).collect());
+    let mut indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+
+    let check_length = |attribute: &str, length: Option<usize>| match length {
+        Some(length) if length != positions.len() => {
+            Err(format!("{attribute} has {length} values for {} positions", positions.len()))
+        }
+        _ => Ok(()),
+    };
+    check_length("NORMAL", normals.as_ref().map(Vec::len))?;
+    check_length("TANGENT", tangents.as_ref().map(Vec::len))?;
+    check_length(&format!("TEXCOORD_{tex_coord}"), uvs.as_ref().map(Vec::len))?;
+    if let Some(&index) = indices.iter().find(|&&index| index as usize >= positions.len()) {
+        return Err(format!("index {index} is out of range for {} positions", positions.len()));
+    }
+
+    let topology = match primitive.mode() {
+        Mode::Points => wgpu::PrimitiveTopology::PointList,
+        Mode::Lines => wgpu::PrimitiveTopology::LineList,
+        Mode::LineStrip => wgpu::PrimitiveTopology::LineStrip,
+        Mode::Triangles => wgpu::PrimitiveTopology::TriangleList,
+        Mode::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
+        // wgpu has no loops or fans, so spell them out as lists
+        Mode::LineLoop => {
+            let first = indices.first().copied();
+            indices = indices.windows(2).flatten().copied().chain(indices.last().copied()).chain(first).collect();
+            wgpu::PrimitiveTopology::LineList
+        }
+        Mode::TriangleFan => {
+            indices = (1..indices.len().saturating_sub(1))
+                .flat_map(|i| [indices[0], indices[i], indices[i + 1]])
+                .collect();
+            wgpu::PrimitiveTopology::TriangleList
+        }
+    };
+
+    let vertices = positions
+        .iter()
+        .enumerate()
+        .map(|(i, &position)| {
+            let normal = normals.as_ref().map_or(Vec3::ZERO, |normals| normals[i]);
+            let uv = uvs.as_ref().map_or(Vec2::ZERO, |uvs| uvs[i]);
+            let mut vertex = MeshVertex::new(position, normal, uv);
+            if let Some(tangents) = &tangents {
+                vertex.tangent = tangents[i];
+            }
+            vertex
+        })
+        .collect();
+    let mut mesh = Mesh::new(vertices, indices).with_topology(topology);
+
+    // The spec asks for flat normals when they are missing; smooth ones are a close enough stand
+    // in without splitting vertices.
+    if topology == wgpu::PrimitiveTopology::TriangleList {
+        if normals.is_none() {
+            let normals = smooth_normals(&mesh);
+            for (vertex, normal) in mesh.vertices.iter_mut().zip(normals) {
+                vertex.normal = normal.into();
+            }
+        }
+        if tangents.is_none() {
+            compute_tangents(&mut mesh);
+        }
+    }
+
+    Ok(Some(GltfPrimitive { mesh, material: primitive.material().index() }))
+}
+
+fn texture_ref(info: Option<::gltf::texture::Info>) -> Option<GltfTextureRef> {
+    info.map(|info| GltfTextureRef { texture: info.texture().index(), tex_coord: info.tex_coord() })
+}
+
+fn load_material(material: &::gltf::Material) -> GltfMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    let normal = material.normal_texture();
+    let occlusion = material.occlusion_texture();
+    GltfMaterial {
+        name: material.name().map(str::to_string),
+        base_color_factor: pbr.base_color_factor(),
+        base_color_texture: texture_ref(pbr.base_color_texture()),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        metallic_roughness_texture: texture_ref(pbr.metallic_roughness_texture()),
+        normal_texture: normal.as_ref().map(|normal| GltfTextureRef { texture: normal.texture().index(), tex_coord: normal.tex_coord() }),
+        normal_scale: normal.as_ref().map_or(1.0, |normal| normal.scale()),
+        occlusion_texture: occlusion.as_ref().map(|occlusion| GltfTextureRef { texture: occlusion.texture().index(), tex_coord: occlusion.tex_coord() }),
+        occlusion_strength: occlusion.as_ref().map_or(1.0, |occlusion| occlusion.strength()),
+        emissive_factor: material.emissive_factor(),
+        emissive_texture: texture_ref(material.emissive_texture()),
+        alpha_mode: material.alpha_mode(),
+        alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+        double_sided: material.double_sided(),
+    }
+}
+
+fn load_texture(texture: &::gltf::Texture) -> GltfTexture {
+    use ::gltf::texture::{MagFilter, MinFilter, WrappingMode};
+
+    let address_mode = |mode: WrappingMode| match mode {
+        WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => wgpu::AddressMode::Repeat,
+    };
+    let sampler = texture.sampler();
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => wgpu::FilterMode::Nearest,
+        _ => wgpu::FilterMode::Linear,
+    };
+    // glTF folds the mipmap filter into the minification filter
+    let (min_filter, mipmap_filter) = match sampler.min_filter() {
+        Some(MinFilter::Nearest | MinFilter::NearestMipmapNearest) => (wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest),
+        Some(MinFilter::NearestMipmapLinear) => (wgpu::FilterMode::Nearest, wgpu::FilterMode::Linear),
+        Some(MinFilter::LinearMipmapNearest) => (wgpu::FilterMode::Linear, wgpu::FilterMode::Nearest),
+        _ => (wgpu::FilterMode::Linear, wgpu::FilterMode::Linear),
+    };
+    GltfTexture {
+        image: texture.source().index(),
+        address_mode_u: address_mode(sampler.wrap_s()),
+        address_mode_v: address_mode(sampler.wrap_t()),
+        mag_filter,
+        min_filter,
+        mipmap_filter,
+    }
+}
+
+// Expands any of glTF's decoded pixel formats to 8-bit RGBA
+fn to_rgba8(image: &::gltf::image::Data) -> Vec<u8> {
+    use ::gltf::image::Format;
+
+    let unorm16 = |bytes: &[u8]| (u16::from_le_bytes([bytes[0], bytes[1]]) >> 8) as u8;
+    let float32 = |bytes: &[u8]| (f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).clamp(0.0, 1.0) * 255.0).round() as u8;
+    let (channels, bytes_per_channel) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+
+    image
+        .pixels
+        .chunks_exact(channels * bytes_per_channel)
+        .flat_map(|pixel| {
+            let channel = |c: usize| {
+                let bytes = &pixel[c * bytes_per_channel..];
+                match bytes_per_channel {
+                    1 => bytes[0],
+                    2 => unorm16(bytes),
+                    _ => float32(bytes),
+                }
+            };
+            match channels {
+                // Single channel images are grayscale, two channel ones grayscale with alpha
+                1 => [channel(0), channel(0), channel(0), 255],
+                2 => [channel(0), channel(0), channel(0), channel(1)],
+                3 => [channel(0), channel(1), channel(2), 255],
+                _ => [channel(0), channel(1), channel(2), channel(3)],
+            }
+        })
+        .collect()
+}
+
+fn upload_image(device: &wgpu::Device, queue: &wgpu::Queue, image: &GltfImage) -> wgpu::Texture {
+    TextureBuilder::new(image.width, image.height)
+        .label("glTF image")
+        .format(image.color_space.rgba8_format())
+        .build_with_data(device, queue, &image.pixels)
+}
@@ -0,0 +1,5 @@
+pub mod gltf;
+pub mod obj;
+
+pub use self::gltf::*;
+pub use obj::*;
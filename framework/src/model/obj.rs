@@ -0,0 +1,314 @@
+/*
+   Loader for Wavefront OBJ models and their MTL material libraries.
+
+   Supports the subset of the format produced by common exporters: `v`, `vt`, `vn`, polygonal `f`
+   (triangulated as fans, with negative indices), `o`/`g` to split objects, and `mtllib`/`usemtl`.
+   Each object is split further wherever its material changes, so every `ObjMesh` can be drawn
+   with a single material. Statements outside that subset, material libraries that can't be
+   read, and materials missing from the libraries are skipped with a warning rather than failing
+   the whole model.
+   */
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use glam::{Vec2, Vec3};
+
+use crate::mesh::{compute_tangents, smooth_normals, GpuMesh, Mesh, MeshVertex};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    Parse { file: String, line: usize, message: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(e) => write!(f, "{e}"),
+            ObjError::Parse { file, line, message } => write!(f, "{file}:{line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    fn from(e: io::Error) -> Self {
+        ObjError::Io(e)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    pub shininess: f32,
+    // Opacity, 1.0 being fully opaque
+    pub dissolve: f32,
+    // Texture paths as written in the MTL file, relative to it
+    pub diffuse_texture: Option<PathBuf>,
+    pub normal_texture: Option<PathBuf>,
+}
+
+impl ObjMaterial {
+    fn new(name: &str) -> Self {
+        ObjMaterial {
+            name: name.to_string(),
+            ambient: [0.0; 3],
+            diffuse: [1.0; 3],
+            specular: [0.0; 3],
+            shininess: 0.0,
+            dissolve: 1.0,
+            diffuse_texture: None,
+            normal_texture: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ObjMesh {
+    pub name: String,
+    // Index into `ObjModel::materials`
+    pub material: Option<usize>,
+    pub mesh: Mesh<MeshVertex>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+}
+
+impl ObjModel {
+    /** Loads the OBJ file at `path` along with any material libraries it references, which are
+     * looked up relative to the OBJ file.
+     */
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let source = fs::read_to_string(path)?;
+        Self::parse(&path.display().to_string(), &source, |library| {
+            fs::read_to_string(directory.join(library))
+        })
+    }
+
+    /** Parses OBJ `source`, calling `load_library` with the name of each `mtllib` to get its
+     * contents. `file` is only used in error messages.
+     */
+    pub fn parse(
+        file: &str,
+        source: &str,
+        mut load_library: impl FnMut(&str) -> io::Result<String>,
+    ) -> Result<Self, ObjError> {
+        let mut parser = ObjParser::default();
+        let mut materials = Vec::new();
+
+        for (line_index, line) in source.lines().enumerate() {
+            let error = |message: String| ObjError::Parse { file: file.to_string(), line: line_index + 1, message };
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else { continue };
+            let rest: Vec<&str> = words.collect();
+
+            match keyword {
+                // Anything after x, y and z is a w coordinate or a vertex color, neither of which is used
+                "v" => parser.positions.push(parse_floats::<3>(&rest[..rest.len().min(3)]).map_err(error)?.into()),
+                "vn" => parser.normals.push(parse_floats::<3>(&rest).map_err(error)?.into()),
+                "vt" => {
+                    // The optional third (w) coordinate is ignored
+                    let [u, v] = parse_floats::<2>(&rest[..rest.len().min(2)]).map_err(error)?;
+                    // OBJ's v axis points up, textures' down
+                    parser.uvs.push(Vec2::new(u, 1.0 - v));
+                }
+                "f" => parser.face(&rest).map_err(error)?,
+                "o" | "g" => parser.start_mesh(rest.join(" "), parser.material),
+                "usemtl" => {
+                    let name = rest.join(" ");
+                    let material = materials.iter().position(|m: &ObjMaterial| m.name == name);
+                    if material.is_none() {
+                        log::warn!("{file}:{}: unknown material `{name}`, using none", line_index + 1);
+                    }
+                    parser.start_mesh(parser.name.clone(), material);
+                }
+                "mtllib" => {
+                    // One file, whose name may contain spaces
+                    let library = rest.join(" ");
+                    match load_library(&library) {
+                        Ok(contents) => materials.extend(parse_mtl(&library, &contents)?),
+                        Err(e) => log::warn!("{file}:{}: skipping material library `{library}`: {e}", line_index + 1),
+                    }
+                }
+                // Smoothing groups, lines and points don't affect triangle meshes
+                "s" | "l" | "p" => {}
+                _ => log::warn!("{file}:{}: skipping unsupported statement `{keyword}`", line_index + 1),
+            }
+        }
+
+        parser.finish_mesh();
+        Ok(ObjModel { meshes: parser.meshes, materials })
+    }
+
+    pub fn upload(&self, device: &wgpu::Device) -> Vec<GpuMesh> {
+        self.meshes.iter().map(|mesh| mesh.mesh.upload(device)).collect()
+    }
+}
+
+/** Parses the materials defined in MTL `source`. `file` is only used in error messages.
+ */
+pub fn parse_mtl(file: &str, source: &str) -> Result<Vec<ObjMaterial>, ObjError> {
+    let mut materials: Vec<ObjMaterial> = Vec::new();
+    for (line_index, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError::Parse { file: file.to_string(), line: line_index + 1, message };
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else { continue };
+        let rest: Vec<&str> = words.collect();
+
+        if keyword == "newmtl" {
+            materials.push(ObjMaterial::new(&rest.join(" ")));
+            continue;
+        }
+        let material = materials
+            .last_mut()
+            .ok_or_else(|| error(format!("`{keyword}` before any `newmtl`")))?;
+        match keyword {
+            "Ka" => material.ambient = parse_floats(&rest).map_err(error)?,
+            "Kd" => material.diffuse = parse_floats(&rest).map_err(error)?,
+            "Ks" => material.specular = parse_floats(&rest).map_err(error)?,
+            "Ns" => material.shininess = parse_floats::<1>(&rest).map_err(error)?[0],
+            "d" => material.dissolve = parse_floats::<1>(&rest).map_err(error)?[0],
+            "Tr" => material.dissolve = 1.0 - parse_floats::<1>(&rest).map_err(error)?[0],
+            // Texture options such as `-bm 1.0` precede the file name, which comes last
+            "map_Kd" => material.diffuse_texture = rest.last().map(PathBuf::from),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_texture = rest.last().map(PathBuf::from),
+            // Remaining properties (illumination model, emission, ...) aren't used by the framework
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+fn parse_floats<const N: usize>(words: &[&str]) -> Result<[f32; N], String> {
+    if words.len() != N {
+        return Err(format!("expected {N} numbers, found {}", words.len()));
+    }
+    let mut values = [0.0; N];
+    for (value, word) in values.iter_mut().zip(words) {
+        *value = word.parse().map_err(|_| format!("`{word}` is not a number"))?;
+    }
+    Ok(values)
+}
+
+// Position, UV and normal indices of one face corner, 0-based
+type Corner = (usize, Option<usize>, Option<usize>);
+
+#[derive(Default)]
+struct ObjParser {
+    positions: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    normals: Vec<Vec3>,
+
+    // The mesh currently being built
+    name: String,
+    material: Option<usize>,
+    vertices: Vec<MeshVertex>,
+    indices: Vec<u32>,
+    // Vertices without a normal in the file, which get one averaged from their faces
+    missing_normals: Vec<bool>,
+    // Maps each distinct corner to the vertex already emitted for it
+    corner_vertices: HashMap<Corner, u32>,
+
+    meshes: Vec<ObjMesh>,
+}
+
+impl ObjParser {
+    fn start_mesh(&mut self, name: String, material: Option<usize>) {
+        self.finish_mesh();
+        self.name = name;
+        self.material = material;
+    }
+
+    fn finish_mesh(&mut self) {
+        self.corner_vertices.clear();
+        let missing_normals = std::mem::take(&mut self.missing_normals);
+        if self.indices.is_empty() {
+            self.vertices.clear();
+            return;
+        }
+
+        let mut mesh = Mesh::new(std::mem::take(&mut self.vertices), std::mem::take(&mut self.indices));
+        smooth_missing_normals(&mut mesh, &missing_normals);
+        compute_tangents(&mut mesh);
+        self.meshes.push(ObjMesh { name: self.name.clone(), material: self.material, mesh });
+    }
+
+    // Resolves a 1-based (or negative, relative to the end) OBJ index
+    fn resolve(word: &str, count: usize, kind: &str) -> Result<usize, String> {
+        let index: i64 = word.parse().map_err(|_| format!("`{word}` is not a {kind} index"))?;
+        let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+        if resolved < 0 || resolved >= count as i64 {
+            return Err(format!("{kind} index {index} out of range, {count} defined"));
+        }
+        Ok(resolved as usize)
+    }
+
+    fn corner(&self, word: &str) -> Result<Corner, String> {
+        let mut parts = word.split('/');
+        let position = Self::resolve(parts.next().unwrap_or(""), self.positions.len(), "position")?;
+        let uv = match parts.next() {
+            Some("") | None => None,
+            Some(uv) => Some(Self::resolve(uv, self.uvs.len(), "texture coordinate")?),
+        };
+        let normal = match parts.next() {
+            Some("") | None => None,
+            Some(normal) => Some(Self::resolve(normal, self.normals.len(), "normal")?),
+        };
+        Ok((position, uv, normal))
+    }
+
+    fn vertex(&mut self, corner: Corner) -> u32 {
+        if let Some(&index) = self.corner_vertices.get(&corner) {
+            return index;
+        }
+        let (position, uv, normal) = corner;
+        self.vertices.push(MeshVertex::new(
+            self.positions[position],
+            normal.map_or(Vec3::ZERO, |normal| self.normals[normal]),
+            uv.map_or(Vec2::ZERO, |uv| self.uvs[uv]),
+        ));
+        self.missing_normals.push(normal.is_none());
+        let index = self.vertices.len() as u32 - 1;
+        self.corner_vertices.insert(corner, index);
+        index
+    }
+
+    fn face(&mut self, words: &[&str]) -> Result<(), String> {
+        if words.len() < 3 {
+            return Err(format!("a face needs at least 3 vertices, found {}", words.len()));
+        }
+        let corners = words.iter().map(|word| self.corner(word)).collect::<Result<Vec<_>, _>>()?;
+        let indices: Vec<u32> = corners.into_iter().map(|corner| self.vertex(corner)).collect();
+        for i in 1..indices.len() - 1 {
+            self.indices.extend_from_slice(&[indices[0], indices[i], indices[i + 1]]);
+        }
+        Ok(())
+    }
+}
+
+// Gives vertices that had no normal in the file the average of their faces' normals
+fn smooth_missing_normals(mesh: &mut Mesh<MeshVertex>, missing: &[bool]) {
+    if !missing.contains(&true) {
+        return;
+    }
+    let normals = smooth_normals(mesh);
+    for ((vertex, normal), missing) in mesh.vertices.iter_mut().zip(normals).zip(missing) {
+        if *missing {
+            vertex.normal = normal.into();
+        }
+    }
+}
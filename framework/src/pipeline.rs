@@ -0,0 +1,297 @@
+/*
+   Creating render pipelines on demand and reusing them.
+
+   A `PipelineCache` owns the shaders and pipeline layouts that pipelines are built from, handing
+   out ids for them. A `RenderPipelineKey` names a shader variant by id and defines and then
+   describes the rest of the pipeline; the first lookup of a key compiles the variant if needed
+   and creates the pipeline, and later lookups return the same one. Keys are plain data, so
+   pipelines differing only in target format, sample count or blend state are easy to ask for.
+
+   Shader variants are `HotShader`s, so `reload` rebuilds the pipelines of any that were edited.
+   Replacing a shader or changing the surface format drops the pipelines that were made for the
+   old one.
+   */
+use std::collections::{BTreeMap, HashMap};
+
+use crate::shader::{HotShader, ShaderSource};
+
+/** A shader added to a `PipelineCache`.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ShaderId(usize);
+
+/** A pipeline layout added to a `PipelineCache`.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LayoutId(usize);
+
+// `wgpu::VertexBufferLayout` borrows its attributes, so keys keep their own copy
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct VertexBufferKey {
+    array_stride: wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode,
+    attributes: Vec<wgpu::VertexAttribute>,
+}
+
+/** Everything a render pipeline is created from. Entry points default to `vs_main` and
+ * `fs_main`, and the layout to one derived from the shader.
+ *
+ * ```ignore
+ * let key = RenderPipelineKey::new(shader)
+ *     .layout(layout)
+ *     .define("SHADOWS", "")
+ *     .vertex_buffer(MeshVertex::layout())
+ *     .target(context.swapchain_format())
+ *     .depth_stencil(depth_state);
+ * rpass.set_pipeline(pipelines.get_or_create(device, &key));
+ * ```
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RenderPipelineKey {
+    label: Option<&'static str>,
+    shader: ShaderId,
+    defines: BTreeMap<String, String>,
+    layout: Option<LayoutId>,
+    vertex_entry_point: &'static str,
+    fragment_entry_point: Option<&'static str>,
+    vertex_buffers: Vec<VertexBufferKey>,
+    targets: Vec<Option<wgpu::ColorTargetState>>,
+    primitive: wgpu::PrimitiveState,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+    sample_count: u32,
+}
+
+impl RenderPipelineKey {
+    pub fn new(shader: ShaderId) -> Self {
+        Self {
+            label: None,
+            shader,
+            defines: BTreeMap::new(),
+            layout: None,
+            vertex_entry_point: "vs_main",
+            fragment_entry_point: Some("fs_main"),
+            vertex_buffers: Vec::new(),
+            targets: Vec::new(),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            sample_count: 1,
+        }
+    }
+
+    pub fn label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /** Picks the shader variant compiled with `name` defined, see `shader::Preprocessor`.
+     */
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn layout(mut self, layout: LayoutId) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    pub fn vertex_entry_point(mut self, entry_point: &'static str) -> Self {
+        self.vertex_entry_point = entry_point;
+        self
+    }
+
+    pub fn fragment_entry_point(mut self, entry_point: &'static str) -> Self {
+        self.fragment_entry_point = Some(entry_point);
+        self
+    }
+
+    /** Leaves out the fragment stage, e.g. for depth-only passes.
+     */
+    pub fn vertex_only(mut self) -> Self {
+        self.fragment_entry_point = None;
+        self
+    }
+
+    pub fn vertex_buffer(mut self, layout: wgpu::VertexBufferLayout) -> Self {
+        self.vertex_buffers.push(VertexBufferKey {
+            array_stride: layout.array_stride,
+            step_mode: layout.step_mode,
+            attributes: layout.attributes.to_vec(),
+        });
+        self
+    }
+
+    /** Adds a color target, either a whole `wgpu::ColorTargetState` or just a format to write
+     * without blending.
+     */
+    pub fn target(mut self, target: impl Into<wgpu::ColorTargetState>) -> Self {
+        self.targets.push(Some(target.into()));
+        self
+    }
+
+    pub fn primitive(mut self, primitive: wgpu::PrimitiveState) -> Self {
+        self.primitive = primitive;
+        self
+    }
+
+    pub fn depth_stencil(mut self, depth_stencil: wgpu::DepthStencilState) -> Self {
+        self.depth_stencil = Some(depth_stencil);
+        self
+    }
+
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    fn uses_format(&self, format: wgpu::TextureFormat) -> bool {
+        self.targets.iter().flatten().any(|target| target.format == format)
+    }
+}
+
+/** Creates render pipelines the first time they're asked for and reuses them after that.
+ */
+#[derive(Default)]
+pub struct PipelineCache {
+    sources: Vec<ShaderSource>,
+    layouts: Vec<wgpu::PipelineLayout>,
+    // Compiled lazily for each set of defines a key asks for
+    shaders: HashMap<(ShaderId, BTreeMap<String, String>), HotShader>,
+    pipelines: HashMap<RenderPipelineKey, wgpu::RenderPipeline>,
+    surface_format: Option<wgpu::TextureFormat>,
+}
+
+impl PipelineCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /** Adds a shader for keys to refer to. Nothing is compiled until a pipeline needs it.
+     */
+    pub fn add_shader(&mut self, source: ShaderSource) -> ShaderId {
+        self.sources.push(source);
+        ShaderId(self.sources.len() - 1)
+    }
+
+    pub fn add_layout(&mut self, layout: wgpu::PipelineLayout) -> LayoutId {
+        self.layouts.push(layout);
+        LayoutId(self.layouts.len() - 1)
+    }
+
+    /** Swaps the source of `shader`, dropping its compiled variants and every pipeline made
+     * from them.
+     */
+    pub fn replace_shader(&mut self, shader: ShaderId, source: ShaderSource) {
+        self.sources[shader.0] = source;
+        self.shaders.retain(|(id, _), _| *id != shader);
+        self.pipelines.retain(|key, _| key.shader != shader);
+    }
+
+    /** Records the format the surface is configured with, dropping the pipelines that render to
+     * the previous one when it changes. Cheap enough to call every frame.
+     */
+    pub fn set_surface_format(&mut self, format: wgpu::TextureFormat) {
+        if let Some(previous) = self.surface_format.replace(format) {
+            if previous != format {
+                self.pipelines.retain(|key, _| !key.uses_format(previous));
+            }
+        }
+    }
+
+    /** Creates the pipeline for `key` unless there already is one.
+     */
+    pub fn prepare(&mut self, device: &wgpu::Device, key: &RenderPipelineKey) {
+        if self.pipelines.contains_key(key) {
+            return;
+        }
+        let variant = (key.shader, key.defines.clone());
+        let shader = self.shaders.entry(variant).or_insert_with(|| {
+            let source = key.defines.iter().fold(self.sources[key.shader.0].clone(), |source, (name, value)| source.define(name, value));
+            HotShader::new(device, source)
+        });
+        let pipeline = create_pipeline(device, key, shader.module(), &self.layouts);
+        self.pipelines.insert(key.clone(), pipeline);
+    }
+
+    /** The pipeline for `key`, if it has been created.
+     */
+    pub fn get(&self, key: &RenderPipelineKey) -> Option<&wgpu::RenderPipeline> {
+        self.pipelines.get(key)
+    }
+
+    pub fn get_or_create(&mut self, device: &wgpu::Device, key: &RenderPipelineKey) -> &wgpu::RenderPipeline {
+        self.prepare(device, key);
+        &self.pipelines[key]
+    }
+
+    /** Rebuilds the pipelines of every shader variant whose file changed, keeping the old ones if
+     * the new version fails to compile. Returns true if anything was rebuilt.
+     */
+    pub fn reload(&mut self, device: &wgpu::Device) -> bool {
+        let PipelineCache { layouts, shaders, pipelines, .. } = self;
+        let mut reloaded = false;
+        for ((id, defines), shader) in shaders.iter_mut() {
+            let keys: Vec<RenderPipelineKey> = pipelines.keys().filter(|key| key.shader == *id && key.defines == *defines).cloned().collect();
+            let rebuilt = shader.reload(device, |module| {
+                keys.into_iter().map(|key| {
+                    let pipeline = create_pipeline(device, &key, module, layouts);
+                    (key, pipeline)
+                }).collect::<Vec<_>>()
+            });
+            if let Some(rebuilt) = rebuilt {
+                pipelines.extend(rebuilt);
+                reloaded = true;
+            }
+        }
+        reloaded
+    }
+
+    /** The number of pipelines created and kept.
+     */
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    key: &RenderPipelineKey,
+    module: &wgpu::ShaderModule,
+    layouts: &[wgpu::PipelineLayout],
+) -> wgpu::RenderPipeline {
+    let buffers: Vec<wgpu::VertexBufferLayout> = key
+        .vertex_buffers
+        .iter()
+        .map(|buffer| wgpu::VertexBufferLayout {
+            array_stride: buffer.array_stride,
+            step_mode: buffer.step_mode,
+            attributes: &buffer.attributes,
+        })
+        .collect();
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: key.label,
+        layout: key.layout.map(|layout| &layouts[layout.0]),
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: key.vertex_entry_point,
+            buffers: &buffers,
+        },
+        primitive: key.primitive,
+        depth_stencil: key.depth_stencil.clone(),
+        multisample: wgpu::MultisampleState {
+            count: key.sample_count,
+            ..Default::default()
+        },
+        fragment: key.fragment_entry_point.map(|entry_point| wgpu::FragmentState {
+            module,
+            entry_point,
+            targets: &key.targets,
+        }),
+        multiview: None,
+    })
+}
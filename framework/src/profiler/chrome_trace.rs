@@ -0,0 +1,213 @@
+use std::{fmt::Write as _, path::{Path, PathBuf}, time::Instant};
+
+use super::{GpuProfiler, ScopeTiming};
+
+/** Set to a file name to record a trace from startup until the example exits, e.g.
+ * `CHROME_TRACE=frames.json cargo run --bin cube`.
+ */
+pub const CHROME_TRACE_ENV: &str = "CHROME_TRACE";
+/** Where a trace started without `CHROME_TRACE` is written.
+ */
+pub const DEFAULT_TRACE_PATH: &str = "chrome_trace.json";
+
+// Tracks of the trace, shown as threads of a single process
+const CPU_TRACK: u32 = 1;
+const GPU_TRACK: u32 = 2;
+
+struct Event {
+    name: String,
+    track: u32,
+    start: Instant,
+    duration: std::time::Duration,
+}
+
+/** Records CPU and GPU scopes as a Chrome Trace Event file, to be opened in chrome://tracing or
+ * Perfetto.
+ *
+ * Wrap the parts of a frame in `scope`, and pass a `GpuProfiler` to `record_profiler` once per
+ * frame for its scopes, which show on a track of their own when they were timed on the GPU.
+ * Nothing is recorded while the trace is stopped, so the calls can stay in place.
+ *
+ * ```ignore
+ * let (frame, view) = trace.scope("Acquire", || context.frame_view(&Default::default()));
+ * // ...
+ * trace.scope("Submit", || context.queue.submit(Some(encoder.finish())));
+ * trace.scope("Present", || frame.present());
+ * trace.record_profiler(&profiler);
+ * ```
+ */
+pub struct ChromeTrace {
+    path: PathBuf,
+    recording: bool,
+    events: Vec<Event>,
+    // Timestamps in the file count from here
+    origin: Instant,
+    // The profiler frame recorded last, so that a lagging one isn't recorded twice
+    profiler_frame: u64,
+}
+
+impl ChromeTrace {
+    /** A stopped trace that will be written to `path`.
+     */
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), recording: false, events: Vec::new(), origin: Instant::now(), profiler_frame: 0 }
+    }
+
+    /** Starts recording straight away if `CHROME_TRACE` is set, writing to the path it names, and
+     * otherwise waits to be started, writing to `DEFAULT_TRACE_PATH`.
+     */
+    pub fn from_env() -> Self {
+        match std::env::var_os(CHROME_TRACE_ENV).filter(|path| !path.is_empty()) {
+            Some(path) => {
+                let mut trace = Self::new(path);
+                trace.start();
+                trace
+            }
+            None => Self::new(DEFAULT_TRACE_PATH),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /** Starts a new recording, dropping whatever was recorded before.
+     */
+    pub fn start(&mut self) {
+        self.events.clear();
+        self.origin = Instant::now();
+        self.recording = true;
+        log::info!("Recording a trace to {}", self.path.display());
+    }
+
+    /** Stops recording and writes the trace to its path. Does nothing if it wasn't recording.
+     */
+    pub fn stop(&mut self) -> std::io::Result<()> {
+        if !self.recording {
+            return Ok(());
+        }
+        self.recording = false;
+        self.write(&self.path)?;
+        log::info!("Wrote a trace of {} events to {}", self.events.len(), self.path.display());
+        Ok(())
+    }
+
+    /** Starts or stops recording, e.g. from a key press.
+     */
+    pub fn toggle(&mut self) -> std::io::Result<()> {
+        if self.recording {
+            self.stop()
+        } else {
+            self.start();
+            Ok(())
+        }
+    }
+
+    /** Runs `f` and records how long it took on the CPU track.
+     */
+    pub fn scope<R>(&mut self, name: &str, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.record_cpu(name, start, Instant::now());
+        result
+    }
+
+    /** Records CPU work that ran from `start` to `end`, for work that doesn't fit in a closure.
+     */
+    pub fn record_cpu(&mut self, name: &str, start: Instant, end: Instant) {
+        self.push(name, CPU_TRACK, start, end - start);
+    }
+
+    /** Records the profiler's scopes if it has reported a frame since the last call: how long
+     * they took to record on the CPU track, and how long they took to run on the GPU track.
+     */
+    pub fn record_profiler(&mut self, profiler: &GpuProfiler) {
+        if profiler.results_frame() == self.profiler_frame {
+            return;
+        }
+        self.profiler_frame = profiler.results_frame();
+        for timing in profiler.results() {
+            self.record_timing(timing);
+        }
+    }
+
+    /** Records a single scope timing, see `record_profiler`.
+     */
+    pub fn record_timing(&mut self, timing: &ScopeTiming) {
+        self.push(&timing.name, CPU_TRACK, timing.start, timing.cpu);
+        if let Some((start, duration)) = timing.gpu_start.zip(timing.gpu) {
+            self.push(&timing.name, GPU_TRACK, start, duration);
+        }
+    }
+
+    fn push(&mut self, name: &str, track: u32, start: Instant, duration: std::time::Duration) {
+        // Scopes from before the recording started, e.g. a profiler frame still in flight
+        if self.recording && start >= self.origin {
+            self.events.push(Event { name: name.to_owned(), track, start, duration });
+        }
+    }
+
+    /** The number of scopes recorded so far.
+     */
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /** The trace in the Chrome Trace Event format, with times in microseconds.
+     */
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"displayTimeUnit\":\"ms\",\"traceEvents\":[");
+        for (track, name) in [(CPU_TRACK, "CPU"), (GPU_TRACK, "GPU")] {
+            write!(json, "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":{track},\"args\":{{\"name\":\"{name}\"}}}},").unwrap();
+        }
+        for event in &self.events {
+            let timestamp = (event.start - self.origin).as_secs_f64() * 1e6;
+            write!(
+                json,
+                "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{timestamp:.3},\"dur\":{:.3},\"pid\":1,\"tid\":{}}},",
+                escape(&event.name),
+                if event.track == GPU_TRACK { "gpu" } else { "cpu" },
+                event.duration.as_secs_f64() * 1e6,
+                event.track,
+            )
+            .unwrap();
+        }
+        json.pop();
+        json.push_str("]}");
+        json
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+}
+
+impl Drop for ChromeTrace {
+    // So that a trace started from the environment is written when the example exits
+    fn drop(&mut self) {
+        if let Err(error) = self.stop() {
+            log::error!("Error in writing the trace to {}: {error}", self.path.display());
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            character if character.is_control() => write!(escaped, "\\u{:04x}", character as u32).unwrap(),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
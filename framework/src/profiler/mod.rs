@@ -0,0 +1,343 @@
+/*
+   Timing passes on the GPU.
+
+   Each named scope covers one render or compute pass. When the device has
+   `Features::TIMESTAMP_QUERY`, the pass writes a timestamp as it begins and ends; the timestamps
+   are resolved into a buffer at the end of the frame and read back once the GPU is done with it,
+   which takes a few frames, so results always lag a little behind. The CPU time spent recording
+   each scope is measured either way, so without the feature, e.g. on the fallback adapter, the
+   profiler still reports something.
+
+   A `ChromeTrace` records the scopes of a whole session, along with CPU work such as acquiring
+   and presenting frames, for viewing in chrome://tracing or Perfetto.
+
+   ```ignore
+   let scope = profiler.begin_scope("Scene");
+   {
+       let mut rpass = RenderPassBuilder::new()
+           .clear(&view, wgpu::Color::BLACK)
+           .timestamp_writes(profiler.render_timestamp_writes(scope))
+           .build(&mut encoder);
+       // ...
+   }
+   profiler.end_scope(scope);
+   profiler.resolve(&mut encoder);
+   queue.submit(Some(encoder.finish()));
+   profiler.end_frame(&device);
+   ```
+   */
+pub mod chrome_trace;
+
+pub use chrome_trace::*;
+
+use std::{cell::Cell, collections::VecDeque, sync::mpsc, time::{Duration, Instant}};
+
+/** Scopes timed on the GPU per frame; later ones are only timed on the CPU.
+ */
+pub const MAX_SCOPES: u32 = 32;
+// Frames waiting to be read back before new ones are skipped rather than queued
+const MAX_PENDING_FRAMES: usize = 4;
+const TIMESTAMP_SIZE: wgpu::BufferAddress = std::mem::size_of::<u64>() as wgpu::BufferAddress;
+// Each scope's pair of timestamps is resolved separately, to an offset that has to be aligned
+const SCOPE_STRIDE: wgpu::BufferAddress = wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT;
+const QUERY_BUFFER_SIZE: wgpu::BufferAddress = MAX_SCOPES as wgpu::BufferAddress * SCOPE_STRIDE;
+
+/** A scope begun with `GpuProfiler::begin_scope`, valid until the end of the frame.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScopeId(usize);
+
+/** How long one scope took in a finished frame.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScopeTiming {
+    pub name: String,
+    /** Time spent recording the scope's commands.
+     */
+    pub cpu: Duration,
+    /** When recording began.
+     */
+    pub start: Instant,
+    /** Time the GPU spent executing them, if it could be measured.
+     */
+    pub gpu: Option<Duration>,
+    /** Roughly when the GPU began executing them. The GPU's clock can't be compared with the
+     * CPU's, so the frame's first timestamp is lined up with the frame's submission.
+     */
+    pub gpu_start: Option<Instant>,
+}
+
+impl std::fmt::Display for ScopeTiming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        match self.gpu {
+            Some(gpu) => write!(f, "{}: {:.2} ms GPU, {:.2} ms CPU", self.name, milliseconds(gpu), milliseconds(self.cpu)),
+            None => write!(f, "{}: {:.2} ms CPU", self.name, milliseconds(self.cpu)),
+        }
+    }
+}
+
+struct Scope {
+    name: String,
+    start: Instant,
+    cpu: Option<Duration>,
+    // Whether a pass was given the scope's timestamp writes. Only those queries are resolved,
+    // since reading ones that were never written isn't defined on every backend
+    timed: Cell<bool>,
+}
+
+struct FinishedScope {
+    name: String,
+    start: Instant,
+    cpu: Duration,
+    timed: bool,
+}
+
+// Everything needed for GPU timing, which only exists with `TIMESTAMP_QUERY`
+struct Queries {
+    set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    // Readback buffers that aren't waiting to be read, one per frame that can be pending
+    free_buffers: Vec<wgpu::Buffer>,
+    // Nanoseconds per timestamp tick
+    period: f64,
+}
+
+struct PendingFrame {
+    scopes: Vec<FinishedScope>,
+    submitted: Instant,
+    buffer: wgpu::Buffer,
+    mapped: mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+/** Times named scopes on the GPU where possible and on the CPU otherwise.
+ *
+ * Per frame: wrap each pass in `begin_scope` and `end_scope`, passing the scope's timestamp
+ * writes to the pass, then call `resolve` before finishing the encoder and `end_frame` after
+ * submitting it. `results` holds the timings of the latest frame that has been read back.
+ */
+pub struct GpuProfiler {
+    queries: Option<Queries>,
+    scopes: Vec<Scope>,
+    // Resolved this frame, mapped in `end_frame`
+    resolved: Option<wgpu::Buffer>,
+    pending: VecDeque<PendingFrame>,
+    results: Vec<ScopeTiming>,
+    results_frame: u64,
+}
+
+impl GpuProfiler {
+    /** Uses timestamp queries if `device` was created with `Features::TIMESTAMP_QUERY`.
+     */
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let queries = device.features().contains(wgpu::Features::TIMESTAMP_QUERY).then(|| Queries {
+            set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Profiler queries"),
+                ty: wgpu::QueryType::Timestamp,
+                count: MAX_SCOPES * 2,
+            }),
+            resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Profiler resolve buffer"),
+                size: QUERY_BUFFER_SIZE,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            free_buffers: (0..MAX_PENDING_FRAMES)
+                .map(|_| {
+                    device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("Profiler readback buffer"),
+                        size: QUERY_BUFFER_SIZE,
+                        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    })
+                })
+                .collect(),
+            period: queue.get_timestamp_period() as f64,
+        });
+        if queries.is_none() {
+            log::info!("Timestamp queries aren't available, profiling on the CPU only");
+        }
+        GpuProfiler { queries, scopes: Vec::new(), resolved: None, pending: VecDeque::new(), results: Vec::new(), results_frame: 0 }
+    }
+
+    /** Whether scopes are timed on the GPU.
+     */
+    pub fn has_gpu_timing(&self) -> bool {
+        self.queries.is_some()
+    }
+
+    pub fn begin_scope(&mut self, name: impl Into<String>) -> ScopeId {
+        self.scopes.push(Scope { name: name.into(), start: Instant::now(), cpu: None, timed: Cell::new(false) });
+        ScopeId(self.scopes.len() - 1)
+    }
+
+    pub fn end_scope(&mut self, scope: ScopeId) {
+        let scope = &mut self.scopes[scope.0];
+        scope.cpu = Some(scope.start.elapsed());
+    }
+
+    // The pair of queries belonging to a scope, if it gets any
+    fn query_indices(&self, scope: ScopeId) -> Option<(&wgpu::QuerySet, u32, u32)> {
+        let queries = self.queries.as_ref()?;
+        let index = u32::try_from(scope.0).ok().filter(|&index| index < MAX_SCOPES)?;
+        self.scopes[scope.0].timed.set(true);
+        Some((&queries.set, index * 2, index * 2 + 1))
+    }
+
+    /** The timestamp writes for a render pass making up `scope`, or `None` without GPU timing.
+     */
+    pub fn render_timestamp_writes(&self, scope: ScopeId) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let (query_set, beginning, end) = self.query_indices(scope)?;
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set,
+            beginning_of_pass_write_index: Some(beginning),
+            end_of_pass_write_index: Some(end),
+        })
+    }
+
+    /** The timestamp writes for a compute pass making up `scope`, or `None` without GPU timing.
+     */
+    pub fn compute_timestamp_writes(&self, scope: ScopeId) -> Option<wgpu::ComputePassTimestampWrites<'_>> {
+        let (query_set, beginning, end) = self.query_indices(scope)?;
+        Some(wgpu::ComputePassTimestampWrites {
+            query_set,
+            beginning_of_pass_write_index: Some(beginning),
+            end_of_pass_write_index: Some(end),
+        })
+    }
+
+    /** Records copying this frame's timestamps somewhere they can be read from. Call once per
+     * frame, after the last scope's pass and before finishing `encoder`.
+     */
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(queries) = &mut self.queries else {
+            return;
+        };
+        if self.resolved.is_some() || !self.scopes.iter().any(|scope| scope.timed.get()) {
+            return;
+        }
+        // None are free while the GPU is far behind, which only costs this frame's GPU times
+        let Some(buffer) = queries.free_buffers.pop() else {
+            return;
+        };
+        let mut size = 0;
+        for (index, _) in self.scopes.iter().enumerate().filter(|(_, scope)| scope.timed.get()) {
+            let offset = index as wgpu::BufferAddress * SCOPE_STRIDE;
+            let first = index as u32 * 2;
+            encoder.resolve_query_set(&queries.set, first..first + 2, &queries.resolve_buffer, offset);
+            size = offset + 2 * TIMESTAMP_SIZE;
+        }
+        encoder.copy_buffer_to_buffer(&queries.resolve_buffer, 0, &buffer, 0, size);
+        self.resolved = Some(buffer);
+    }
+
+    /** Finishes the frame's scopes and picks up the timings of earlier frames that the GPU has
+     * finished. Call once per frame, after submitting the encoder passed to `resolve`.
+     */
+    pub fn end_frame(&mut self, device: &wgpu::Device) {
+        let scopes: Vec<FinishedScope> = self
+            .scopes
+            .drain(..)
+            .map(|scope| FinishedScope {
+                start: scope.start,
+                cpu: scope.cpu.unwrap_or_else(|| scope.start.elapsed()),
+                timed: scope.timed.get(),
+                name: scope.name,
+            })
+            .collect();
+
+        match self.resolved.take() {
+            Some(buffer) => {
+                let (sender, mapped) = mpsc::channel();
+                buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = sender.send(result);
+                });
+                self.pending.push_back(PendingFrame { scopes, submitted: Instant::now(), buffer, mapped });
+            }
+            // Nothing to wait for, e.g. without GPU timing
+            None if self.pending.is_empty() => {
+                self.results = scopes
+                    .into_iter()
+                    .map(|scope| ScopeTiming { name: scope.name, start: scope.start, cpu: scope.cpu, gpu: None, gpu_start: None })
+                    .collect();
+                self.results_frame += 1;
+            }
+            // Reported once the frames in flight are, rather than jumping ahead of them
+            None => {}
+        }
+        if self.queries.is_some() {
+            device.poll(wgpu::Maintain::Poll);
+            self.read_finished_frames();
+        }
+    }
+
+    /** Waits for every frame in flight to be read back, e.g. before reporting the last one.
+     */
+    pub fn wait(&mut self, device: &wgpu::Device) {
+        device.poll(wgpu::Maintain::Wait);
+        self.read_finished_frames();
+    }
+
+    fn read_finished_frames(&mut self) {
+        let Some(queries) = &mut self.queries else {
+            return;
+        };
+        while let Some(frame) = self.pending.front() {
+            match frame.mapped.try_recv() {
+                Err(mpsc::TryRecvError::Empty) => break,
+                Ok(Ok(())) => {
+                    let frame = self.pending.pop_front().unwrap();
+                    {
+                        let data = frame.buffer.slice(..).get_mapped_range();
+                        let timestamps: &[u64] = bytemuck::cast_slice(&data);
+                        let pair = |index: usize| {
+                            let first = index * (SCOPE_STRIDE / TIMESTAMP_SIZE) as usize;
+                            (timestamps[first], timestamps[first + 1])
+                        };
+                        let to_duration = |ticks: u64| Duration::from_nanos((ticks as f64 * queries.period) as u64);
+                        let frame_start = (0..frame.scopes.len()).filter(|&index| frame.scopes[index].timed).map(|index| pair(index).0).min();
+                        self.results = frame
+                            .scopes
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, scope)| {
+                                let (begin, end) = Some(pair(index)).filter(|_| scope.timed).unzip();
+                                // Some drivers reorder the writes of very short passes
+                                let gpu = begin.zip(end).and_then(|(begin, end)| end.checked_sub(begin)).map(to_duration);
+                                let gpu_start = begin.zip(frame_start).map(|(begin, frame_start)| frame.submitted + to_duration(begin.saturating_sub(frame_start)));
+                                ScopeTiming { name: scope.name, start: scope.start, cpu: scope.cpu, gpu, gpu_start }
+                            })
+                            .collect();
+                        self.results_frame += 1;
+                    }
+                    frame.buffer.unmap();
+                    queries.free_buffers.push(frame.buffer);
+                }
+                // The frame's timings are lost, but its buffer was never mapped and can be reused;
+                // dropping it would stop GPU timing once every buffer had failed
+                Ok(Err(error)) => {
+                    log::warn!("Error in reading back timestamps: {error}");
+                    let frame = self.pending.pop_front().unwrap();
+                    queries.free_buffers.push(frame.buffer);
+                }
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    let frame = self.pending.pop_front().unwrap();
+                    queries.free_buffers.push(frame.buffer);
+                }
+            }
+        }
+    }
+
+    /** The timings of the latest frame that has been read back, in the order the scopes began.
+     */
+    pub fn results(&self) -> &[ScopeTiming] {
+        &self.results
+    }
+
+    /** Counts the frames that have been reported so far, so that callers can tell when `results`
+     * holds a new frame.
+     */
+    pub fn results_frame(&self) -> u64 {
+        self.results_frame
+    }
+}
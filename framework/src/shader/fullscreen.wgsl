@@ -0,0 +1,26 @@
+// A single triangle covering the whole target, for passes that shade every pixel. Draw it with
+// `draw(0..3, ..)` and no vertex buffers.
+
+struct FullscreenOutput {
+	@builtin(position) position: vec4<f32>,
+	// 0..1 across the target with (0, 0) at the top left, like texture coordinates
+	@location(0) uv: vec2<f32>,
+}
+
+// Texture coordinates of the triangle's corners, which reach 2 so that the 0..1 square is covered
+fn fullscreen_uv(index: u32) -> vec2<f32> {
+	return vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+}
+
+// Normalized device coordinates of a texture coordinate, flipping y to point up
+fn uv_to_ndc(uv: vec2<f32>) -> vec2<f32> {
+	return uv * vec2(2.0, -2.0) + vec2(-1.0, 1.0);
+}
+
+fn fullscreen_vertex(index: u32) -> FullscreenOutput {
+	let uv = fullscreen_uv(index);
+	var output: FullscreenOutput;
+	output.position = vec4(uv_to_ndc(uv), 0.0, 1.0);
+	output.uv = uv;
+	return output;
+}
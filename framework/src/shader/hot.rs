@@ -0,0 +1,300 @@
+use std::{borrow::Cow, path::PathBuf, sync::{atomic::{AtomicBool, Ordering}, Arc}};
+
+use super::{validate_wgsl, Preprocessor};
+
+/** Where a shader's WGSL comes from: always the copy compiled into the binary, and optionally a
+ * file on disk that is preferred while it can be read.
+ */
+#[derive(Clone, Debug)]
+pub struct ShaderSource {
+    pub label: &'static str,
+    pub embedded: &'static str,
+    pub path: Option<PathBuf>,
+    pub preprocessor: Option<Preprocessor>,
+}
+
+impl ShaderSource {
+    pub fn embedded(label: &'static str, source: &'static str) -> Self {
+        ShaderSource { label, embedded: source, path: None, preprocessor: None }
+    }
+
+    /** Loads the shader from `path` instead, and watches it when used for a `HotShader`.
+     */
+    pub fn watch(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /** Runs the shader through `preprocessor` before compiling it. Files it includes from disk
+     * are watched along with the shader itself.
+     */
+    pub fn preprocess(mut self, preprocessor: Preprocessor) -> Self {
+        self.preprocessor = Some(preprocessor);
+        self
+    }
+
+    /** Defines `name` for the preprocessor, adding a default one if there isn't one yet, to pick
+     * a variant of the shader.
+     */
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.preprocessor = Some(self.preprocessor.take().unwrap_or_default().define(name, value));
+        self
+    }
+
+    // Preprocesses and validates the embedded copy, or the file's contents if `from_disk`,
+    // returning the WGSL to create the module from and the files it was read from
+    fn compile(&self, text: Cow<'static, str>, from_disk: bool) -> Result<(Cow<'static, str>, Vec<PathBuf>), String> {
+        let path = self.path.as_deref().filter(|_| from_disk);
+        let name = path.map_or_else(|| self.label.to_string(), |path| path.display().to_string());
+        let Some(preprocessor) = &self.preprocessor else {
+            validate_wgsl(&text, &name)?;
+            return Ok((text, path.into_iter().map(PathBuf::from).collect()));
+        };
+        let processed = preprocessor.process_source(&name, &text, path).map_err(|error| format!("error: {error}"))?;
+        processed.validate()?;
+        Ok((Cow::Owned(processed.source), processed.dependencies))
+    }
+
+    // The embedded copy, which has to compile for there to be a shader at all
+    fn compile_embedded(&self) -> (Cow<'static, str>, Vec<PathBuf>) {
+        match self.compile(Cow::Borrowed(self.embedded), false) {
+            Ok(compiled) => compiled,
+            Err(diagnostic) => panic!("Error in compiling {}:\n{diagnostic}", self.label),
+        }
+    }
+}
+
+/** A `ShaderSource` for a WGSL file given relative to the calling crate's root. The file is
+ * embedded, and in debug builds also loaded from disk and watched.
+ *
+ * ```ignore
+ * let shader = HotShader::new(device, framework::shader_source!("src/shader.wgsl"));
+ * ```
+ */
+#[macro_export]
+macro_rules! shader_source {
+    ($path:literal) => {{
+        let source = $crate::shader::ShaderSource::embedded($path, include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)));
+        if cfg!(debug_assertions) {
+            source.watch(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path))
+        } else {
+            source
+        }
+    }};
+}
+
+/** A shader module that is recreated when its source file changes.
+ *
+ * Call `reload` once per frame with a function building whatever depends on the module. Nothing
+ * happens until the file, or a file it includes, changes; then the new source is validated and
+ * the pipelines rebuilt, and if either fails the diagnostic is logged and the old module and
+ * pipelines stay in use.
+ */
+pub struct HotShader {
+    source: ShaderSource,
+    text: Cow<'static, str>,
+    module: wgpu::ShaderModule,
+    // Set from the watcher's thread when the file changes
+    changed: Arc<AtomicBool>,
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: Option<FileWatcher>,
+}
+
+impl HotShader {
+    /** Creates the module, panicking if neither the file nor the embedded copy is valid.
+     */
+    pub fn new(device: &wgpu::Device, source: ShaderSource) -> Self {
+        Self::with_notify(device, source, || {})
+    }
+
+    /** Like `new`, also calling `on_change` from the watcher's thread whenever the file changes,
+     * e.g. to request a redraw from an app that doesn't redraw continuously.
+     */
+    pub fn with_notify(device: &wgpu::Device, source: ShaderSource, on_change: impl Fn() + Send + 'static) -> Self {
+        let file = source.path.as_ref().and_then(|path| match std::fs::read_to_string(path) {
+            Ok(text) => Some(text),
+            Err(error) => {
+                log::warn!("Using the embedded copy of {}, reading {} failed: {error}", source.label, path.display());
+                None
+            }
+        });
+        let (text, dependencies) = match file.map(|text| source.compile(Cow::Owned(text), true)) {
+            Some(Ok(compiled)) => compiled,
+            Some(Err(diagnostic)) => {
+                log::error!("Using the embedded copy of {}, the file doesn't compile:\n{diagnostic}", source.label);
+                source.compile_embedded()
+            }
+            None => source.compile_embedded(),
+        };
+
+        let module = Self::create_module(device, source.label, &text);
+        let changed = Arc::new(AtomicBool::new(false));
+        #[cfg(target_arch = "wasm32")]
+        let _ = (on_change, dependencies);
+        #[cfg(not(target_arch = "wasm32"))]
+        let watcher = source.path.as_ref().and_then(|path| {
+            let mut watcher = FileWatcher::new(path, changed.clone(), on_change)?;
+            for dependency in &dependencies {
+                watcher.watch(dependency);
+            }
+            Some(watcher)
+        });
+        HotShader {
+            source,
+            text,
+            module,
+            changed,
+            #[cfg(not(target_arch = "wasm32"))]
+            watcher,
+        }
+    }
+
+    fn create_module(device: &wgpu::Device, label: &str, text: &str) -> wgpu::ShaderModule {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(text)),
+        })
+    }
+
+    pub fn module(&self) -> &wgpu::ShaderModule {
+        &self.module
+    }
+
+    pub fn source(&self) -> &ShaderSource {
+        &self.source
+    }
+
+    /** The WGSL the current module was created from, after preprocessing.
+     */
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /** Whether the file has changed since the last `reload`.
+     */
+    pub fn is_changed(&self) -> bool {
+        self.changed.load(Ordering::Acquire)
+    }
+
+    /** Marks the shader as changed, so the next `reload` reads the file whether or not the watcher
+     * noticed anything.
+     */
+    pub fn mark_changed(&self) {
+        self.changed.store(true, Ordering::Release);
+    }
+
+    /** If the file changed, compiles it and calls `build` with the new module, returning what it
+     * built. Returns `None` if nothing changed or if the shader or `build` failed, which leaves the
+     * current module in place.
+     */
+    pub fn reload<T>(&mut self, device: &wgpu::Device, build: impl FnOnce(&wgpu::ShaderModule) -> T) -> Option<T> {
+        if !self.changed.swap(false, Ordering::AcqRel) {
+            return None;
+        }
+        let path = self.source.path.as_ref()?;
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) => {
+                log::error!("Error in reloading {}: {error}", path.display());
+                return None;
+            }
+        };
+        let (text, dependencies) = match self.source.compile(Cow::Owned(text), true) {
+            Ok(compiled) => compiled,
+            Err(diagnostic) => {
+                log::error!("Keeping the previous {}, the new version doesn't compile:\n{diagnostic}", self.source.label);
+                return None;
+            }
+        };
+        // An edit may have added includes, which need watching whether or not it builds
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(watcher) = &mut self.watcher {
+            for dependency in &dependencies {
+                watcher.watch(dependency);
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = dependencies;
+        // Editors often touch a file several times per save
+        if text == self.text {
+            return None;
+        }
+
+        // naga accepts some things the device doesn't support, and pipelines can still disagree
+        // with their layouts, so wgpu's own validation is caught rather than left to panic
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = Self::create_module(device, self.source.label, &text);
+        let built = build(&module);
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            log::error!("Keeping the previous {}, rebuilding with the new version failed:\n{error}", self.source.label);
+            return None;
+        }
+
+        log::info!("Reloaded {}", path.display());
+        self.module = module;
+        self.text = text;
+        Some(built)
+    }
+}
+
+/** Watches a shader and the files it includes. Directories are watched rather than the files,
+ * since many editors save by replacing the file, and files stay watched once they're no longer
+ * included, which only costs a reload that finds nothing changed.
+ */
+#[cfg(not(target_arch = "wasm32"))]
+struct FileWatcher {
+    watcher: notify::RecommendedWatcher,
+    file_names: Arc<std::sync::Mutex<std::collections::HashSet<std::ffi::OsString>>>,
+    directories: std::collections::HashSet<PathBuf>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileWatcher {
+    fn new(path: &std::path::Path, changed: Arc<AtomicBool>, on_change: impl Fn() + Send + 'static) -> Option<Self> {
+        let file_names = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+        let watched = file_names.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let relevant = matches!(event.kind, notify::EventKind::Create(_) | notify::EventKind::Modify(_));
+            let watched = watched.lock().unwrap();
+            if relevant && event.paths.iter().any(|path| path.file_name().is_some_and(|name| watched.contains(name))) {
+                changed.store(true, Ordering::Release);
+                on_change();
+            }
+        });
+        match watcher {
+            Ok(watcher) => {
+                let mut watcher = FileWatcher { watcher, file_names, directories: std::collections::HashSet::new() };
+                watcher.watch(path).then_some(watcher)
+            }
+            Err(error) => {
+                log::warn!("Hot reloading is off for {}, watching it failed: {error}", path.display());
+                None
+            }
+        }
+    }
+
+    // Returns false if the file's directory couldn't be watched
+    fn watch(&mut self, path: &std::path::Path) -> bool {
+        use notify::Watcher;
+
+        let Some(file_name) = path.file_name() else {
+            return false;
+        };
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => std::path::Path::new("."),
+        };
+        if !self.directories.contains(directory) {
+            if let Err(error) = self.watcher.watch(directory, notify::RecursiveMode::NonRecursive) {
+                log::warn!("Hot reloading is off for {}, watching it failed: {error}", path.display());
+                return false;
+            }
+            self.directories.insert(directory.to_path_buf());
+        }
+        self.file_names.lock().unwrap().insert(file_name.to_owned());
+        true
+    }
+}
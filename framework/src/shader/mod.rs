@@ -0,0 +1,29 @@
+/*
+   Loading WGSL shaders.
+
+   `shader_source!` embeds a shader in the binary like `include_wgsl!`, and in debug builds also
+   remembers where it lives on disk. A `HotShader` made from such a source watches the file and
+   rebuilds pipelines from it whenever it changes, so shaders can be tweaked while an example
+   runs. Sources are checked with naga first, so mistakes are reported with the offending line
+   rather than as a wgpu validation panic.
+
+   WGSL has no includes, so a `Preprocessor` can run first to paste shared files in and pick
+   shader variants with `#define`/`#ifdef`. It remembers where every line came from, so that
+   diagnostics still point at the file that was written rather than the combined source.
+   */
+pub mod hot;
+pub mod preprocess;
+
+pub use hot::*;
+pub use preprocess::*;
+
+/** Parses and validates WGSL, returning naga's diagnostic, formatted with the offending source
+ * lines, if it is invalid. `path` is only used in the diagnostic.
+ */
+pub fn validate_wgsl(source: &str, path: &str) -> Result<naga::Module, String> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|error| error.emit_to_string_with_path(source, path))?;
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+        .validate(&module)
+        .map_err(|error| error.emit_to_string_with_path(source, path))?;
+    Ok(module)
+}
@@ -0,0 +1,388 @@
+use std::{collections::{BTreeMap, HashMap, HashSet}, fmt::{self, Write}, path::{Path, PathBuf}};
+
+/** Files every `Preprocessor` can include, by the name they're included with.
+ */
+const FRAMEWORK_FILES: &[(&str, &str)] = &[
+    ("framework/fullscreen.wgsl", include_str!("fullscreen.wgsl")),
+];
+
+/** Expands `#include`, `#define` and `#ifdef` directives in WGSL, which has no way of sharing code
+ * between files or compiling variants of a shader.
+ *
+ * Directives take up a whole line:
+ *
+ * - `#include "file.wgsl"` pastes a file in, the first time it is included. The name is looked up
+ *   relative to the including file on disk, then among the files added with `add_file`, then in
+ *   each search path.
+ * - `#define NAME value` replaces every later occurrence of the word `NAME` with `value`, which
+ *   may be empty. `#undef NAME` forgets it again.
+ * - `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif` keep or drop the lines between them.
+ *
+ * The framework's own helpers are always available, e.g. `#include "framework/fullscreen.wgsl"`
+ * for a fullscreen triangle.
+ *
+ * ```ignore
+ * let shader = Preprocessor::new()
+ *     .add_file("common.wgsl", include_str!("common.wgsl"))
+ *     .define("SHADOWS", "")
+ *     .process_source("shader.wgsl", include_str!("shader.wgsl"), None)?;
+ * ```
+ */
+#[derive(Clone, Debug)]
+pub struct Preprocessor {
+    files: HashMap<String, &'static str>,
+    search_paths: Vec<PathBuf>,
+    defines: BTreeMap<String, String>,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Self {
+            files: FRAMEWORK_FILES.iter().map(|&(name, source)| (name.to_string(), source)).collect(),
+            search_paths: Vec::new(),
+            defines: BTreeMap::new(),
+        }
+    }
+
+    /** Makes `source` includable as `name` without touching the disk, typically with
+     * `include_str!` so that it is embedded in the binary.
+     */
+    pub fn add_file(mut self, name: impl Into<String>, source: &'static str) -> Self {
+        self.files.insert(name.into(), source);
+        self
+    }
+
+    pub fn add_search_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /** Defines `name` before processing starts, as if the shader began with `#define`.
+     */
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    /** Processes the file `name`, looked up among the added files and then the search paths.
+     */
+    pub fn process(&self, name: &str) -> Result<ProcessedShader, PreprocessError> {
+        let mut state = State::new(self);
+        let file = self.resolve(name, None).ok_or_else(|| PreprocessError::NotFound {
+            include: name.to_string(),
+            location: None,
+        })?;
+        state.process_file(file)?;
+        Ok(state.output)
+    }
+
+    /** Processes `source`, which is called `name` in diagnostics. If it was read from `path`,
+     * relative includes are looked up next to it.
+     */
+    pub fn process_source(&self, name: &str, source: &str, path: Option<&Path>) -> Result<ProcessedShader, PreprocessError> {
+        let mut state = State::new(self);
+        let file = match path {
+            Some(path) => File::disk(name.to_string(), path, source.to_string()),
+            None => File { key: FileKey::Virtual(name.to_string()), name: name.to_string(), source: source.to_string(), path: None },
+        };
+        state.process_file(file)?;
+        Ok(state.output)
+    }
+
+    fn resolve(&self, include: &str, directory: Option<&Path>) -> Option<File> {
+        let read = |path: PathBuf| {
+            let source = std::fs::read_to_string(&path).ok()?;
+            Some(File::disk(path.display().to_string(), &path, source))
+        };
+        if let Some(file) = directory.and_then(|directory| read(directory.join(include))) {
+            return Some(file);
+        }
+        if let Some(source) = self.files.get(include) {
+            return Some(File { key: FileKey::Virtual(include.to_string()), name: include.to_string(), source: source.to_string(), path: None });
+        }
+        self.search_paths.iter().find_map(|directory| read(directory.join(include)))
+    }
+}
+
+// Disk files are identified by their canonical path so that different spellings match
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum FileKey {
+    Disk(PathBuf),
+    Virtual(String),
+}
+
+struct File {
+    key: FileKey,
+    name: String,
+    source: String,
+    path: Option<PathBuf>,
+}
+
+impl File {
+    fn disk(name: String, path: &Path, source: String) -> Self {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        File { key: FileKey::Disk(canonical.clone()), name, source, path: Some(canonical) }
+    }
+}
+
+// An `#ifdef` or `#ifndef` block being processed
+struct Conditional {
+    // Whether the lines around the block are kept
+    outer: bool,
+    condition: bool,
+    seen_else: bool,
+    line: u32,
+}
+
+struct State<'a> {
+    preprocessor: &'a Preprocessor,
+    defines: BTreeMap<String, String>,
+    // Files being processed, outermost first
+    stack: Vec<(FileKey, String)>,
+    included: HashSet<FileKey>,
+    output: ProcessedShader,
+}
+
+impl<'a> State<'a> {
+    fn new(preprocessor: &'a Preprocessor) -> Self {
+        State {
+            preprocessor,
+            defines: preprocessor.defines.clone(),
+            stack: Vec::new(),
+            included: HashSet::new(),
+            output: ProcessedShader { source: String::new(), dependencies: Vec::new(), files: Vec::new(), lines: Vec::new() },
+        }
+    }
+
+    fn process_file(&mut self, file: File) -> Result<(), PreprocessError> {
+        if let Some(start) = self.stack.iter().position(|(key, _)| *key == file.key) {
+            let mut chain: Vec<String> = self.stack[start..].iter().map(|(_, name)| name.clone()).collect();
+            chain.push(file.name);
+            return Err(PreprocessError::Cycle { chain });
+        }
+        if !self.included.insert(file.key.clone()) {
+            return Ok(());
+        }
+        if let Some(path) = &file.path {
+            self.output.dependencies.push(path.clone());
+        }
+        let file_index = self.output.files.len();
+        self.output.files.push(file.name.clone());
+        self.stack.push((file.key.clone(), file.name.clone()));
+
+        let directory = file.path.as_deref().and_then(Path::parent);
+        let mut conditionals: Vec<Conditional> = Vec::new();
+        for (index, line) in file.source.lines().enumerate() {
+            let line_number = index as u32 + 1;
+            let active = conditionals.last().is_none_or(|block| block.outer && block.condition);
+            let location = || format!("{}:{line_number}", file.name);
+            let error = |message: String| PreprocessError::Directive { message, location: location() };
+
+            let Some(directive) = line.trim_start().strip_prefix('#') else {
+                if active {
+                    let line = substitute(line, &self.defines);
+                    self.output.source.push_str(&line);
+                    self.output.source.push('\n');
+                    self.output.lines.push((file_index, line_number));
+                }
+                continue;
+            };
+            let (name, argument) = directive.split_once(char::is_whitespace).unwrap_or((directive, ""));
+            let argument = argument.trim();
+            match name {
+                "ifdef" | "ifndef" => {
+                    let defined = self.defines.contains_key(identifier(argument).map_err(error)?);
+                    conditionals.push(Conditional { outer: active, condition: defined == (name == "ifdef"), seen_else: false, line: line_number });
+                }
+                "else" => {
+                    let block = conditionals.last_mut().ok_or_else(|| error("#else without #ifdef".to_string()))?;
+                    if block.seen_else {
+                        return Err(error("#else after #else".to_string()));
+                    }
+                    block.condition = !block.condition;
+                    block.seen_else = true;
+                }
+                "endif" => {
+                    conditionals.pop().ok_or_else(|| error("#endif without #ifdef".to_string()))?;
+                }
+                _ if !active => {}
+                "include" => {
+                    let include = argument
+                        .strip_prefix('"')
+                        .and_then(|rest| rest.strip_suffix('"'))
+                        .ok_or_else(|| error(format!("expected a quoted file name after #include, found `{argument}`")))?;
+                    let included = self.preprocessor.resolve(include, directory).ok_or_else(|| PreprocessError::NotFound {
+                        include: include.to_string(),
+                        location: Some(location()),
+                    })?;
+                    self.process_file(included)?;
+                }
+                "define" => {
+                    let (define, value) = argument.split_once(char::is_whitespace).unwrap_or((argument, ""));
+                    let define = identifier(define).map_err(error)?;
+                    self.defines.insert(define.to_string(), value.trim().to_string());
+                }
+                "undef" => {
+                    self.defines.remove(identifier(argument).map_err(error)?);
+                }
+                _ => return Err(error(format!("unknown directive #{name}"))),
+            }
+        }
+        if let Some(block) = conditionals.last() {
+            return Err(PreprocessError::Directive {
+                message: "#ifdef without #endif".to_string(),
+                location: format!("{}:{}", file.name, block.line),
+            });
+        }
+
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+fn is_identifier_start(character: char) -> bool {
+    character.is_alphabetic() || character == '_'
+}
+
+fn is_identifier_continue(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+fn identifier(argument: &str) -> Result<&str, String> {
+    let mut characters = argument.chars();
+    let valid = characters.next().is_some_and(is_identifier_start) && characters.all(is_identifier_continue);
+    if valid {
+        Ok(argument)
+    } else {
+        Err(format!("expected a name, found `{argument}`"))
+    }
+}
+
+// Replaces whole words that are defined, leaving line comments alone
+fn substitute(line: &str, defines: &BTreeMap<String, String>) -> String {
+    if defines.is_empty() {
+        return line.to_string();
+    }
+    let mut output = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find(|character: char| is_identifier_start(character) || character == '/') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("//") {
+            break;
+        }
+        let end = rest.find(|character: char| !is_identifier_continue(character)).unwrap_or(rest.len()).max(1);
+        let word = &rest[..end];
+        output.push_str(defines.get(word).map_or(word, String::as_str));
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/** WGSL produced by a `Preprocessor`, along with where each of its lines came from.
+ */
+#[derive(Clone, Debug)]
+pub struct ProcessedShader {
+    pub source: String,
+    /** Every file read from disk, which a watcher should look at to notice changes.
+     */
+    pub dependencies: Vec<PathBuf>,
+    files: Vec<String>,
+    // The file index and line number each line of `source` came from
+    lines: Vec<(usize, u32)>,
+}
+
+impl ProcessedShader {
+    /** The file name and line number that the given 1-based line of `source` came from.
+     */
+    pub fn location(&self, line: u32) -> Option<(&str, u32)> {
+        let &(file, line) = self.lines.get(line.checked_sub(1)? as usize)?;
+        Some((&self.files[file], line))
+    }
+
+    /** Parses and validates the WGSL like `validate_wgsl`, with diagnostics pointing into the
+     * original files rather than the combined source.
+     */
+    pub fn validate(&self) -> Result<naga::Module, String> {
+        let module = naga::front::wgsl::parse_str(&self.source).map_err(|error| {
+            self.diagnostic(error.message(), error.labels().map(|(span, label)| (span, label.to_string())))
+        })?;
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+            .validate(&module)
+            .map_err(|error| {
+                let mut message = error.as_inner().to_string();
+                let mut source = std::error::Error::source(error.as_inner());
+                while let Some(cause) = source {
+                    write!(message, ": {cause}").unwrap();
+                    source = cause.source();
+                }
+                self.diagnostic(&message, error.spans().cloned())
+            })?;
+        Ok(module)
+    }
+
+    /** Validates the shader and creates a module from it, panicking with the diagnostic if it is
+     * invalid.
+     */
+    pub fn create_shader_module(&self, device: &wgpu::Device, label: &str) -> wgpu::ShaderModule {
+        if let Err(diagnostic) = self.validate() {
+            panic!("Error in compiling {label}:\n{diagnostic}");
+        }
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(self.source.as_str().into()),
+        })
+    }
+
+    fn diagnostic(&self, message: &str, labels: impl Iterator<Item = (naga::Span, String)>) -> String {
+        let mut output = format!("error: {message}\n");
+        for (span, label) in labels.filter(|(span, _)| span.is_defined()) {
+            let location = span.location(&self.source);
+            let (file, line) = self.location(location.line_number).unwrap_or(("<unknown>", location.line_number));
+            let text = self.source.lines().nth(location.line_number as usize - 1).unwrap_or_default();
+            let column = location.line_position as usize - 1;
+            // Keeps tabs so the markers line up with the text above them
+            let indent: String = text
+                .chars()
+                .take(column)
+                .map(|character| if character == '\t' { '\t' } else { ' ' })
+                .collect();
+            let markers = "^".repeat(text.chars().skip(column).take(location.length as usize).count().max(1));
+            writeln!(output, "  --> {file}:{line}:{}", location.line_position).unwrap();
+            writeln!(output, "   | {text}").unwrap();
+            writeln!(output, "   | {indent}{markers} {label}").unwrap();
+        }
+        output
+    }
+}
+
+/** Why a shader couldn't be preprocessed. Locations are given as `file:line`.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum PreprocessError {
+    NotFound { include: String, location: Option<String> },
+    /** Files including each other, starting and ending with the same file.
+     */
+    Cycle { chain: Vec<String> },
+    Directive { message: String, location: String },
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreprocessError::NotFound { include, location: Some(location) } => write!(f, "{location}: can't find \"{include}\""),
+            PreprocessError::NotFound { include, location: None } => write!(f, "can't find \"{include}\""),
+            PreprocessError::Cycle { chain } => write!(f, "include cycle: {}", chain.join(" -> ")),
+            PreprocessError::Directive { message, location } => write!(f, "{location}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {}
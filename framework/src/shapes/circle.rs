@@ -0,0 +1,205 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec2;
+
+use super::InstanceBuffer;
+use crate::BufferBuilder;
+
+/** One circle, in pixels with the origin at the top left of the target. Colors are linear RGBA
+ * with straight (not premultiplied) alpha.
+ *
+ * The stroke is drawn inside the radius, so a stroked circle covers exactly the same pixels as a
+ * filled one of the same radius.
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Circle {
+    pub center: [f32; 2],
+    pub radius: f32,
+    pub stroke_width: f32,
+    pub fill_color: [f32; 4],
+    pub stroke_color: [f32; 4],
+}
+
+impl Circle {
+    pub const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32,
+        2 => Float32,
+        3 => Float32x4,
+        4 => Float32x4,
+    ];
+
+    pub fn filled(center: Vec2, radius: f32, color: [f32; 4]) -> Self {
+        Circle {
+            center: center.into(),
+            radius,
+            stroke_width: 0.0,
+            fill_color: color,
+            stroke_color: color,
+        }
+    }
+
+    /** An outline with a transparent inside.
+     */
+    pub fn stroked(center: Vec2, radius: f32, width: f32, color: [f32; 4]) -> Self {
+        Circle::filled(center, radius, [0.0; 4]).with_stroke(width, color)
+    }
+
+    pub fn with_stroke(mut self, width: f32, color: [f32; 4]) -> Self {
+        self.stroke_width = width;
+        self.stroke_color = color;
+        self
+    }
+
+    /** Per-instance layout for drawing straight from a buffer of `Circle`s.
+     */
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        Self::layout_with_stride(std::mem::size_of::<Circle>() as wgpu::BufferAddress)
+    }
+
+    /** Per-instance layout for a buffer of larger structs that begin with a `Circle`, such as
+     * simulation state kept next to what is drawn.
+     */
+    pub fn layout_with_stride(array_stride: wgpu::BufferAddress) -> wgpu::VertexBufferLayout<'static> {
+        assert!(array_stride >= std::mem::size_of::<Circle>() as wgpu::BufferAddress, "Error in circle layout: the stride is smaller than a Circle");
+        wgpu::VertexBufferLayout {
+            array_stride,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/** Draws anti-aliased circles given in pixel coordinates.
+ *
+ * Circles are collected with `push` during a frame, uploaded with `prepare` and drawn in one
+ * instanced draw call. Call `resize` whenever the render target changes size.
+ */
+pub struct CircleRenderer {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    instance_buffer: InstanceBuffer<Circle>,
+    circles: Vec<Circle>,
+}
+
+impl CircleRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        Self::with_stride(device, format, width, height, std::mem::size_of::<Circle>() as wgpu::BufferAddress)
+    }
+
+    /** A renderer whose pipeline reads instances `stride` bytes apart, for use with `draw_buffer`
+     * on buffers of structs that begin with a `Circle`.
+     */
+    pub fn with_stride(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32, stride: wgpu::BufferAddress) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("circle.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Circle bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                    count: None,
+                },
+            ],
+        });
+
+        let uniform_buffer = BufferBuilder::slice_of(&[width as f32, height as f32])
+            .label("Circle resolution")
+            .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
+            .build(device);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Circle bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let layout = crate::PipelineLayoutBuilder::new()
+            .label("Circle pipeline layout")
+            .add_bind_group_layout(&bind_group_layout)
+            .build(device);
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Circle pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[Circle::layout_with_stride(stride)],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let instance_buffer = InstanceBuffer::new(device, "Circle instances", wgpu::BufferUsages::VERTEX, 64);
+
+        CircleRenderer { pipeline, bind_group, uniform_buffer, instance_buffer, circles: Vec::new() }
+    }
+
+    /** Updates the size of the render target in pixels.
+     */
+    pub fn resize(&self, queue: &wgpu::Queue, width: u32, height: u32) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[width as f32, height as f32]));
+    }
+
+    pub fn push(&mut self, circle: Circle) {
+        self.circles.push(circle);
+    }
+
+    pub fn clear(&mut self) {
+        self.circles.clear();
+    }
+
+    pub fn circles(&self) -> &[Circle] {
+        &self.circles
+    }
+
+    pub fn circles_mut(&mut self) -> &mut Vec<Circle> {
+        &mut self.circles
+    }
+
+    /** Uploads the pushed circles, growing the instance buffer if needed.
+     */
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.instance_buffer.write(device, queue, &self.circles);
+    }
+
+    pub fn draw<'pass>(&'pass self, rpass: &mut wgpu::RenderPass<'pass>) {
+        if self.instance_buffer.is_empty() {
+            return;
+        }
+        self.draw_buffer(rpass, self.instance_buffer.slice(), self.instance_buffer.len() as u32);
+    }
+
+    /** Draws `count` circles from a buffer the caller manages, laid out with the stride the
+     * renderer was created with.
+     */
+    pub fn draw_buffer<'pass>(&'pass self, rpass: &mut wgpu::RenderPass<'pass>, instances: wgpu::BufferSlice<'pass>, count: u32) {
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, instances);
+        rpass.draw(0..4, 0..count);
+    }
+}
@@ -0,0 +1,63 @@
+// Circles drawn as instanced quads, shaded by their signed distance so the edges are
+// anti-aliased. Everything is in pixels with the origin at the top left of the target.
+
+struct Circle {
+	@location(0) center: vec2<f32>,
+	@location(1) radius: f32,
+	@location(2) stroke_width: f32,
+	@location(3) fill_color: vec4<f32>,
+	@location(4) stroke_color: vec4<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	// Offset from the center in pixels
+	@location(0) local: vec2<f32>,
+	@location(1) radius: f32,
+	@location(2) stroke_width: f32,
+	@location(3) fill_color: vec4<f32>,
+	@location(4) stroke_color: vec4<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> resolution: vec2<f32>;
+
+// Extra pixels around each quad so the anti-aliased edge isn't clipped
+const EDGE: f32 = 1.0;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, circle: Circle) -> VertexOutput {
+	// Triangle strip corners: (-1, -1), (1, -1), (-1, 1), (1, 1)
+	let corner = vec2(f32(index & 1u), f32(index >> 1u)) * 2.0 - 1.0;
+	let local = corner * (circle.radius + EDGE);
+	let pixel = circle.center + local;
+
+	var output: VertexOutput;
+	output.position = vec4((pixel / resolution * 2.0 - 1.0) * vec2(1.0, -1.0), 0.0, 1.0);
+	output.local = local;
+	output.radius = circle.radius;
+	output.stroke_width = circle.stroke_width;
+	output.fill_color = circle.fill_color;
+	output.stroke_color = circle.stroke_color;
+	return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	let distance = length(input.local);
+	// Coverage of the whole circle and of the part inside the stroke, with a one pixel ramp
+	let outer = clamp(input.radius - distance + 0.5, 0.0, 1.0);
+	let inner = clamp(input.radius - input.stroke_width - distance + 0.5, 0.0, 1.0);
+	if outer <= 0.0 {
+		discard;
+	}
+	// Mixing premultiplied colors keeps a transparent fill from darkening the stroke's inner edge
+	let stroke = vec4(input.stroke_color.rgb * input.stroke_color.a, input.stroke_color.a);
+	let fill = vec4(input.fill_color.rgb * input.fill_color.a, input.fill_color.a);
+	let color = mix(stroke, fill, inner);
+	if color.a <= 0.0 {
+		discard;
+	}
+	return vec4(color.rgb / color.a, color.a * outer);
+}
@@ -0,0 +1,418 @@
+use std::collections::BTreeMap;
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Affine2, Mat4, Vec2};
+
+use super::InstanceBuffer;
+use crate::{mesh::Mesh, BufferBuilder};
+
+// Width of the transparent fringe added around every shape for anti-aliasing, in pixels
+const FEATHER: f32 = 1.0;
+// Largest distance allowed between a curve and the polygon approximating it, in pixels
+const CURVE_TOLERANCE: f32 = 0.25;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ShapeVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl ShapeVertex {
+    pub const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ShapeVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/** How the ends of a line are finished.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    // Ends exactly at the end points
+    Butt,
+    // Extends past the end points by half the width
+    Square,
+    // Extends past the end points with a half circle
+    Round,
+}
+
+/** Collects 2D shapes for one frame.
+ *
+ * Shapes are given in pixels with the origin at the top left and y pointing down, and are
+ * tessellated into triangles as they are added, after applying the current transform. Colors are
+ * linear RGBA with straight alpha. Shapes on higher layers are drawn on top; within a layer,
+ * later shapes are drawn on top of earlier ones. Everything ends up in one draw call.
+ *
+ * ```ignore
+ * draw.rect(vec2(10.0, 10.0), vec2(200.0, 100.0), [0.1, 0.1, 0.1, 0.8]);
+ * draw.set_layer(1);
+ * draw.line(vec2(20.0, 90.0), vec2(200.0, 20.0), 2.0, LineCap::Round, [1.0, 0.5, 0.0, 1.0]);
+ * shapes.prepare(&device, &queue, &mut draw);
+ * ```
+ */
+#[derive(Clone, Debug)]
+pub struct Draw {
+    layers: BTreeMap<i32, Mesh<ShapeVertex>>,
+    layer: i32,
+    transform: Affine2,
+}
+
+impl Default for Draw {
+    fn default() -> Self {
+        Draw { layers: BTreeMap::new(), layer: 0, transform: Affine2::IDENTITY }
+    }
+}
+
+impl Draw {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /** Sets the layer the following shapes are drawn on.
+     */
+    pub fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    pub fn layer(&self) -> i32 {
+        self.layer
+    }
+
+    /** Sets the transform applied to the following shapes, e.g. to map plot coordinates onto
+     * the screen.
+     */
+    pub fn set_transform(&mut self, transform: Affine2) {
+        self.transform = transform;
+    }
+
+    pub fn transform(&self) -> Affine2 {
+        self.transform
+    }
+
+    pub fn reset_transform(&mut self) {
+        self.transform = Affine2::IDENTITY;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /** Removes every shape, keeping the current layer and transform.
+     */
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+
+    pub fn rect(&mut self, min: Vec2, size: Vec2, color: [f32; 4]) {
+        let max = min + size;
+        self.polygon(&[min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)], color);
+    }
+
+    pub fn rounded_rect(&mut self, min: Vec2, size: Vec2, radius: f32, color: [f32; 4]) {
+        let radius = radius.min(size.x / 2.0).min(size.y / 2.0);
+        if radius <= 0.0 {
+            return self.rect(min, size, color);
+        }
+        let max = min + size;
+        let segments = self.arc_segments(radius, std::f32::consts::FRAC_PI_2);
+        let corners = [
+            (Vec2::new(max.x - radius, min.y + radius), -std::f32::consts::FRAC_PI_2),
+            (Vec2::new(max.x - radius, max.y - radius), 0.0),
+            (Vec2::new(min.x + radius, max.y - radius), std::f32::consts::FRAC_PI_2),
+            (Vec2::new(min.x + radius, min.y + radius), std::f32::consts::PI),
+        ];
+        let mut points = Vec::with_capacity(4 * (segments + 1));
+        for (center, start) in corners {
+            arc(&mut points, center, radius, start, std::f32::consts::FRAC_PI_2, segments);
+        }
+        self.polygon(&points, color);
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: [f32; 4]) {
+        let segments = self.arc_segments(radius, std::f32::consts::TAU);
+        let mut points = Vec::with_capacity(segments + 1);
+        arc(&mut points, center, radius, 0.0, std::f32::consts::TAU, segments);
+        points.pop();
+        self.polygon(&points, color);
+    }
+
+    pub fn line(&mut self, from: Vec2, to: Vec2, width: f32, cap: LineCap, color: [f32; 4]) {
+        let Some(direction) = (to - from).try_normalize() else {
+            // A zero length line is only visible through its caps
+            match cap {
+                LineCap::Butt => {}
+                LineCap::Square => self.rect(from - width / 2.0, Vec2::splat(width), color),
+                LineCap::Round => self.circle(from, width / 2.0, color),
+            }
+            return;
+        };
+        let half = width / 2.0;
+        let normal = direction.perp() * half;
+
+        let points = match cap {
+            LineCap::Butt => vec![from + normal, to + normal, to - normal, from - normal],
+            LineCap::Square => {
+                let (from, to) = (from - direction * half, to + direction * half);
+                vec![from + normal, to + normal, to - normal, from - normal]
+            }
+            LineCap::Round => {
+                let segments = self.arc_segments(half, std::f32::consts::PI);
+                let angle = normal.y.atan2(normal.x);
+                let mut points = Vec::with_capacity(2 * (segments + 1));
+                arc(&mut points, to, half, angle, -std::f32::consts::PI, segments);
+                arc(&mut points, from, half, angle + std::f32::consts::PI, -std::f32::consts::PI, segments);
+                points
+            }
+        };
+        self.polygon(&points, color);
+    }
+
+    /** Fills a simple polygon, convex or not. The points may wind either way, but the outline
+     * must not cross itself.
+     */
+    pub fn polygon(&mut self, points: &[Vec2], color: [f32; 4]) {
+        let mut transformed: Vec<Vec2> = Vec::with_capacity(points.len());
+        for &point in points {
+            let point = self.transform.transform_point2(point);
+            match transformed.last() {
+                Some(last) if last.distance_squared(point) <= 1e-6 => {}
+                _ => transformed.push(point),
+            }
+        }
+        while transformed.len() > 1 && transformed[0].distance_squared(*transformed.last().unwrap()) <= 1e-6 {
+            transformed.pop();
+        }
+        if transformed.len() < 3 {
+            return;
+        }
+        let mesh = self.layers.entry(self.layer).or_insert_with(|| Mesh::new(Vec::new(), Vec::new()));
+        fill_polygon(mesh, &transformed, color);
+    }
+
+    // Enough segments for an arc of `radius` and `angle` on screen to stay within the tolerance
+    fn arc_segments(&self, radius: f32, angle: f32) -> usize {
+        let scale = self.transform.matrix2.x_axis.length().max(self.transform.matrix2.y_axis.length());
+        let radius = radius * scale;
+        if radius <= CURVE_TOLERANCE {
+            return 2;
+        }
+        let step = 2.0 * (1.0 - CURVE_TOLERANCE / radius).acos();
+        ((angle.abs() / step).ceil() as usize).clamp(2, 256)
+    }
+
+    /** Every layer's triangles in drawing order, as they will be uploaded.
+     */
+    pub fn mesh(&self) -> Mesh<ShapeVertex> {
+        let mut combined = Mesh::new(Vec::new(), Vec::new());
+        for mesh in self.layers.values() {
+            combined.append(mesh.clone());
+        }
+        combined
+    }
+}
+
+// Appends `segments + 1` points along an arc, starting at `start` and turning by `sweep` radians
+fn arc(points: &mut Vec<Vec2>, center: Vec2, radius: f32, start: f32, sweep: f32, segments: usize) {
+    for i in 0..=segments {
+        let angle = start + sweep * i as f32 / segments as f32;
+        points.push(center + radius * Vec2::new(angle.cos(), angle.sin()));
+    }
+}
+
+// Fills a polygon and surrounds it with a fringe fading to transparent, half inside and half
+// outside the outline so that the shape keeps its size
+fn fill_polygon(mesh: &mut Mesh<ShapeVertex>, points: &[Vec2], color: [f32; 4]) {
+    let count = points.len();
+    let area: f32 = (0..count).map(|i| points[i].perp_dot(points[(i + 1) % count])).sum();
+    let orientation = if area < 0.0 { 1.0 } else { -1.0 };
+
+    let edge_normals: Vec<Vec2> = (0..count)
+        .map(|i| (points[(i + 1) % count] - points[i]).normalize_or_zero().perp() * orientation)
+        .collect();
+
+    let transparent = [color[0], color[1], color[2], 0.0];
+    let base = mesh.vertices.len() as u32;
+    for i in 0..count {
+        let normal = (edge_normals[(i + count - 1) % count] + edge_normals[i]) / 2.0;
+        // Scaling by the inverse squared length keeps the fringe the same width at sharp corners
+        let miter = normal / normal.length_squared().max(0.05);
+        let offset = miter * FEATHER / 2.0;
+        mesh.vertices.push(ShapeVertex { position: (points[i] - offset).into(), color });
+        mesh.vertices.push(ShapeVertex { position: (points[i] + offset).into(), color: transparent });
+    }
+
+    // The inner vertices are every other one
+    for [a, b, c] in triangulate(points, area) {
+        mesh.indices.extend([base + 2 * a, base + 2 * b, base + 2 * c]);
+    }
+    for i in 0..count as u32 {
+        let next = (i + 1) % count as u32;
+        let (inner, outer) = (base + 2 * i, base + 2 * i + 1);
+        let (next_inner, next_outer) = (base + 2 * next, base + 2 * next + 1);
+        mesh.indices.extend([inner, outer, next_outer, inner, next_outer, next_inner]);
+    }
+}
+
+// Splits a simple polygon with signed doubled `area` into triangles of point indices, as a fan
+// when it's convex and by ear clipping otherwise
+fn triangulate(points: &[Vec2], area: f32) -> Vec<[u32; 3]> {
+    let count = points.len();
+    // Positive for corners turning the same way as the polygon
+    let turn = |a: Vec2, b: Vec2, c: Vec2| (b - a).perp_dot(c - b) * area.signum();
+    let convex = (0..count).all(|i| turn(points[i], points[(i + 1) % count], points[(i + 2) % count]) >= 0.0);
+    if convex {
+        return (1..count as u32 - 1).map(|i| [0, i, i + 1]).collect();
+    }
+
+    let mut remaining: Vec<u32> = (0..count as u32).collect();
+    let mut triangles = Vec::with_capacity(count - 2);
+    let mut i = 0;
+    // Counts corners tried since the last ear, to notice when there are none left
+    let mut attempts = 0;
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let (previous, current, next) = (remaining[(i + len - 1) % len], remaining[i % len], remaining[(i + 1) % len]);
+        let (a, b, c) = (points[previous as usize], points[current as usize], points[next as usize]);
+        let is_ear = turn(a, b, c) > 0.0
+            && remaining
+                .iter()
+                .filter(|&&other| other != previous && other != current && other != next)
+                .all(|&other| !in_triangle(points[other as usize], a, b, c, area));
+        // An outline that crosses itself can run out of ears, so clip anyway rather than loop
+        if is_ear || attempts >= len {
+            triangles.push([previous, current, next]);
+            remaining.remove(i % len);
+            attempts = 0;
+        } else {
+            i += 1;
+            attempts += 1;
+        }
+        i %= remaining.len();
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+// Whether `point` is inside or on the edge of triangle `a`, `b`, `c`, wound like a polygon with
+// signed doubled `area`
+fn in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2, area: f32) -> bool {
+    let side = |from: Vec2, to: Vec2| (to - from).perp_dot(point - from) * area.signum() >= 0.0;
+    side(a, b) && side(b, c) && side(c, a)
+}
+
+/** Draws the shapes collected in a `Draw` with a pixel-space orthographic projection.
+ */
+pub struct ShapeRenderer {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    vertex_buffer: InstanceBuffer<ShapeVertex>,
+    index_buffer: InstanceBuffer<u32>,
+}
+
+impl ShapeRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("shape.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shape bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                    count: None,
+                },
+            ],
+        });
+
+        let uniform_buffer = BufferBuilder::bytes_of(&Self::projection(width, height))
+            .label("Shape projection")
+            .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
+            .build(device);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shape bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let layout = crate::PipelineLayoutBuilder::new()
+            .label("Shape pipeline layout")
+            .add_bind_group_layout(&bind_group_layout)
+            .build(device);
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shape pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[ShapeVertex::layout()],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        ShapeRenderer {
+            pipeline,
+            bind_group,
+            uniform_buffer,
+            vertex_buffer: InstanceBuffer::new(device, "Shape vertices", wgpu::BufferUsages::VERTEX, 1024),
+            index_buffer: InstanceBuffer::new(device, "Shape indices", wgpu::BufferUsages::INDEX, 2048),
+        }
+    }
+
+    // Maps pixels, origin at the top left and y down, onto clip space
+    fn projection(width: u32, height: u32) -> [[f32; 4]; 4] {
+        Mat4::orthographic_rh(0.0, width.max(1) as f32, height.max(1) as f32, 0.0, -1.0, 1.0).to_cols_array_2d()
+    }
+
+    /** Updates the projection to a render target of the given size in pixels.
+     */
+    pub fn resize(&self, queue: &wgpu::Queue, width: u32, height: u32) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&Self::projection(width, height)));
+    }
+
+    /** Uploads everything collected in `draw` and clears it for the next frame.
+     */
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, draw: &mut Draw) {
+        let mesh = draw.mesh();
+        self.vertex_buffer.write(device, queue, &mesh.vertices);
+        self.index_buffer.write(device, queue, &mesh.indices);
+        draw.clear();
+    }
+
+    pub fn draw<'pass>(&'pass self, rpass: &mut wgpu::RenderPass<'pass>) {
+        if self.index_buffer.is_empty() {
+            return;
+        }
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.vertex_buffer.slice());
+        rpass.set_index_buffer(self.index_buffer.slice(), wgpu::IndexFormat::Uint32);
+        rpass.draw_indexed(0..self.index_buffer.len() as u32, 0, 0..1);
+    }
+}
@@ -0,0 +1,75 @@
+use std::marker::PhantomData;
+
+/** A GPU buffer of `T`s that is rewritten every frame and reallocated, at twice the size, when
+ * more items are written than it can hold.
+ */
+pub struct InstanceBuffer<T> {
+    buffer: wgpu::Buffer,
+    label: &'static str,
+    usage: wgpu::BufferUsages,
+    capacity: usize,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> InstanceBuffer<T> {
+    /** `usage` is combined with `COPY_DST`, which writing needs.
+     */
+    pub fn new(device: &wgpu::Device, label: &'static str, usage: wgpu::BufferUsages, capacity: usize) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        let capacity = capacity.max(1);
+        InstanceBuffer {
+            buffer: Self::create_buffer(device, label, usage, capacity),
+            label,
+            usage,
+            capacity,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, label: &str, usage: wgpu::BufferUsages, capacity: usize) -> wgpu::Buffer {
+        crate::BufferBuilder::sized((std::mem::size_of::<T>() * capacity) as wgpu::BufferAddress)
+            .label(label)
+            .usage(usage)
+            .build(device)
+    }
+
+    /** Replaces the contents with `items`. Returns `true` if the buffer was reallocated, in which
+     * case bind groups using it must be recreated.
+     */
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, items: &[T]) -> bool {
+        let reallocated = items.len() > self.capacity;
+        if reallocated {
+            self.capacity = items.len().next_power_of_two();
+            self.buffer = Self::create_buffer(device, self.label, self.usage, self.capacity);
+        }
+        if !items.is_empty() {
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(items));
+        }
+        self.len = items.len();
+        reallocated
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /** The part of the buffer holding the last written items.
+     */
+    pub fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.buffer.slice(..(std::mem::size_of::<T>() * self.len.max(1)) as wgpu::BufferAddress)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
@@ -0,0 +1,10 @@
+/*
+   2D shapes drawn in pixel coordinates.
+   */
+pub mod circle;
+pub mod draw;
+pub mod instances;
+
+pub use circle::*;
+pub use draw::*;
+pub use instances::*;
@@ -0,0 +1,24 @@
+// Flat colored triangles in pixel coordinates, as tessellated by `Draw`. Anti-aliasing comes
+// from the transparent fringe around every shape rather than from the shader.
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) color: vec4<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> projection: mat4x4<f32>;
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+	var output: VertexOutput;
+	output.position = projection * vec4(position, 0.0, 1.0);
+	output.color = color;
+	return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	return input.color;
+}
@@ -0,0 +1,143 @@
+/*
+   Renders a cubemap as the background of a scene.
+   */
+use encase::ShaderType;
+use glam::{Mat4, Vec3};
+
+use crate::{camera::Camera, shader::Preprocessor, texture::Texture, BufferBuilder};
+
+#[derive(ShaderType)]
+pub struct SkyboxUniform {
+    pub inverse_view_projection: Mat4,
+    pub position: Vec3,
+}
+
+impl SkyboxUniform {
+    pub fn from_camera(camera: &Camera) -> Self {
+        SkyboxUniform {
+            inverse_view_projection: camera.view_projection_matrix().inverse(),
+            position: camera.eye,
+        }
+    }
+
+    pub fn as_wgsl_bytes(&self) -> encase::internal::Result<Vec<u8>> {
+        let mut buffer = encase::UniformBuffer::new(vec![]);
+        buffer.write(self)?;
+        Ok(buffer.into_inner())
+    }
+}
+
+/** Draws a cubemap wherever the scene left the depth buffer at the far plane.
+ *
+ * The skybox is drawn as one fullscreen triangle after the rest of the scene, in the same render
+ * pass and against the same depth attachment (cleared to 1.0), so only background pixels are
+ * shaded. It doesn't write depth.
+ */
+pub struct Skybox {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+}
+
+impl Skybox {
+    /** `cubemap` must have a `Cube` view, see `framework::cubemap`. Pass the depth format of
+     * the pass the skybox is drawn in, or `None` if it has no depth attachment.
+     */
+    pub fn new(
+        device: &wgpu::Device,
+        cubemap: &Texture,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        assert_eq!(cubemap.view_dimension, wgpu::TextureViewDimension::Cube, "Error in creating skybox: the texture isn't viewed as a cubemap");
+        let module = Preprocessor::new()
+            .process_source("skybox.wgsl", include_str!("skybox.wgsl"), None)
+            .expect("Error in preprocessing skybox.wgsl")
+            .create_shader_module(device, "skybox.wgsl");
+
+        let [texture_entry, sampler_entry] = cubemap.layout_entries(1, wgpu::ShaderStages::FRAGMENT);
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Skybox bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: Some(SkyboxUniform::min_size()),
+                    },
+                    count: None,
+                },
+                texture_entry,
+                sampler_entry,
+            ],
+        });
+
+        let initial = SkyboxUniform { inverse_view_projection: Mat4::IDENTITY, position: Vec3::ZERO };
+        let uniform_buffer = BufferBuilder::slice_of(&initial.as_wgsl_bytes().expect("Error in translating SkyboxUniform to wgsl bytes."))
+            .label("Skybox uniform")
+            .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
+            .build(device);
+
+        let [texture_binding, sampler_binding] = cubemap.bind_group_entries(1);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Skybox bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                texture_binding,
+                sampler_binding,
+            ],
+        });
+
+        let layout = crate::PipelineLayoutBuilder::new()
+            .label("Skybox pipeline layout")
+            .add_bind_group_layout(&bind_group_layout)
+            .build(device);
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Skybox pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                // The triangle lies exactly on the far plane, which an untouched pixel also holds
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(color_format.into())],
+            }),
+            multiview: None,
+        });
+
+        Skybox { pipeline, bind_group, uniform_buffer }
+    }
+
+    /** Uploads the camera's orientation; call once per frame before drawing.
+     */
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera) {
+        let bytes = SkyboxUniform::from_camera(camera).as_wgsl_bytes().expect("Error in translating SkyboxUniform to wgsl bytes.");
+        queue.write_buffer(&self.uniform_buffer, 0, &bytes);
+    }
+
+    pub fn draw<'pass>(&'pass self, rpass: &mut wgpu::RenderPass<'pass>) {
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
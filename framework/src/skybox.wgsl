@@ -0,0 +1,36 @@
+// Draws a cubemap behind everything else. The triangle sits on the far plane, so with a
+// `LessEqual` depth test it only covers pixels the scene left untouched.
+
+#include "framework/fullscreen.wgsl"
+
+struct SkyboxUniform {
+	inverse_view_projection: mat4x4<f32>,
+	position: vec3<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> skybox: SkyboxUniform;
+
+@group(0)
+@binding(1)
+var cubemap: texture_cube<f32>;
+
+@group(0)
+@binding(2)
+var cubemap_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+	var output = fullscreen_vertex(index);
+	output.position.z = 1.0;
+	return output;
+}
+
+@fragment
+fn fs_main(vertex: FullscreenOutput) -> @location(0) vec4<f32> {
+	// Unproject the point on the far plane and look from the camera towards it
+	let far = skybox.inverse_view_projection * vec4(uv_to_ndc(vertex.uv), 1.0, 1.0);
+	let direction = far.xyz / far.w - skybox.position;
+	return textureSample(cubemap, cubemap_sampler, direction);
+}
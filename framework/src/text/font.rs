@@ -0,0 +1,79 @@
+// DejaVu Sans, see fonts/DejaVuSans-LICENSE.txt
+const FALLBACK_FONT: &[u8] = include_bytes!("../../fonts/DejaVuSans.ttf");
+
+/** Vertical metrics of a font at one size, in pixels with y up from the baseline.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineMetrics {
+    pub ascent: f32,
+    // Negative for fonts that extend below the baseline, as nearly all do
+    pub descent: f32,
+    pub line_gap: f32,
+}
+
+impl LineMetrics {
+    /** Distance between the baselines of consecutive lines.
+     */
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+/** A parsed font. Sizes are given in pixels per em.
+ */
+pub struct Font {
+    font: fontdue::Font,
+}
+
+impl Font {
+    /** Parses a TrueType or OpenType font, returning fontdue's description of the problem if it
+     * can't be read.
+     */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())?;
+        Ok(Font { font })
+    }
+
+    /** The font bundled with the framework, for when nothing else is available.
+     */
+    pub fn fallback() -> Self {
+        Font::from_bytes(FALLBACK_FONT).expect("Error in loading the fallback font.")
+    }
+
+    pub fn line_metrics(&self, size: f32) -> LineMetrics {
+        match self.font.horizontal_line_metrics(size) {
+            Some(metrics) => LineMetrics { ascent: metrics.ascent, descent: metrics.descent, line_gap: metrics.line_gap },
+            // Fonts without horizontal metrics are rare, fall back to the em box
+            None => LineMetrics { ascent: size, descent: 0.0, line_gap: 0.0 },
+        }
+    }
+
+    /** Index of the glyph drawn for `character`, 0 (the "missing" glyph) if the font has none.
+     */
+    pub fn glyph_index(&self, character: char) -> u16 {
+        self.font.lookup_glyph_index(character)
+    }
+
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.font.has_glyph(character)
+    }
+
+    /** How far the pen moves after drawing `character`.
+     */
+    pub fn advance(&self, character: char, size: f32) -> f32 {
+        self.font.metrics(character, size).advance_width
+    }
+
+    /** Adjustment to the advance between `left` and `right`, usually negative, 0.0 if the pair
+     * isn't kerned.
+     */
+    pub fn kern(&self, left: char, right: char, size: f32) -> f32 {
+        self.font.horizontal_kern(left, right, size).unwrap_or(0.0)
+    }
+
+    /** Rasterizes a glyph into an 8-bit coverage bitmap.
+     */
+    pub fn rasterize(&self, glyph_index: u16, size: f32) -> (fontdue::Metrics, Vec<u8>) {
+        self.font.rasterize_indexed(glyph_index, size)
+    }
+}
@@ -0,0 +1,190 @@
+use std::ops::Range;
+
+use glam::Vec2;
+
+use super::Font;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    // Fraction of the spare width placed before a line
+    fn factor(self) -> f32 {
+        match self {
+            Align::Left => 0.0,
+            Align::Center => 0.5,
+            Align::Right => 1.0,
+        }
+    }
+}
+
+/** How a string is laid out. Sizes are in pixels.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    pub size: f32,
+    // Lines are wrapped at whitespace to fit, or mid-word when a single word doesn't
+    pub max_width: Option<f32>,
+    pub align: Align,
+    // Multiplier on the font's line height
+    pub line_spacing: f32,
+}
+
+impl TextStyle {
+    pub fn new(size: f32) -> Self {
+        TextStyle { size, max_width: None, align: Align::Left, line_spacing: 1.0 }
+    }
+
+    pub fn max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+}
+
+/** One character placed on a line. Whitespace is kept, so every character of the input except
+ * line breaks has a glyph.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionedGlyph {
+    pub character: char,
+    pub glyph_index: u16,
+    // Pen position the glyph is drawn from: `x` at its left and `baseline` under it
+    pub x: f32,
+    pub baseline: f32,
+    pub advance: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineLayout {
+    // Indices into `TextLayout::glyphs`
+    pub glyphs: Range<usize>,
+    pub baseline: f32,
+    // Not counting trailing whitespace
+    pub width: f32,
+}
+
+/** Glyph positions for a string, relative to the top left corner of the text with y down.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<PositionedGlyph>,
+    pub lines: Vec<LineLayout>,
+    // The size the glyphs need to be rasterized at
+    pub font_size: f32,
+    // Width of the widest line and height of all lines
+    pub size: Vec2,
+}
+
+impl TextLayout {
+    pub fn new(font: &Font, text: &str, style: &TextStyle) -> Self {
+        let metrics = font.line_metrics(style.size);
+        let line_height = metrics.line_height() * style.line_spacing;
+
+        let mut lines: Vec<Vec<PositionedGlyph>> = Vec::new();
+        for paragraph in text.split('\n') {
+            Self::wrap_paragraph(font, paragraph.trim_end_matches('\r'), style, &mut lines);
+        }
+
+        let widths: Vec<f32> = lines.iter().map(|line| Self::line_width(line)).collect();
+        let widest = widths.iter().copied().fold(0.0, f32::max);
+        // Alignment is within the wrapping width when there is one, otherwise the widest line
+        let area = style.max_width.unwrap_or(widest);
+
+        let mut layout = TextLayout {
+            glyphs: Vec::new(),
+            lines: Vec::with_capacity(lines.len()),
+            font_size: style.size,
+            size: Vec2::new(widest, line_height * lines.len() as f32),
+        };
+        for (index, (line, width)) in lines.into_iter().zip(widths).enumerate() {
+            let baseline = metrics.ascent + line_height * index as f32;
+            let offset = (area - width).max(0.0) * style.align.factor();
+            let start = layout.glyphs.len();
+            layout.glyphs.extend(line.into_iter().map(|glyph| PositionedGlyph { x: glyph.x + offset, baseline, ..glyph }));
+            layout.lines.push(LineLayout { glyphs: start..layout.glyphs.len(), baseline, width });
+        }
+        layout
+    }
+
+    // Places the glyphs of a paragraph on as many lines as `style.max_width` calls for, with
+    // positions relative to the start of each line
+    fn wrap_paragraph(font: &Font, paragraph: &str, style: &TextStyle, lines: &mut Vec<Vec<PositionedGlyph>>) {
+        let mut line: Vec<PositionedGlyph> = Vec::new();
+        let mut pen = 0.0;
+        let mut previous: Option<char> = None;
+        // Number of glyphs on the line up to and including its last whitespace
+        let mut break_at: Option<usize> = None;
+
+        for character in paragraph.chars() {
+            let advance = font.advance(character, style.size);
+            let kern = match previous {
+                Some(previous) => font.kern(previous, character, style.size),
+                None => 0.0,
+            };
+            let mut x = pen + kern;
+
+            let overflows = match style.max_width {
+                Some(max_width) => x + advance > max_width,
+                None => false,
+            };
+            // Whitespace may hang past the edge, it's never what makes a line wrap
+            if overflows && !character.is_whitespace() && !line.is_empty() {
+                // Carry the partial word after the last whitespace over, or nothing if the word
+                // is as wide as the line and has to be split here
+                let mut rest = match break_at {
+                    Some(index) => line.split_off(index),
+                    None => Vec::new(),
+                };
+                lines.push(std::mem::take(&mut line));
+                if let Some(first) = rest.first().copied() {
+                    for glyph in &mut rest {
+                        glyph.x -= first.x;
+                    }
+                }
+                line = rest;
+                break_at = None;
+                x = match line.last() {
+                    Some(last) => last.x + last.advance + kern,
+                    None => 0.0,
+                };
+            }
+
+            line.push(PositionedGlyph { character, glyph_index: font.glyph_index(character), x, baseline: 0.0, advance });
+            pen = x + advance;
+            previous = Some(character);
+            if character.is_whitespace() {
+                break_at = Some(line.len());
+            }
+        }
+        lines.push(line);
+    }
+
+    fn line_width(line: &[PositionedGlyph]) -> f32 {
+        line.iter()
+            .rev()
+            .find(|glyph| !glyph.character.is_whitespace())
+            .map_or(0.0, |glyph| glyph.x + glyph.advance)
+    }
+
+    /** The text of each line, without the whitespace it was wrapped at.
+     */
+    pub fn line_text(&self, line: usize) -> String {
+        let text: String = self.glyphs[self.lines[line].glyphs.clone()].iter().map(|glyph| glyph.character).collect();
+        text.trim_end().to_string()
+    }
+}
@@ -0,0 +1,14 @@
+/*
+   Text drawn from TrueType/OpenType fonts.
+
+   `TextLayout` places the glyphs of a string, with kerning, wrapping and alignment, using only
+   font metrics, so it works without a GPU. `TextRenderer` rasterizes the glyphs a layout needs
+   into a glyph atlas and draws them as instanced quads.
+   */
+pub mod font;
+pub mod layout;
+pub mod renderer;
+
+pub use font::*;
+pub use layout::*;
+pub use renderer::*;
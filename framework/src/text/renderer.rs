@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use bytemuck::{Pod, Zeroable};
+use glam::Vec2;
+
+use super::{Font, TextLayout, TextStyle};
+use crate::{atlas::{AtlasId, TextureAtlas}, shapes::InstanceBuffer, BufferBuilder, SamplerBuilder};
+
+// Starting size of the glyph atlas, it grows as more glyphs are cached
+const ATLAS_SIZE: u32 = 256;
+// Glyphs are cached per quarter pixel of font size, so animated sizes reuse a bounded set
+const SIZE_STEPS: f32 = 4.0;
+// Glyphs not drawn for this many frames are dropped from the atlas
+const EVICT_AFTER_FRAMES: u64 = 120;
+
+/** One glyph quad, in pixels with the origin at the top left of the target.
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct GlyphInstance {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl GlyphInstance {
+    pub const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x2,
+        3 => Float32x2,
+        4 => Float32x4,
+    ];
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GlyphInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+// Glyph index and the size it was rasterized at, in `SIZE_STEPS` per pixel
+type GlyphKey = (u16, u32);
+
+struct CacheEntry {
+    // `None` for glyphs with nothing to draw, such as spaces
+    glyph: Option<CachedGlyph>,
+    last_used: u64,
+}
+
+#[derive(Clone, Copy)]
+struct CachedGlyph {
+    id: AtlasId,
+    // From the pen position to the top left of the bitmap, y down
+    offset: Vec2,
+    size: Vec2,
+}
+
+struct QueuedGlyph {
+    key: GlyphKey,
+    // Top left of the bitmap
+    position: Vec2,
+    color: [f32; 4],
+}
+
+/** Draws text given in pixel coordinates.
+ *
+ * Text is queued with `push_text` or `push_layout` during a frame, uploaded with `prepare` and
+ * drawn in one instanced draw call. Glyphs are rasterized the first time they are used and kept
+ * in an `R8Unorm` atlas until they go unused for `EVICT_AFTER_FRAMES` frames, or sooner if the
+ * atlas is full. Call `resize` whenever the render target changes size.
+ */
+pub struct TextRenderer {
+    font: Font,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    // Recreated whenever the atlas texture is
+    bind_group: Option<wgpu::BindGroup>,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    instance_buffer: InstanceBuffer<GlyphInstance>,
+    atlas: TextureAtlas,
+    glyphs: HashMap<GlyphKey, CacheEntry>,
+    queued: Vec<QueuedGlyph>,
+    // Counts calls to `prepare`, to tell which glyphs are still in use
+    frame: u64,
+}
+
+impl TextRenderer {
+    /** A renderer using the framework's fallback font.
+     */
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        Self::with_font(device, format, width, height, Font::fallback())
+    }
+
+    pub fn with_font(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32, font: Font) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("text.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Text bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let uniform_buffer = BufferBuilder::slice_of(&[width as f32, height as f32])
+            .label("Text resolution")
+            .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
+            .build(device);
+
+        // Glyphs are drawn at whole pixels, so nearest filtering samples the bitmaps exactly
+        let sampler = SamplerBuilder::new()
+            .label("Glyph sampler")
+            .build(device);
+
+        let layout = crate::PipelineLayoutBuilder::new()
+            .label("Text pipeline layout")
+            .add_bind_group_layout(&bind_group_layout)
+            .build(device);
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[GlyphInstance::layout()],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let atlas = TextureAtlas::new(ATLAS_SIZE, wgpu::TextureFormat::R8Unorm)
+            .with_max_size(device.limits().max_texture_dimension_2d);
+
+        TextRenderer {
+            font,
+            pipeline,
+            bind_group_layout,
+            bind_group: None,
+            uniform_buffer,
+            sampler,
+            instance_buffer: InstanceBuffer::new(device, "Glyph instances", wgpu::BufferUsages::VERTEX, 256),
+            atlas,
+            glyphs: HashMap::new(),
+            queued: Vec::new(),
+            frame: 0,
+        }
+    }
+
+    /** Limits the glyph atlas to `max_size` texels square instead of the device's limit.
+     */
+    pub fn with_max_atlas_size(mut self, max_size: u32) -> Self {
+        self.atlas = TextureAtlas::new(ATLAS_SIZE.min(max_size), wgpu::TextureFormat::R8Unorm).with_max_size(max_size);
+        self.glyphs.clear();
+        self.bind_group = None;
+        self
+    }
+
+    pub fn font(&self) -> &Font {
+        &self.font
+    }
+
+    /** Lays out `text` with the renderer's font, for measuring before drawing.
+     */
+    pub fn layout(&self, text: &str, style: &TextStyle) -> TextLayout {
+        TextLayout::new(&self.font, text, style)
+    }
+
+    /** Updates the size of the render target in pixels.
+     */
+    pub fn resize(&self, queue: &wgpu::Queue, width: u32, height: u32) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[width as f32, height as f32]));
+    }
+
+    /** Queues `text` with its top left corner at `position`, returning its layout.
+     */
+    pub fn push_text(&mut self, text: &str, position: Vec2, style: &TextStyle, color: [f32; 4]) -> TextLayout {
+        let layout = self.layout(text, style);
+        self.push_layout(&layout, position, color);
+        layout
+    }
+
+    /** Queues text laid out earlier with this renderer's font. Glyphs that don't fit in the atlas,
+     * even after dropping those not drawn this frame, are skipped; returns how many were.
+     */
+    pub fn push_layout(&mut self, layout: &TextLayout, position: Vec2, color: [f32; 4]) -> usize {
+        let size = (layout.font_size * SIZE_STEPS).round() as u32;
+        let mut skipped = 0;
+        for glyph in &layout.glyphs {
+            let key = (glyph.glyph_index, size);
+            let cached = match self.cache_glyph(key) {
+                Ok(Some(cached)) => cached,
+                Ok(None) => continue,
+                Err(()) => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let pen = position + Vec2::new(glyph.x, glyph.baseline);
+            self.queued.push(QueuedGlyph { key, position: (pen + cached.offset).round(), color });
+        }
+        if skipped > 0 {
+            log::warn!("Skipped {skipped} glyphs, the glyph atlas is full");
+        }
+        skipped
+    }
+
+    // Fails if the glyph doesn't fit in the atlas
+    fn cache_glyph(&mut self, key: GlyphKey) -> Result<Option<CachedGlyph>, ()> {
+        if let Some(entry) = self.glyphs.get_mut(&key) {
+            entry.last_used = self.frame;
+            return Ok(entry.glyph);
+        }
+        let (glyph_index, size) = key;
+        let (metrics, bitmap) = self.font.rasterize(glyph_index, size as f32 / SIZE_STEPS);
+        let glyph = if metrics.width == 0 || metrics.height == 0 {
+            None
+        } else {
+            let (width, height) = (metrics.width as u32, metrics.height as u32);
+            let id = match self.atlas.insert(width, height, &bitmap) {
+                Some(id) => id,
+                None => {
+                    self.evict(self.frame);
+                    self.atlas.insert(width, height, &bitmap).ok_or(())?
+                }
+            };
+            Some(CachedGlyph {
+                id,
+                offset: Vec2::new(metrics.xmin as f32, -(metrics.ymin as f32 + metrics.height as f32)),
+                size: Vec2::new(metrics.width as f32, metrics.height as f32),
+            })
+        };
+        self.glyphs.insert(key, CacheEntry { glyph, last_used: self.frame });
+        Ok(glyph)
+    }
+
+    // Drops the glyphs last used before frame `used_since`
+    fn evict(&mut self, used_since: u64) {
+        let atlas = &mut self.atlas;
+        self.glyphs.retain(|_, entry| {
+            let keep = entry.last_used >= used_since;
+            if let (false, Some(glyph)) = (keep, entry.glyph) {
+                atlas.remove(glyph.id);
+            }
+            keep
+        });
+    }
+
+    /** The number of glyphs currently cached, including empty ones such as spaces.
+     */
+    pub fn cached_glyphs(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /** Uploads new glyphs and everything queued since the last call, then clears the queue and
+     * drops glyphs that have gone unused for too long.
+     */
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if self.atlas.upload(device, queue) || self.bind_group.is_none() {
+            self.bind_group = self.atlas.view().map(|view| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Text bind group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry { binding: 0, resource: self.uniform_buffer.as_entire_binding() },
+                        wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(view) },
+                        wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                    ],
+                })
+            });
+        }
+
+        // UVs are looked up now rather than when queued since the atlas may have grown since
+        let instances: Vec<GlyphInstance> = self.queued
+            .drain(..)
+            .filter_map(|queued| {
+                let cached = self.glyphs.get(&queued.key)?.glyph?;
+                let uv = self.atlas.uv_rect(cached.id)?;
+                Some(GlyphInstance {
+                    min: queued.position.into(),
+                    max: (queued.position + cached.size).into(),
+                    uv_min: uv.min.into(),
+                    uv_max: uv.max.into(),
+                    color: queued.color,
+                })
+            })
+            .collect();
+        self.instance_buffer.write(device, queue, &instances);
+
+        self.frame += 1;
+        if let Some(used_since) = self.frame.checked_sub(EVICT_AFTER_FRAMES) {
+            self.evict(used_since);
+        }
+    }
+
+    pub fn draw<'pass>(&'pass self, rpass: &mut wgpu::RenderPass<'pass>) {
+        let Some(bind_group) = &self.bind_group else {
+            return;
+        };
+        if self.instance_buffer.is_empty() {
+            return;
+        }
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.instance_buffer.slice());
+        rpass.draw(0..4, 0..self.instance_buffer.len() as u32);
+    }
+}
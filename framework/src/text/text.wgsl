@@ -0,0 +1,47 @@
+// Glyphs drawn as instanced quads textured from a coverage atlas. Everything is in pixels with
+// the origin at the top left of the target.
+
+struct Glyph {
+	@location(0) min: vec2<f32>,
+	@location(1) max: vec2<f32>,
+	@location(2) uv_min: vec2<f32>,
+	@location(3) uv_max: vec2<f32>,
+	@location(4) color: vec4<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) uv: vec2<f32>,
+	@location(1) color: vec4<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> resolution: vec2<f32>;
+
+@group(0)
+@binding(1)
+var atlas: texture_2d<f32>;
+
+@group(0)
+@binding(2)
+var atlas_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, glyph: Glyph) -> VertexOutput {
+	// Triangle strip corners: (0, 0), (1, 0), (0, 1), (1, 1)
+	let corner = vec2(f32(index & 1u), f32(index >> 1u));
+	let pixel = mix(glyph.min, glyph.max, corner);
+
+	var output: VertexOutput;
+	output.position = vec4((pixel / resolution * 2.0 - 1.0) * vec2(1.0, -1.0), 0.0, 1.0);
+	output.uv = mix(glyph.uv_min, glyph.uv_max, corner);
+	output.color = glyph.color;
+	return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	let coverage = textureSample(atlas, atlas_sampler, input.uv).r;
+	return vec4(input.color.rgb, input.color.a * coverage);
+}
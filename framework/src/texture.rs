@@ -0,0 +1,144 @@
+/*
+   Textures bundled with the view and sampler needed to bind them, and loading them from image
+   files.
+   */
+use crate::{SamplerBuilder, TextureBuilder};
+
+/** How the texels of an image are to be interpreted.
+ *
+ * Colors meant to be looked at (albedo, emissive, UI) are almost always stored in sRGB, while
+ * data such as normal maps, roughness or heights is linear. Sampling through an sRGB format
+ * converts to linear on the fly, so shaders always see linear values.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    Srgb,
+    Linear,
+}
+
+impl ColorSpace {
+    pub fn rgba8_format(self) -> wgpu::TextureFormat {
+        match self {
+            ColorSpace::Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            ColorSpace::Linear => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    // How `view` sees the texture, e.g. `Cube` for a 6 layer texture viewed as a cubemap
+    pub view_dimension: wgpu::TextureViewDimension,
+    pub sampler: wgpu::Sampler,
+}
+
+impl Texture {
+    /** Decodes a PNG or JPEG image from memory and uploads it as an RGBA8 texture with a
+     * repeating, linearly filtered sampler.
+     */
+    pub fn from_image_bytes(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        color_space: ColorSpace,
+        label: Option<&str>,
+    ) -> image::ImageResult<Self> {
+        let image = image::load_from_memory(bytes)?;
+        Ok(Self::from_image(device, queue, &image, color_space, label))
+    }
+
+    pub fn from_image(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &image::DynamicImage,
+        color_space: ColorSpace,
+        label: Option<&str>,
+    ) -> Self {
+        let image = image.to_rgba8();
+        Self::from_rgba8(device, queue, image.width(), image.height(), &image, color_space, label)
+    }
+
+    /** Uploads tightly packed RGBA8 pixels, such as a texture generated on the CPU.
+     */
+    pub fn from_rgba8(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        color_space: ColorSpace,
+        label: Option<&str>,
+    ) -> Self {
+        let mut builder = TextureBuilder::new(width, height).format(color_space.rgba8_format());
+        if let Some(label) = label {
+            builder = builder.label(label);
+        }
+        let texture = builder.build_with_data(device, queue, pixels);
+        let sampler = SamplerBuilder::linear().build(device);
+        Self::from_texture(texture, sampler)
+    }
+
+    /** Bundles an existing texture with a sampler, viewing the whole texture.
+     */
+    pub fn from_texture(texture: wgpu::Texture, sampler: wgpu::Sampler) -> Self {
+        let view_dimension = match texture.dimension() {
+            wgpu::TextureDimension::D1 => wgpu::TextureViewDimension::D1,
+            wgpu::TextureDimension::D2 if texture.depth_or_array_layers() > 1 => wgpu::TextureViewDimension::D2Array,
+            wgpu::TextureDimension::D2 => wgpu::TextureViewDimension::D2,
+            wgpu::TextureDimension::D3 => wgpu::TextureViewDimension::D3,
+        };
+        Self::from_texture_with_dimension(texture, view_dimension, sampler)
+    }
+
+    pub fn from_texture_with_dimension(texture: wgpu::Texture, view_dimension: wgpu::TextureViewDimension, sampler: wgpu::Sampler) -> Self {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(view_dimension),
+            ..Default::default()
+        });
+        Texture { texture, view, view_dimension, sampler }
+    }
+
+    /** Bind group layout entries for the view at `binding` and the sampler at `binding + 1`,
+     * matching `bind_group_entries`.
+     */
+    pub fn layout_entries(&self, binding: u32, visibility: wgpu::ShaderStages) -> [wgpu::BindGroupLayoutEntry; 2] {
+        let sample_type = self.texture.format().sample_type(None, None).expect("Error in binding texture: the format can't be sampled");
+        let filterable = matches!(sample_type, wgpu::TextureSampleType::Float { filterable: true });
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility,
+                ty: wgpu::BindingType::Texture {
+                    sample_type,
+                    view_dimension: self.view_dimension,
+                    multisampled: self.texture.sample_count() > 1,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: binding + 1,
+                visibility,
+                ty: wgpu::BindingType::Sampler(if filterable {
+                    wgpu::SamplerBindingType::Filtering
+                } else {
+                    wgpu::SamplerBindingType::NonFiltering
+                }),
+                count: None,
+            },
+        ]
+    }
+
+    pub fn bind_group_entries(&self, binding: u32) -> [wgpu::BindGroupEntry<'_>; 2] {
+        [
+            wgpu::BindGroupEntry {
+                binding,
+                resource: wgpu::BindingResource::TextureView(&self.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: binding + 1,
+                resource: wgpu::BindingResource::Sampler(&self.sampler),
+            },
+        ]
+    }
+}
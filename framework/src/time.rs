@@ -0,0 +1,358 @@
+/*
+   Frame timing.
+
+   A `Clock` is ticked once per frame. It measures how long the frame took, keeps a
+   `FrameHistory` of recent frame times for statistics, and advances an animation time that can
+   be paused or slowed down while debugging. Simulations that need the same step every time run
+   a `FixedTimestep` off the clock's delta, and `FpsTitle` shows the frame rate in the window's
+   title.
+   */
+use std::{collections::VecDeque, time::{Duration, Instant}};
+
+use winit::window::Window;
+
+/** Frames kept by a clock's history, a few seconds' worth at common refresh rates.
+ */
+pub const DEFAULT_HISTORY: usize = 240;
+
+/** Measures frames and keeps the time animations see.
+ *
+ * ```ignore
+ * clock.tick();
+ * let angle = clock.elapsed() * speed;
+ * ```
+ */
+pub struct Clock {
+    last_tick: Instant,
+    real_delta: Duration,
+    delta: Duration,
+    elapsed: Duration,
+    frame: u64,
+    scale: f64,
+    paused: bool,
+    max_delta: Duration,
+    history: FrameHistory,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock {
+    /** Starts the clock; the first tick measures from here.
+     */
+    pub fn new() -> Self {
+        Self {
+            last_tick: Instant::now(),
+            real_delta: Duration::ZERO,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            frame: 0,
+            scale: 1.0,
+            paused: false,
+            max_delta: Duration::from_millis(250),
+            history: FrameHistory::new(DEFAULT_HISTORY),
+        }
+    }
+
+    /** Caps the animation time a single frame can add, so that a stall, e.g. from dragging the
+     * window or stopping in a debugger, doesn't make everything jump. Defaults to 250 ms.
+     */
+    pub fn with_max_delta(mut self, max_delta: Duration) -> Self {
+        self.max_delta = max_delta;
+        self
+    }
+
+    /** Ends a frame, measuring it against the previous tick. Call once per frame.
+     */
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        let real_delta = now - self.last_tick;
+        self.last_tick = now;
+        self.advance(real_delta);
+    }
+
+    /** Ends a frame that took `real_delta`, for driving the clock without measuring, e.g. in
+     * tests or when rendering frames offline.
+     */
+    pub fn advance(&mut self, real_delta: Duration) {
+        self.real_delta = real_delta;
+        self.history.push(real_delta);
+        self.frame += 1;
+        self.delta = if self.paused {
+            Duration::ZERO
+        } else {
+            real_delta.min(self.max_delta).mul_f64(self.scale)
+        };
+        self.elapsed += self.delta;
+    }
+
+    /** Seconds of animation time the last frame added: scaled, capped, and zero while paused.
+     */
+    pub fn delta(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn delta_duration(&self) -> Duration {
+        self.delta
+    }
+
+    /** How long the last frame really took, whether or not the clock is paused.
+     */
+    pub fn real_delta(&self) -> Duration {
+        self.real_delta
+    }
+
+    /** Seconds of animation time since the clock started.
+     */
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    pub fn elapsed_duration(&self) -> Duration {
+        self.elapsed
+    }
+
+    /** The number of ticks so far.
+     */
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn history(&self) -> &FrameHistory {
+        &self.history
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /** Stops animation time, while frames are still measured.
+     */
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /** Runs animation time `scale` times as fast as real time, e.g. 0.25 for slow motion.
+     */
+    pub fn set_scale(&mut self, scale: f64) {
+        assert!(scale >= 0.0, "Error in setting the clock's scale: {scale} is negative");
+        self.scale = scale;
+    }
+}
+
+/** Splits variable frame times into steps of the same length, for simulations that behave
+ * differently depending on their time step.
+ *
+ * Each frame, `update` adds the frame's time and returns how many steps to run; what is left
+ * over carries on into the next frame.
+ */
+#[derive(Clone, Debug)]
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "Error in creating a fixed timestep: the step is zero");
+        Self { step, accumulator: Duration::ZERO, max_steps: u32::MAX }
+    }
+
+    /** Steps of `1 / rate` seconds.
+     */
+    pub fn per_second(rate: f64) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / rate))
+    }
+
+    /** Limits the steps run in one frame. If a frame falls further behind than that, the extra
+     * time is dropped rather than making the next frame slower still.
+     */
+    pub fn max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /** Adds a frame's worth of time, returning the number of steps to run for it.
+     */
+    pub fn update(&mut self, delta: Duration) -> u32 {
+        self.accumulator += delta;
+        let steps = (self.accumulator.as_nanos() / self.step.as_nanos()).min(self.max_steps as u128) as u32;
+        self.accumulator -= self.step * steps;
+        // Only possible when the steps ran out, in which case the backlog is dropped
+        if self.accumulator >= self.step {
+            self.accumulator = Duration::ZERO;
+        }
+        steps
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    pub fn step_seconds(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    /** How far the leftover time is into the next step, from 0 to 1, for interpolating between
+     * the last two simulated states when drawing.
+     */
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator.as_secs_f64() / self.step.as_secs_f64()).min(1.0) as f32
+    }
+}
+
+/** The most recent frame times, oldest first.
+ */
+#[derive(Clone, Debug)]
+pub struct FrameHistory {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl FrameHistory {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Error in creating a frame history: the capacity is zero");
+        Self { samples: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /** Records a frame, forgetting the oldest one once the history is full.
+     */
+    pub fn push(&mut self, frame_time: Duration) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(frame_time);
+    }
+
+    pub fn samples(&self) -> impl ExactSizeIterator<Item = Duration> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn latest(&self) -> Option<Duration> {
+        self.samples.back().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /** The frame time that `percent` percent of the frames took at most, by nearest rank.
+     */
+    pub fn percentile(&self, percent: f64) -> Option<Duration> {
+        let mut sorted: Vec<Duration> = self.samples().collect();
+        sorted.sort_unstable();
+        percentile(&sorted, percent)
+    }
+
+    pub fn stats(&self) -> Option<FrameStats> {
+        let mut sorted: Vec<Duration> = self.samples().collect();
+        sorted.sort_unstable();
+        Some(FrameStats {
+            min: *sorted.first()?,
+            max: *sorted.last()?,
+            average: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            median: percentile(&sorted, 50.0)?,
+            p95: percentile(&sorted, 95.0)?,
+            p99: percentile(&sorted, 99.0)?,
+        })
+    }
+}
+
+fn percentile(sorted: &[Duration], percent: f64) -> Option<Duration> {
+    let rank = (percent.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.max(1) - 1).copied()
+}
+
+/** A summary of a `FrameHistory`. Shown as the frame rate, the average frame time and the 99th
+ * percentile, which catches stutters that the average hides.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameStats {
+    pub min: Duration,
+    pub average: Duration,
+    pub max: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+impl FrameStats {
+    /** Frames per second at the average frame time.
+     */
+    pub fn fps(&self) -> f64 {
+        if self.average.is_zero() {
+            0.0
+        } else {
+            1.0 / self.average.as_secs_f64()
+        }
+    }
+}
+
+impl std::fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        write!(f, "{:.0} fps, {:.1} ms (99%: {:.1} ms)", self.fps(), milliseconds(self.average), milliseconds(self.p99))
+    }
+}
+
+/** Appends the frame rate to a window's title. The title is only rewritten a couple of times a
+ * second so that the numbers can be read.
+ */
+pub struct FpsTitle {
+    title: String,
+    last_update: Option<Instant>,
+    interval: Duration,
+}
+
+impl FpsTitle {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self { title: title.into(), last_update: None, interval: Duration::from_millis(500) }
+    }
+
+    /** Changes the part of the title before the frame rate, showing it on the next update.
+     */
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+        self.last_update = None;
+    }
+
+    pub fn update(&mut self, window: &Window, history: &FrameHistory) {
+        let now = Instant::now();
+        if self.last_update.is_some_and(|last| now - last < self.interval) {
+            return;
+        }
+        self.last_update = Some(now);
+        match history.stats() {
+            Some(stats) => window.set_title(&format!("{} - {stats}", self.title)),
+            None => window.set_title(&self.title),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use encase::ShaderType;
+use glam::{Mat3, Mat4, Quat, Vec3};
+
+/** Translation, rotation and scale of an object, applied in the order scale, rotate, translate.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Transform {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Transform { translation, ..Self::IDENTITY }
+    }
+
+    pub fn from_rotation(rotation: Quat) -> Self {
+        Transform { rotation, ..Self::IDENTITY }
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Transform { scale, ..Self::IDENTITY }
+    }
+
+    pub fn with_translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn model_matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /** The inverse transpose of the model matrix's upper 3x3, which keeps normals perpendicular
+     * to their surface under non-uniform scaling.
+     */
+    pub fn normal_matrix(&self) -> Mat3 {
+        Mat3::from_mat4(self.model_matrix()).inverse().transpose()
+    }
+
+    pub fn uniform(&self) -> TransformUniform {
+        TransformUniform {
+            model: self.model_matrix(),
+            normal: self.normal_matrix(),
+        }
+    }
+
+    pub fn instance(&self) -> TransformInstance {
+        TransformInstance {
+            model: self.model_matrix().to_cols_array_2d(),
+            normal: self.normal_matrix().to_cols_array_2d(),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<Mat4> for Transform {
+    fn from(matrix: Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+        Transform { translation, rotation, scale }
+    }
+}
+
+/** A transform as seen by shaders through a uniform buffer. The matching WGSL struct is
+ *
+ * ```wgsl
+ * struct Transform {
+ *     model: mat4x4<f32>,
+ *     normal: mat3x3<f32>,
+ * }
+ * ```
+ */
+#[derive(ShaderType, Clone, Copy, Debug)]
+pub struct TransformUniform {
+    pub model: Mat4,
+    pub normal: Mat3,
+}
+
+/** A transform as per-instance vertex data, matrices stored column by column.
+ *
+ * The columns occupy shader locations 8 through 14 so they can sit alongside the vertex
+ * attributes of a mesh:
+ *
+ * ```wgsl
+ * struct InstanceInput {
+ *     @location(8) model_0: vec4<f32>,
+ *     @location(9) model_1: vec4<f32>,
+ *     @location(10) model_2: vec4<f32>,
+ *     @location(11) model_3: vec4<f32>,
+ *     @location(12) normal_0: vec3<f32>,
+ *     @location(13) normal_1: vec3<f32>,
+ *     @location(14) normal_2: vec3<f32>,
+ * }
+ * ```
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TransformInstance {
+    pub model: [[f32; 4]; 4],
+    pub normal: [[f32; 3]; 3],
+}
+
+impl TransformInstance {
+    pub const ATTRIBUTES: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![
+        8 => Float32x4,
+        9 => Float32x4,
+        10 => Float32x4,
+        11 => Float32x4,
+        12 => Float32x3,
+        13 => Float32x3,
+        14 => Float32x3,
+    ];
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TransformInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/** A uniform buffer holding one `TransformUniform` per draw, selected with a dynamic offset
+ * when setting the bind group:
+ *
+ * ```ignore
+ * for (index, mesh) in meshes.iter().enumerate() {
+ *     rpass.set_bind_group(1, &transform_bind_group, &[transforms.offset(index)]);
+ *     mesh.draw(&mut rpass, 0..1);
+ * }
+ * ```
+ */
+pub struct TransformBuffer {
+    pub buffer: wgpu::Buffer,
+    alignment: wgpu::BufferAddress,
+    capacity: usize,
+}
+
+impl TransformBuffer {
+    pub fn new(device: &wgpu::Device, capacity: usize) -> Self {
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress;
+        let capacity = capacity.max(1);
+        TransformBuffer {
+            buffer: Self::create_buffer(device, alignment, capacity),
+            alignment,
+            capacity,
+        }
+    }
+
+    fn stride(alignment: wgpu::BufferAddress) -> wgpu::BufferAddress {
+        TransformUniform::min_size().get().next_multiple_of(alignment)
+    }
+
+    fn create_buffer(device: &wgpu::Device, alignment: wgpu::BufferAddress, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Transform buffer"),
+            size: Self::stride(alignment) * capacity as wgpu::BufferAddress,
+            // Copyable so its contents can be read back and inspected
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub fn layout_entry(binding: u32, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: Some(TransformUniform::min_size()),
+            },
+            count: None,
+        }
+    }
+
+    /** Binds a single transform's worth of the buffer; the dynamic offset picks which one.
+     */
+    pub fn binding(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+            buffer: &self.buffer,
+            offset: 0,
+            size: Some(TransformUniform::min_size()),
+        })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn offset(&self, index: usize) -> wgpu::DynamicOffset {
+        (Self::stride(self.alignment) * index as wgpu::BufferAddress) as wgpu::DynamicOffset
+    }
+
+    /** Uploads `transforms`, reallocating the buffer if they no longer fit. Returns `true` when
+     * the buffer was reallocated, in which case bind groups using it must be recreated.
+     */
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, transforms: &[Transform]) -> bool {
+        let reallocated = transforms.len() > self.capacity;
+        if reallocated {
+            self.capacity = transforms.len().next_power_of_two();
+            self.buffer = Self::create_buffer(device, self.alignment, self.capacity);
+        }
+
+        let mut contents = encase::DynamicUniformBuffer::new_with_alignment(Vec::new(), self.alignment);
+        for transform in transforms {
+            contents.write(&transform.uniform()).expect("Error in translating Transform to wgsl bytes.");
+        }
+        queue.write_buffer(&self.buffer, 0, &contents.into_inner());
+        reallocated
+    }
+}
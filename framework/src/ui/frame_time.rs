@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use crate::time::FrameHistory;
+
+// Frame times drawn as reference lines: 60 and 30 fps
+const GUIDES: [Duration; 2] = [Duration::from_micros(16_667), Duration::from_micros(33_333)];
+const GRAPH_HEIGHT: f32 = 60.0;
+
+/** Shows the frame statistics and a bar per frame in `history`, newest on the right. The scale
+ * grows with the slowest frame, but always fits the 30 fps line so that a smooth run looks flat.
+ */
+pub fn frame_time_graph(ui: &mut egui::Ui, history: &FrameHistory) {
+    match history.stats() {
+        Some(stats) => ui.label(format!("{stats}\nmin {:.1} ms, max {:.1} ms", stats.min.as_secs_f64() * 1000.0, stats.max.as_secs_f64() * 1000.0)),
+        None => ui.label("No frames yet"),
+    };
+
+    let width = ui.available_width();
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, GRAPH_HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    let scale = history.samples().max().unwrap_or_default().max(GUIDES[1]).as_secs_f32() * 1.1;
+    let height = |duration: Duration| rect.height() * (duration.as_secs_f32() / scale).min(1.0);
+    for guide in GUIDES {
+        let y = rect.bottom() - height(guide);
+        painter.hline(rect.x_range(), y, egui::Stroke::new(1.0, visuals.weak_text_color()));
+    }
+
+    let bar_width = rect.width() / history.capacity() as f32;
+    // Right aligned, so the graph scrolls left as frames come in
+    let left = rect.right() - bar_width * history.len() as f32;
+    for (index, frame_time) in history.samples().enumerate() {
+        let x = left + bar_width * index as f32;
+        // With some slack, since vsynced frames jitter around the refresh interval
+        let color = if frame_time.mul_f32(0.9) > GUIDES[1] {
+            egui::Color32::from_rgb(220, 80, 60)
+        } else if frame_time.mul_f32(0.9) > GUIDES[0] {
+            egui::Color32::from_rgb(230, 180, 60)
+        } else {
+            egui::Color32::from_rgb(90, 190, 90)
+        };
+        let bar = egui::Rect::from_min_max(egui::pos2(x, rect.bottom() - height(frame_time)), egui::pos2(x + bar_width, rect.bottom()));
+        painter.rect_filled(bar, 0.0, color);
+    }
+}
@@ -0,0 +1,93 @@
+use glam::{Vec2, Vec3, Vec4};
+
+/** Values that can be edited in a debug UI.
+ */
+pub trait Inspect {
+    /** Shows an editor for the value next to `label`, returning true if it was changed.
+     */
+    fn inspect(&mut self, ui: &mut egui::Ui, label: &str) -> bool;
+}
+
+macro_rules! impl_inspect_numeric {
+    ($($type:ty),*) => {
+        $(
+            impl Inspect for $type {
+                fn inspect(&mut self, ui: &mut egui::Ui, label: &str) -> bool {
+                    ui.horizontal(|ui| {
+                        let changed = ui.add(egui::DragValue::new(self)).changed();
+                        ui.label(label);
+                        changed
+                    }).inner
+                }
+            }
+        )*
+    };
+}
+
+impl_inspect_numeric!(f32, f64, i32, u32, i64, u64, usize);
+
+impl Inspect for bool {
+    fn inspect(&mut self, ui: &mut egui::Ui, label: &str) -> bool {
+        ui.checkbox(self, label).changed()
+    }
+}
+
+macro_rules! impl_inspect_vector {
+    ($($type:ty),*) => {
+        $(
+            impl Inspect for $type {
+                fn inspect(&mut self, ui: &mut egui::Ui, label: &str) -> bool {
+                    ui.horizontal(|ui| {
+                        let mut changed = false;
+                        for component in self.as_mut() {
+                            changed |= ui.add(egui::DragValue::new(component).speed(0.01)).changed();
+                        }
+                        ui.label(label);
+                        changed
+                    }).inner
+                }
+            }
+        )*
+    };
+}
+
+impl_inspect_vector!(Vec2, Vec3, Vec4);
+
+/** Implements `Inspect` for a struct by listing the fields to show, in order.
+ *
+ * Fields given a range get a slider, `#[log]` makes it logarithmic. Other fields use their own
+ * `Inspect` implementation. Structs are shown under a collapsible header named by the label.
+ *
+ * ```ignore
+ * framework::impl_inspect!(AppState {
+ *     #[log] zoom: 1.0..=1.0e6,
+ *     max_iterations: 1..=1000,
+ *     julia_c,
+ * });
+ * ```
+ */
+#[macro_export]
+macro_rules! impl_inspect {
+    ($type:ty { $($(#[$mode:ident])? $field:ident $(: $range:expr)?),* $(,)? }) => {
+        impl $crate::ui::Inspect for $type {
+            fn inspect(&mut self, ui: &mut $crate::ui::egui::Ui, label: &str) -> bool {
+                ui.collapsing(label, |ui| {
+                    let mut changed = false;
+                    $(
+                        changed |= $crate::impl_inspect!(@field ui, self.$field, stringify!($field), [$($mode)?] $($range)?);
+                    )*
+                    changed
+                }).body_returned.unwrap_or(false)
+            }
+        }
+    };
+    (@field $ui:ident, $value:expr, $label:expr, []) => {
+        $crate::ui::Inspect::inspect(&mut $value, $ui, $label)
+    };
+    (@field $ui:ident, $value:expr, $label:expr, [] $range:expr) => {
+        $ui.add($crate::ui::egui::Slider::new(&mut $value, $range).text($label)).changed()
+    };
+    (@field $ui:ident, $value:expr, $label:expr, [log] $range:expr) => {
+        $ui.add($crate::ui::egui::Slider::new(&mut $value, $range).logarithmic(true).text($label)).changed()
+    };
+}
@@ -0,0 +1,18 @@
+/*
+   An immediate-mode debug UI, built with egui and drawn over the rest of the frame. Only
+   available with the `egui` feature.
+
+   `UiOverlay` feeds winit events to egui and renders whatever the UI closure builds in a pass of
+   its own. `Inspect` turns values into editors, and `impl_inspect!` derives it for structs such
+   as uniforms. `frame_time_graph` plots a clock's recent frame times.
+   */
+pub mod frame_time;
+pub mod inspect;
+pub mod overlay;
+
+pub use frame_time::*;
+pub use inspect::*;
+pub use overlay::*;
+
+// So that examples can build UIs without depending on egui themselves
+pub use egui;
@@ -0,0 +1,88 @@
+use winit::{event::WindowEvent, window::Window};
+
+use crate::{RenderPassBuilder, WgpuContext};
+
+/** Draws an egui UI over a window's frames.
+ *
+ * Pass every window event to `handle_event` before the app acts on it, then call `render` once
+ * per frame after the app's own passes have been recorded.
+ */
+pub struct UiOverlay {
+    state: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+}
+
+impl UiOverlay {
+    /** Panics for headless contexts, since egui takes its input from the window.
+     */
+    pub fn new(context: &WgpuContext) -> Self {
+        let window = window(context);
+        let state = egui_winit::State::new(
+            egui::Context::default(),
+            egui::ViewportId::ROOT,
+            &**window,
+            Some(window.scale_factor() as f32),
+            Some(context.device.limits().max_texture_dimension_2d as usize));
+        let renderer = egui_wgpu::Renderer::new(&context.device, context.swapchain_format(), None, 1);
+        UiOverlay { state, renderer }
+    }
+
+    pub fn context(&self) -> &egui::Context {
+        self.state.egui_ctx()
+    }
+
+    /** Hands an event to egui. Returns true if egui used it, e.g. a click on one of its windows,
+     * in which case the app should ignore it.
+     */
+    pub fn handle_event(&mut self, window: &Window, event: &WindowEvent) -> bool {
+        let response = self.state.on_window_event(window, event);
+        if response.repaint {
+            window.request_redraw();
+        }
+        response.consumed
+    }
+
+    /** Runs `build_ui` and records a pass drawing the result over `view`. Returns true if egui
+     * wants another frame straight away, e.g. while animating.
+     */
+    pub fn render(&mut self, context: &WgpuContext, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView, build_ui: impl FnMut(&egui::Context)) -> bool {
+        let input = self.state.take_egui_input(window(context));
+        let output = self.state.egui_ctx().run(input, build_ui);
+        self.state.handle_platform_output(window(context), output.platform_output);
+
+        let primitives = self.state.egui_ctx().tessellate(output.shapes, output.pixels_per_point);
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [context.surface_config.width, context.surface_config.height],
+            pixels_per_point: output.pixels_per_point,
+        };
+
+        for (id, delta) in &output.textures_delta.set {
+            self.renderer.update_texture(&context.device, &context.queue, *id, delta);
+        }
+        // Only paint callbacks produce command buffers, and they have to run before the pass
+        let callback_buffers = self.renderer.update_buffers(&context.device, &context.queue, encoder, &primitives, &screen);
+        if !callback_buffers.is_empty() {
+            context.queue.submit(callback_buffers);
+        }
+
+        {
+            let mut rpass = RenderPassBuilder::new()
+                .load(view)
+                .build(encoder);
+            self.renderer.render(&mut rpass, &primitives, &screen);
+        }
+
+        for id in &output.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+
+        match output.viewport_output.get(&egui::ViewportId::ROOT) {
+            Some(viewport) => viewport.repaint_delay.is_zero(),
+            None => false,
+        }
+    }
+}
+
+fn window(context: &WgpuContext) -> &std::sync::Arc<Window> {
+    context.window.as_ref().expect("Error in using the UI overlay: the context has no window")
+}
@@ -5,9 +5,28 @@ use winit::window::Window;
 
 use crate::RenderPassBuilder;
 
+/** A device and queue on the default adapter with `features` enabled, for tests and tools that
+ * only draw offscreen. Returns `None` when there is no usable adapter or it lacks `features`, so
+ * GPU tests can be skipped.
+ */
+#[cfg(not(target_arch = "wasm32"))]
+pub fn offscreen_device(features: wgpu::Features) -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::default();
+    let adapter = pollster::block_on(instance.request_adapter(&RequestAdapterOptions::default()))?;
+    if !adapter.features().contains(features) {
+        return None;
+    }
+    let descriptor = wgpu::DeviceDescriptor { required_features: features, ..Default::default() };
+    pollster::block_on(adapter.request_device(&descriptor, None)).ok()
+}
+
+/** The device and queue an example draws with, along with the window and surface it presents
+ * to. Headless contexts, made with `headless`, have neither and are drawn into offscreen
+ * textures instead.
+ */
 pub struct WgpuContext {
-    pub window: Arc<Window>,
-    pub surface: wgpu::Surface<'static>,
+    pub window: Option<Arc<Window>>,
+    pub surface: Option<wgpu::Surface<'static>>,
     pub adapter: wgpu::Adapter,
     pub surface_config: wgpu::SurfaceConfiguration,
     pub device: wgpu::Device,
@@ -16,6 +35,11 @@ pub struct WgpuContext {
 
 
 impl WgpuContext {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    /** The format headless contexts draw in, which is what screenshots are saved from.
+     */
+    pub const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
     pub async fn from_window(window: Arc<Window>) -> Self {
         let size = window.inner_size();
         
@@ -32,16 +56,13 @@ impl WgpuContext {
         let surface_config = surface
             .get_default_config(&adapter, size.width.max(1), size.height.max(1))
             .expect("Error in retrieving the surface config fromt he adapter");
-        let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor::default(), None)
-            .await
-            .expect("Error in retrieving the device and queue from the adapter");
+        let (device, queue) = Self::request_device(&adapter).await;
 
         surface.configure(&device, &surface_config);
 
         WgpuContext {
-            window: window,
-            surface: surface,
+            window: Some(window),
+            surface: Some(surface),
             adapter: adapter,
             surface_config: surface_config,
             device: device,
@@ -49,22 +70,93 @@ impl WgpuContext {
         }
     }
 
+    /** A context without a window, for drawing `width` x `height` frames offscreen, e.g. in tests
+     * or on machines without a display. `surface_config` describes the frames to draw.
+     */
+    pub async fn headless(width: u32, height: u32) -> Self {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&RequestAdapterOptions::default())
+            .await
+            .expect("Error in getting the underlying adapter for the WgpuContext");
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: Self::HEADLESS_FORMAT,
+            width: width.max(1),
+            height: height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: Vec::new(),
+        };
+        let (device, queue) = Self::request_device(&adapter).await;
+
+        WgpuContext { window: None, surface: None, adapter, surface_config, device, queue }
+    }
+
+    async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+        adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    // Lets `GpuProfiler` time passes wherever the adapter can
+                    required_features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
+                    ..Default::default()
+                },
+                crate::args::trace_path().as_deref(),
+            )
+            .await
+            .expect("Error in retrieving the device and queue from the adapter")
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         self.surface_config.width = new_size.width;
         self.surface_config.height = new_size.height;
-        self.surface.configure(&self.device, &self.surface_config);
-        self.window.request_redraw();
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.surface_config);
+        }
+        self.request_redraw();
+    }
+
+    /** Asks the window for another frame. Does nothing for headless contexts, whose frames are
+     * drawn by whoever made them.
+     */
+    pub fn request_redraw(&self) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
     }
 
-    /** Uses the surface and adapter 
-     *
+    /** The format of the frames the context draws, the surface's preferred one when there is a
+     * surface.
      */
     pub fn swapchain_format(&self) -> wgpu::TextureFormat {
-       self.surface.get_capabilities(&self.adapter).formats[0]
+        self.surface_config.format
+    }
+
+    /** Creates a depth texture matching the current surface size and returns a view of it. Needs
+     * to be recreated after every `resize`.
+     */
+    pub fn create_depth_view(&self) -> wgpu::TextureView {
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth texture"),
+            size: wgpu::Extent3d {
+                width: self.surface_config.width,
+                height: self.surface_config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
     }
 
     pub fn frame_view(&self, descriptor: &wgpu::TextureViewDescriptor) -> (wgpu::SurfaceTexture, wgpu::TextureView) {
-        let frame = self.surface.get_current_texture().expect("Failed to acquire next swap chain texture.");
+        let surface = self.surface.as_ref().expect("Error in acquiring a frame: the context is headless");
+        let frame = surface.get_current_texture().expect("Failed to acquire next swap chain texture.");
         let view = frame.texture.create_view(descriptor);
         (frame, view)
     }
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use framework::args::{parse_trace_path, strip_framework_options, RunOptions};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn finds_the_trace_directory() {
+    assert_eq!(parse_trace_path(args(&["--trace", "trace"])), Some(PathBuf::from("trace")));
+    assert_eq!(parse_trace_path(args(&["sky.hdr", "--trace=out/trace"])), Some(PathBuf::from("out/trace")));
+    assert_eq!(parse_trace_path(args(&["sky.hdr"])), None);
+    // Neither a missing value nor a longer option is a trace directory
+    assert_eq!(parse_trace_path(args(&["--trace"])), None);
+    assert_eq!(parse_trace_path(args(&["--traces=out"])), None);
+    // Nor is one passed on to the example
+    assert_eq!(parse_trace_path(args(&["--", "--trace", "trace"])), None);
+    assert_eq!(parse_trace_path(args(&["sky.hdr", "--", "--trace=trace"])), None);
+}
+
+#[test]
+fn leaves_the_examples_own_arguments() {
+    assert_eq!(strip_framework_options(args(&["--trace", "trace", "sky.hdr"])), args(&["sky.hdr"]));
+    assert_eq!(strip_framework_options(args(&["gradient.png", "--trace=trace"])), args(&["gradient.png"]));
+    assert_eq!(strip_framework_options(args(&["--frames", "10"])), args(&["--frames", "10"]));
+    assert_eq!(
+        strip_framework_options(args(&["--trace=trace", "--", "--trace", "out", "--trace=out"])),
+        args(&["--", "--trace", "out", "--trace=out"])
+    );
+}
+
+#[test]
+fn parses_run_options() {
+    let options = RunOptions::parse(args(&["--headless", "--frames", "10", "--screenshot=cube.png", "sky.hdr"])).unwrap();
+    assert_eq!(options, RunOptions {
+        headless: true,
+        frames: Some(10),
+        screenshot: Some(PathBuf::from("cube.png")),
+        args: args(&["sky.hdr"]),
+    });
+    assert_eq!(RunOptions::parse(args(&[])).unwrap(), RunOptions::default());
+    // The trace directory is read by the context, and everything after `--` belongs to the example
+    let options = RunOptions::parse(args(&["--trace", "trace", "--", "--headless"])).unwrap();
+    assert!(!options.headless);
+    assert_eq!(options.args, args(&["--headless"]));
+    let options = RunOptions::parse(args(&["--headless", "--", "--trace", "out", "gradient.png"])).unwrap();
+    assert!(options.headless);
+    assert_eq!(options.args, args(&["--trace", "out", "gradient.png"]));
+}
+
+#[test]
+fn rejects_invalid_run_options() {
+    assert!(RunOptions::parse(args(&["--frames"])).is_err());
+    assert!(RunOptions::parse(args(&["--frames", "0"])).is_err());
+    assert!(RunOptions::parse(args(&["--frames=many"])).is_err());
+    // Windows are presented, not saved
+    assert!(RunOptions::parse(args(&["--screenshot", "cube.png"])).is_err());
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn records_an_api_trace() {
+    let instance = wgpu::Instance::default();
+    let Some(adapter) = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let directory = std::env::temp_dir().join("framework-api-trace");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    let (device, _queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), Some(&directory))).unwrap();
+    drop(device);
+    assert!(directory.join("trace.ron").exists());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,202 @@
+use framework::{atlas::{AtlasRect, ShelfPacker, TextureAtlas}, offscreen_device};
+
+fn assert_disjoint(rects: &[AtlasRect], width: u32, height: u32) {
+    for (i, a) in rects.iter().enumerate() {
+        assert!(a.right() <= width && a.bottom() <= height, "{a:?} is outside {width}x{height}");
+        for b in &rects[i + 1..] {
+            assert!(!a.intersects(b), "{a:?} overlaps {b:?}");
+        }
+    }
+}
+
+fn texels(width: u32, height: u32) -> Vec<u8> {
+    vec![255; (width * height * 4) as usize]
+}
+
+#[test]
+fn packs_rows_onto_shelves() {
+    let mut packer = ShelfPacker::new(64, 64);
+
+    assert_eq!(packer.allocate(32, 16), Some(AtlasRect::new(0, 0, 32, 16)));
+    assert_eq!(packer.allocate(16, 10), Some(AtlasRect::new(32, 0, 16, 10)));
+    // Too tall for the first shelf, so a second one starts below it
+    assert_eq!(packer.allocate(16, 20), Some(AtlasRect::new(0, 16, 16, 20)));
+    // Fits the first shelf best
+    assert_eq!(packer.allocate(16, 16), Some(AtlasRect::new(48, 0, 16, 16)));
+}
+
+#[test]
+fn rejects_what_does_not_fit() {
+    let mut packer = ShelfPacker::new(32, 32);
+
+    assert_eq!(packer.allocate(33, 1), None);
+    assert_eq!(packer.allocate(0, 4), None);
+    assert!(packer.allocate(32, 32).is_some());
+    assert_eq!(packer.allocate(1, 1), None);
+}
+
+#[test]
+fn never_overlaps() {
+    let mut packer = ShelfPacker::new(256, 256);
+    let rects: Vec<AtlasRect> = (0..200)
+        .filter_map(|i| packer.allocate(4 + (i * 7) % 23, 4 + (i * 13) % 17))
+        .collect();
+
+    assert!(rects.len() > 50);
+    assert_disjoint(&rects, 256, 256);
+}
+
+#[test]
+fn reuses_freed_space() {
+    let mut packer = ShelfPacker::new(64, 64);
+    let a = packer.allocate(32, 32).unwrap();
+    let b = packer.allocate(32, 32).unwrap();
+    packer.allocate(64, 32).unwrap();
+    assert_eq!(packer.allocate(8, 8), None);
+
+    packer.deallocate(a);
+    packer.deallocate(b);
+    // The two halves merged back into one span
+    assert_eq!(packer.allocate(64, 16), Some(AtlasRect::new(0, 0, 64, 16)));
+}
+
+#[test]
+fn releases_empty_bottom_shelves() {
+    let mut packer = ShelfPacker::new(64, 64);
+    let small = packer.allocate(64, 8).unwrap();
+    packer.deallocate(small);
+
+    assert!(packer.is_empty());
+    assert_eq!(packer.allocate(64, 64), Some(AtlasRect::new(0, 0, 64, 64)));
+}
+
+#[test]
+fn growing_keeps_allocations() {
+    let mut packer = ShelfPacker::new(32, 32);
+    let first = packer.allocate(32, 16).unwrap();
+    packer.grow(64, 64);
+
+    assert_eq!(packer.allocate(32, 16), Some(AtlasRect::new(32, 0, 32, 16)));
+    assert_eq!(first, AtlasRect::new(0, 0, 32, 16));
+}
+
+#[test]
+fn atlas_grows_when_full() {
+    let mut atlas = TextureAtlas::new(32, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let ids: Vec<_> = (0..8).map(|_| atlas.insert(14, 14, &texels(14, 14)).unwrap()).collect();
+
+    // Padded to 16x16, so four fit at first and doubling the width makes room for the rest
+    assert_eq!(atlas.size(), (64, 32));
+    let rects: Vec<_> = ids.iter().map(|&id| atlas.rect(id).unwrap()).collect();
+    assert_disjoint(&rects, 64, 32);
+}
+
+#[test]
+fn atlas_respects_max_size() {
+    let mut atlas = TextureAtlas::new(16, wgpu::TextureFormat::R8Unorm).with_max_size(32);
+
+    assert!(atlas.insert(30, 30, &[0; 900]).is_some());
+    assert!(atlas.insert(30, 30, &[0; 900]).is_none());
+}
+
+#[test]
+fn padding_separates_entries() {
+    let mut atlas = TextureAtlas::new(64, wgpu::TextureFormat::Rgba8UnormSrgb).with_padding(2);
+    let a = atlas.insert(8, 8, &texels(8, 8)).unwrap();
+    let b = atlas.insert(8, 8, &texels(8, 8)).unwrap();
+    let (a, b) = (atlas.rect(a).unwrap(), atlas.rect(b).unwrap());
+
+    assert_eq!((a.x, a.y), (2, 2));
+    assert_eq!(b.x, a.right() + 4);
+}
+
+#[test]
+fn uv_rect_is_normalized() {
+    let mut atlas = TextureAtlas::new(64, wgpu::TextureFormat::Rgba8UnormSrgb).with_padding(0);
+    atlas.insert(64, 16, &texels(64, 16)).unwrap();
+    let id = atlas.insert(16, 32, &texels(16, 32)).unwrap();
+    let uv = atlas.uv_rect(id).unwrap();
+
+    assert_eq!(uv.min, glam::vec2(0.0, 0.25));
+    assert_eq!(uv.max, glam::vec2(0.25, 0.75));
+}
+
+#[test]
+fn repacking_reclaims_fragmented_space() {
+    let mut atlas = TextureAtlas::new(64, wgpu::TextureFormat::R8Unorm).with_padding(0).with_max_size(64);
+    // Four shelves of two entries each
+    let ids: Vec<_> = (0..8).map(|_| atlas.insert(32, 16, &[0; 512]).unwrap()).collect();
+    // Free one entry on every shelf; no shelf alone has room for a full width entry
+    for id in ids.iter().step_by(2) {
+        atlas.remove(*id);
+    }
+
+    let wide = atlas.insert(64, 16, &[0; 1024]).unwrap();
+    assert_eq!(atlas.size(), (64, 64));
+    let rects: Vec<_> = ids.iter().skip(1).step_by(2).chain([&wide]).map(|&id| atlas.rect(id).unwrap()).collect();
+    assert_disjoint(&rects, 64, 64);
+}
+
+#[test]
+fn tracks_dirty_regions() {
+    let mut atlas = TextureAtlas::new(64, wgpu::TextureFormat::R8Unorm);
+    let id = atlas.insert(4, 4, &[0; 16]).unwrap();
+
+    // Nothing is on the GPU yet, so the whole texture has to be written
+    assert_eq!(atlas.dirty_regions(), vec![AtlasRect::new(0, 0, 64, 64)]);
+    assert!(atlas.remove(id));
+    assert!(!atlas.remove(id));
+}
+
+// Reads back an `R8Unorm` atlas that is 256 texels wide, so rows need no padding
+fn read_back(device: &wgpu::Device, queue: &wgpu::Queue, atlas: &TextureAtlas) -> Vec<u8> {
+    let texture = atlas.texture().unwrap();
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (texture.width() * texture.height()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(texture.width()), rows_per_image: None },
+        },
+        texture.size(),
+    );
+    queue.submit(Some(encoder.finish()));
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let texels = buffer.slice(..).get_mapped_range().to_vec();
+    texels
+}
+
+#[test]
+fn clears_padding_left_by_removed_entries() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let mut atlas = TextureAtlas::new(256, wgpu::TextureFormat::R8Unorm).with_max_size(256);
+    let old = atlas.insert(8, 8, &[255; 64]).unwrap();
+    atlas.upload(&device, &queue);
+
+    // A smaller entry takes the freed space, so its padding covers texels of the old one
+    atlas.remove(old);
+    let new = atlas.insert(6, 6, &[128; 36]).unwrap();
+    let rect = atlas.rect(new).unwrap();
+    assert_eq!(rect, AtlasRect::new(1, 1, 6, 6));
+    assert_eq!(atlas.dirty_regions(), vec![AtlasRect::new(0, 0, 8, 8)]);
+    assert!(!atlas.upload(&device, &queue));
+
+    // Everything linear filtering can reach from the new entry
+    let texels = read_back(&device, &queue, &atlas);
+    for y in 0..8 {
+        for x in 0..8 {
+            let inside = (rect.x..rect.right()).contains(&x) && (rect.y..rect.bottom()).contains(&y);
+            assert_eq!(texels[(y * 256 + x) as usize], if inside { 128 } else { 0 }, "texel {x}, {y}");
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use encase::ShaderType;
+use framework::camera::{Camera, CameraController, CameraUniform, OrbitController};
+use glam::{vec3, Vec3};
+use winit::{
+    dpi::PhysicalSize,
+    event::{DeviceId, MouseScrollDelta, TouchPhase, WindowEvent},
+};
+
+fn camera() -> Camera {
+    Camera::perspective(vec3(0.0, 0.0, 5.0), Vec3::ZERO, std::f32::consts::FRAC_PI_4, 1.0)
+}
+
+fn scroll(lines: f32) -> WindowEvent {
+    WindowEvent::MouseWheel {
+        // Never compared by the controller
+        device_id: unsafe { DeviceId::dummy() },
+        delta: MouseScrollDelta::LineDelta(0.0, lines),
+        phase: TouchPhase::Moved,
+    }
+}
+
+#[test]
+fn resize_updates_the_aspect_ratio() {
+    let mut camera = camera();
+    let square = camera.projection_matrix();
+
+    camera.resize(PhysicalSize::new(1920, 1080));
+    assert_eq!(camera.aspect, 1920.0 / 1080.0);
+    // A wider view squeezes x by the aspect ratio and leaves y alone
+    let wide = camera.projection_matrix();
+    assert!((wide.x_axis.x - square.x_axis.x / camera.aspect).abs() < 1e-6);
+    assert_eq!(wide.y_axis.y, square.y_axis.y);
+
+    // A minimized window doesn't divide by zero
+    camera.resize(PhysicalSize::new(800, 0));
+    assert_eq!(camera.aspect, 800.0);
+    camera.resize(PhysicalSize::new(0, 0));
+    assert_eq!(camera.aspect, 1.0);
+}
+
+#[test]
+fn orbit_keeps_its_distance_from_the_target() {
+    let mut camera = camera();
+    let mut controller = OrbitController::new(&camera);
+    assert!((controller.distance - 5.0).abs() < 1e-6);
+
+    controller.update(&mut camera, 0.0);
+    assert!(camera.eye.abs_diff_eq(vec3(0.0, 0.0, 5.0), 1e-5));
+
+    // Scrolling out moves away by the zoom speed per line
+    assert!(controller.handle_event(&scroll(-2.0)));
+    controller.update(&mut camera, 0.0);
+    assert!((controller.distance - 5.0 * (1.0 + 2.0 * controller.zoom_speed)).abs() < 1e-5);
+    assert!((camera.eye.distance(camera.target) - controller.distance).abs() < 1e-5);
+}
+
+#[test]
+fn orbit_zoom_stops_at_the_minimum_distance() {
+    let mut camera = camera();
+    let mut controller = OrbitController::new(&camera);
+    controller.min_distance = 2.0;
+
+    // Far enough to overshoot past the target
+    controller.handle_event(&scroll(100.0));
+    controller.update(&mut camera, 0.0);
+    assert_eq!(controller.distance, 2.0);
+    assert!((camera.eye.distance(camera.target) - 2.0).abs() < 1e-5);
+
+    // And stays there while scrolling in further
+    for _ in 0..10 {
+        controller.handle_event(&scroll(1.0));
+        controller.update(&mut camera, 0.0);
+    }
+    assert_eq!(controller.distance, 2.0);
+}
+
+#[test]
+fn camera_uniform_matches_wgsl_layout() {
+    // Three `mat4x4` and a `vec3`, padded to the struct's 16 byte alignment
+    assert_eq!(CameraUniform::min_size().get(), 3 * 64 + 16);
+    assert_eq!(camera().uniform().as_wgsl_bytes().unwrap().len(), 208);
+}
@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+use framework::profiler::{ChromeTrace, ScopeTiming};
+
+fn trace() -> ChromeTrace {
+    ChromeTrace::new(std::env::temp_dir().join("framework-chrome-trace-test.json"))
+}
+
+#[test]
+fn only_records_while_recording() {
+    let mut trace = trace();
+    assert_eq!(trace.scope("Ignored", || 1 + 1), 2);
+    assert!(trace.is_empty());
+
+    trace.start();
+    trace.scope("Recorded", || ());
+    assert_eq!(trace.len(), 1);
+
+    // Starting again begins a new recording
+    trace.start();
+    assert!(trace.is_empty());
+}
+
+#[test]
+fn exports_complete_events_on_separate_tracks() {
+    let mut trace = trace();
+    trace.start();
+    let start = Instant::now();
+    trace.record_cpu("Submit", start, start + Duration::from_micros(250));
+    trace.record_timing(&ScopeTiming {
+        name: "Shadow \"pass\"".to_owned(),
+        start,
+        cpu: Duration::from_micros(10),
+        gpu: Some(Duration::from_micros(500)),
+        gpu_start: Some(start + Duration::from_millis(1)),
+    });
+    // Without GPU timing, a scope only shows on the CPU track
+    trace.record_timing(&ScopeTiming { name: "Untimed".to_owned(), start, cpu: Duration::from_micros(5), gpu: None, gpu_start: None });
+    assert_eq!(trace.len(), 4);
+
+    let json = trace.to_json();
+    assert!(json.starts_with("{\"displayTimeUnit\":\"ms\",\"traceEvents\":[{\"name\":\"thread_name\""));
+    assert!(json.ends_with("}]}"));
+    assert!(json.contains("\"args\":{\"name\":\"GPU\"}"));
+    assert!(json.contains("\"name\":\"Submit\",\"cat\":\"cpu\",\"ph\":\"X\""));
+    assert!(json.contains("\"dur\":250.000,\"pid\":1,\"tid\":1}"));
+    assert!(json.contains("\"name\":\"Shadow \\\"pass\\\"\",\"cat\":\"gpu\""));
+    assert!(json.contains("\"dur\":500.000,\"pid\":1,\"tid\":2}"));
+    assert_eq!(json.matches("\"name\":\"Untimed\"").count(), 1);
+}
+
+#[test]
+fn stopping_writes_the_file() {
+    let path = std::env::temp_dir().join("framework-chrome-trace-stop.json");
+    let _ = std::fs::remove_file(&path);
+    let mut trace = ChromeTrace::new(&path);
+    // Stopping a trace that isn't recording writes nothing
+    trace.stop().unwrap();
+    assert!(!path.exists());
+
+    trace.toggle().unwrap();
+    trace.scope("Frame", || ());
+    trace.toggle().unwrap();
+    assert!(!trace.is_recording());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), trace.to_json());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,195 @@
+use framework::{
+    camera::Camera,
+    cubemap::{self, HDR_CUBEMAP_FORMAT},
+    offscreen_device,
+    skybox::Skybox,
+    RenderPassBuilder, TextureBuilder, WgpuContext,
+};
+use glam::{vec3, Vec3};
+use image::{codecs::hdr::HdrEncoder, Rgb};
+
+const FACE_SIZE: u32 = 32;
+// Rows of 32 `Rgba16Float` texels are 256 bytes, so they need no padding to be copied
+const TARGET_SIZE: u32 = 32;
+
+// Encodes a Radiance `.hdr` image, `pixel` giving each texel's color from its coordinates
+fn hdr(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [f32; 3]) -> Vec<u8> {
+    let pixels: Vec<Rgb<f32>> = (0..width * height).map(|index| Rgb(pixel(index % width, index / width))).collect();
+    let mut bytes = Vec::new();
+    HdrEncoder::new(&mut bytes).encode(&pixels, width as usize, height as usize).unwrap();
+    bytes
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits >> 15 == 1 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32 / 1024.0;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-14),
+        31 => sign * f32::INFINITY,
+        _ => sign * (1.0 + mantissa) * 2f32.powi(exponent - 15),
+    }
+}
+
+fn read_back(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture, bytes_per_texel: u32) -> Vec<u8> {
+    let (width, height) = (texture.width(), texture.height());
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (width * height * bytes_per_texel) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(width * bytes_per_texel), rows_per_image: None },
+        },
+        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+    queue.submit(Some(encoder.finish()));
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let bytes = buffer.slice(..).get_mapped_range().to_vec();
+    bytes
+}
+
+#[test]
+fn equirectangular_faces_look_the_right_way() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    // Bright red around the top pole, blue around the bottom one, and a green ramp along the
+    // longitude in between
+    let (width, height) = (64, 32);
+    let panorama = hdr(width, height, |x, y| match y * 4 / height {
+        0 => [4.0, 0.0, 0.0],
+        3 => [0.0, 0.0, 0.5],
+        _ => [0.0, (x as f32 + 0.5) / width as f32, 0.0],
+    });
+    let cubemap = cubemap::from_equirectangular(&device, &queue, &panorama, FACE_SIZE).unwrap();
+    assert_eq!(cubemap.texture.format(), HDR_CUBEMAP_FORMAT);
+
+    // Copies out of cube textures read zeros on the GL backend, so each face is looked at through
+    // a skybox instead, rendered to a float target that keeps values above 1.0
+    let skybox = Skybox::new(&device, &cubemap, HDR_CUBEMAP_FORMAT, None);
+    let target = TextureBuilder::new(TARGET_SIZE, TARGET_SIZE)
+        .format(HDR_CUBEMAP_FORMAT)
+        .usage(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC)
+        .build(&device);
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let look = |direction: Vec3| {
+        let mut camera = Camera::perspective(Vec3::ZERO, direction, 0.1, 1.0);
+        if direction.y != 0.0 {
+            camera.up = Vec3::Z;
+        }
+        skybox.update(&queue, &camera);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut rpass = RenderPassBuilder::new().clear(&view, wgpu::Color::BLACK).build(&mut encoder);
+            skybox.draw(&mut rpass);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        let texels = read_back(&device, &queue, &target, 8);
+        let index = ((TARGET_SIZE / 2 * TARGET_SIZE + TARGET_SIZE / 2) * 8) as usize;
+        let channel = |channel: usize| f16_to_f32(u16::from_le_bytes([texels[index + 2 * channel], texels[index + 2 * channel + 1]]));
+        Vec3::new(channel(0), channel(1), channel(2))
+    };
+
+    let up = look(Vec3::Y);
+    assert!(up.abs_diff_eq(vec3(4.0, 0.0, 0.0), 1e-2), "+Y is {up}");
+    let down = look(Vec3::NEG_Y);
+    assert!(down.abs_diff_eq(vec3(0.0, 0.0, 0.5), 1e-2), "-Y is {down}");
+    // Longitude 0.5 faces +X and increases towards +Z
+    for (direction, longitude) in [(Vec3::X, 0.5), (Vec3::Z, 0.75), (Vec3::NEG_Z, 0.25)] {
+        let color = look(direction);
+        assert!((color.y - longitude).abs() < 0.02 && color.x == 0.0 && color.z == 0.0, "{direction} is {color}");
+    }
+}
+
+#[test]
+fn skybox_is_drawn_behind_geometry() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    const SIZE: u32 = 64;
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+
+    let cubemap = cubemap::from_equirectangular(&device, &queue, &hdr(8, 4, |_, _| [1.0, 0.0, 0.0]), 8).unwrap();
+    let skybox = Skybox::new(&device, &cubemap, format, Some(WgpuContext::DEPTH_FORMAT));
+    let camera = Camera::perspective(Vec3::ZERO, Vec3::NEG_Z, std::f32::consts::FRAC_PI_2, 1.0);
+    skybox.update(&queue, &camera);
+
+    // A green rectangle over the left half of the target, half way into the depth range
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(
+            "@vertex
+            fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+                return vec4(f32(index & 1u) - 1.0, f32(index >> 1u) * 2.0 - 1.0, 0.5, 1.0);
+            }
+
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4(0.0, 1.0, 0.0, 1.0);
+            }"
+            .into(),
+        ),
+    });
+    let geometry = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: None,
+        layout: None,
+        vertex: wgpu::VertexState { module: &module, entry_point: "vs_main", buffers: &[] },
+        primitive: wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleStrip, ..Default::default() },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: WgpuContext::DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState { module: &module, entry_point: "fs_main", targets: &[Some(format.into())] }),
+        multiview: None,
+    });
+
+    let target = TextureBuilder::new(SIZE, SIZE)
+        .format(format)
+        .usage(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC)
+        .build(&device);
+    let depth = TextureBuilder::new(SIZE, SIZE)
+        .format(WgpuContext::DEPTH_FORMAT)
+        .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
+        .build(&device);
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut rpass = RenderPassBuilder::new()
+            .clear(&view, wgpu::Color::BLACK)
+            .depth(&depth_view, 1.0)
+            .build(&mut encoder);
+        rpass.set_pipeline(&geometry);
+        rpass.draw(0..4, 0..1);
+        skybox.draw(&mut rpass);
+    }
+    queue.submit(Some(encoder.finish()));
+
+    let pixels = read_back(&device, &queue, &target, 4);
+    let pixel = |x: u32, y: u32| {
+        let index = ((y * SIZE + x) * 4) as usize;
+        [pixels[index], pixels[index + 1], pixels[index + 2]]
+    };
+    for y in [0, SIZE / 2, SIZE - 1] {
+        // The geometry stays in front, and the sky fills everything the depth buffer left at
+        // the far plane
+        assert_eq!(pixel(SIZE / 4, y), [0, 255, 0], "row {y}");
+        assert_eq!(pixel(3 * SIZE / 4, y), [255, 0, 0], "row {y}");
+    }
+}
+
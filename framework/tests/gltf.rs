@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+
+use framework::{
+    model::{AlphaMode, GltfError, GltfScene},
+    texture::ColorSpace,
+};
+use glam::{Mat4, Vec3};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+// Packs a glTF document and its buffer into a `.glb`
+fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+    let pad = |bytes: &[u8], with: u8| {
+        let mut bytes = bytes.to_vec();
+        bytes.resize(bytes.len().next_multiple_of(4), with);
+        bytes
+    };
+    let (json, bin) = (pad(json.as_bytes(), b' '), pad(bin, 0));
+    let mut glb = Vec::new();
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+    for (chunk, kind) in [(&json, b"JSON"), (&bin, b"BIN\0")] {
+        glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        glb.extend_from_slice(kind);
+        glb.extend_from_slice(chunk);
+    }
+    glb
+}
+
+// A triangle with two UV sets, sampled by a material whose base color and normal textures use
+// `base_color_set` and `normal_set`
+fn two_uv_sets(base_color_set: u32, normal_set: u32) -> Vec<u8> {
+    let floats: [f32; 21] = [
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+        0.5, 0.5, 0.25, 0.5, 0.5, 0.25,
+    ];
+    let mut bin: Vec<u8> = floats.iter().flat_map(|value| value.to_le_bytes()).collect();
+    let mut png = Vec::new();
+    image::RgbaImage::new(1, 1)
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    bin.extend_from_slice(&png);
+
+    let json = format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "nodes": [{{ "mesh": 0 }}],
+            "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 1, "TEXCOORD_1": 2 }}, "material": 0 }}] }}],
+            "materials": [{{
+                "pbrMetallicRoughness": {{ "baseColorTexture": {{ "index": 0, "texCoord": {base_color_set} }} }},
+                "normalTexture": {{ "index": 0, "texCoord": {normal_set} }}
+            }}],
+            "textures": [{{ "source": 0 }}],
+            "images": [{{ "bufferView": 3, "mimeType": "image/png" }}],
+            "buffers": [{{ "byteLength": {} }}],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                {{ "buffer": 0, "byteOffset": 36, "byteLength": 24 }},
+                {{ "buffer": 0, "byteOffset": 60, "byteLength": 24 }},
+                {{ "buffer": 0, "byteOffset": 84, "byteLength": {} }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }},
+                {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" }},
+                {{ "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2" }}
+            ]
+        }}"#,
+        bin.len(),
+        png.len(),
+    );
+    glb(&json, &bin)
+}
+
+#[test]
+fn loads_the_box_sample() {
+    let scene = GltfScene::load(fixture("Box.gltf")).unwrap();
+
+    assert_eq!(scene.roots, vec![0]);
+    assert_eq!(scene.nodes[0].children, vec![1]);
+    assert_eq!(scene.nodes[1].parent, Some(0));
+    assert_eq!(scene.nodes[1].mesh, Some(0));
+    assert_eq!(scene.meshes[0].name.as_deref(), Some("Mesh"));
+
+    let primitive = &scene.meshes[0].primitives[0];
+    assert_eq!(primitive.mesh.vertices.len(), 24);
+    assert_eq!(primitive.mesh.indices.len(), 36);
+    assert_eq!(&primitive.mesh.indices[..6], &[0, 1, 2, 3, 2, 1]);
+    assert_eq!(primitive.mesh.indices.iter().max(), Some(&23));
+    assert_eq!(primitive.material, Some(0));
+    for vertex in &primitive.mesh.vertices {
+        assert!(Vec3::from(vertex.position).abs().abs_diff_eq(Vec3::splat(0.5), 1e-6));
+        // Every vertex sits on the face its normal points out of
+        assert!((Vec3::from(vertex.position).dot(Vec3::from(vertex.normal)) - 0.5).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn combines_node_transforms() {
+    let scene = GltfScene::load(fixture("Box.gltf")).unwrap();
+
+    // The root's published matrix turns the Z-up model Y-up, and the mesh node has no transform
+    let root = Mat4::from_cols_array(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+    assert!(scene.world_transform(0).abs_diff_eq(root, 1e-6));
+    assert!(scene.world_transform(1).abs_diff_eq(root, 1e-6));
+    assert!(root.transform_vector3(Vec3::Z).abs_diff_eq(Vec3::Y, 1e-6));
+
+    let instances = scene.mesh_instances();
+    assert_eq!(instances.len(), 1);
+    assert_eq!(instances[0].0, 0);
+    assert!(instances[0].1.abs_diff_eq(root, 1e-6));
+}
+
+#[test]
+fn reads_pbr_material() {
+    let scene = GltfScene::load(fixture("Box.gltf")).unwrap();
+    let material = &scene.materials[0];
+
+    assert_eq!(material.name.as_deref(), Some("Red"));
+    assert_eq!(material.base_color_factor, [0.8, 0.0, 0.0, 1.0]);
+    assert_eq!(material.metallic_factor, 0.0);
+    // Not in the file, so the spec's default
+    assert_eq!(material.roughness_factor, 1.0);
+    assert_eq!(material.alpha_mode, AlphaMode::Opaque);
+    assert!(!material.double_sided);
+    assert!(material.base_color_texture.is_none());
+}
+
+#[test]
+fn fills_in_missing_indices_and_normals() {
+    let scene = GltfScene::load(fixture("TriangleWithoutIndices.gltf")).unwrap();
+    let mesh = &scene.meshes[0].primitives[0].mesh;
+
+    assert_eq!(mesh.indices, vec![0, 1, 2]);
+    let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|vertex| vertex.position).collect();
+    assert_eq!(positions, [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+    for vertex in &mesh.vertices {
+        assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+    }
+    assert_eq!(scene.meshes[0].primitives[0].material, None);
+    assert_eq!(scene.world_transform(0), Mat4::IDENTITY);
+}
+
+#[test]
+fn loads_binary_with_embedded_texture() {
+    let bytes = std::fs::read(fixture("QuadTextured.glb")).unwrap();
+    let scene = GltfScene::from_slice(&bytes).unwrap();
+
+    // The triangle fan is turned into a list
+    let mesh = &scene.meshes[0].primitives[0].mesh;
+    assert_eq!(mesh.topology, wgpu::PrimitiveTopology::TriangleList);
+    assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(mesh.vertices[2].uv, [1.0, 0.0]);
+
+    let material = &scene.materials[0];
+    assert_eq!(material.alpha_mode, AlphaMode::Mask);
+    assert_eq!(material.alpha_cutoff, 0.25);
+
+    let texture = &scene.textures[0];
+    assert_eq!(texture.mag_filter, wgpu::FilterMode::Nearest);
+    assert_eq!(texture.address_mode_u, wgpu::AddressMode::ClampToEdge);
+    assert_eq!(texture.address_mode_v, wgpu::AddressMode::MirrorRepeat);
+
+    let image = &scene.images[0];
+    assert_eq!((image.width, image.height), (2, 2));
+    assert_eq!(&image.pixels[..8], &[255, 0, 0, 255, 0, 255, 0, 255]);
+}
+
+#[test]
+fn splits_images_used_as_color_and_data() {
+    let bytes = std::fs::read(fixture("QuadTextured.glb")).unwrap();
+    let scene = GltfScene::from_slice(&bytes).unwrap();
+
+    // The file's only texture is both the base color and the metallic-roughness texture
+    let material = &scene.materials[0];
+    let base_color = &scene.textures[material.base_color_texture.unwrap().texture];
+    let metallic_roughness = &scene.textures[material.metallic_roughness_texture.unwrap().texture];
+    assert_eq!(scene.textures.len(), 2);
+    assert_eq!(scene.images.len(), 2);
+    assert_eq!(scene.images[base_color.image].color_space, ColorSpace::Srgb);
+    assert_eq!(scene.images[metallic_roughness.image].color_space, ColorSpace::Linear);
+    assert_eq!(scene.images[base_color.image].pixels, scene.images[metallic_roughness.image].pixels);
+    // The copy keeps the sampler
+    assert_eq!(base_color.address_mode_v, metallic_roughness.address_mode_v);
+}
+
+#[test]
+fn reads_the_uv_set_the_material_uses() {
+    let scene = GltfScene::from_slice(&two_uv_sets(1, 1)).unwrap();
+    let uvs: Vec<[f32; 2]> = scene.meshes[0].primitives[0].mesh.vertices.iter().map(|vertex| vertex.uv).collect();
+    assert_eq!(uvs, [[0.5, 0.5], [0.25, 0.5], [0.5, 0.25]]);
+    assert_eq!(scene.materials[0].base_color_texture.unwrap().tex_coord, 1);
+
+    let scene = GltfScene::from_slice(&two_uv_sets(0, 0)).unwrap();
+    assert_eq!(scene.meshes[0].primitives[0].mesh.vertices[1].uv, [1.0, 0.0]);
+}
+
+#[test]
+fn loads_the_base_color_uv_set_for_mixed_materials() {
+    let scene = GltfScene::from_slice(&two_uv_sets(0, 1)).unwrap();
+    assert_eq!(scene.meshes[0].primitives[0].mesh.vertices[1].uv, [1.0, 0.0]);
+
+    let scene = GltfScene::from_slice(&two_uv_sets(1, 0)).unwrap();
+    assert_eq!(scene.meshes[0].primitives[0].mesh.vertices[1].uv, [0.25, 0.5]);
+    // The other texture keeps its reference, even though its set wasn't loaded
+    assert_eq!(scene.materials[0].normal_texture.unwrap().tex_coord, 0);
+}
+
+#[test]
+fn rejects_attributes_shorter_than_positions() {
+    // Three positions, but normals for only two of them
+    match GltfScene::load(fixture("ShortNormals.gltf")) {
+        Err(GltfError::MalformedPrimitive { mesh, primitive, message }) => {
+            assert_eq!((mesh, primitive), (0, 0));
+            assert_eq!(message, "NORMAL has 2 values for 3 positions");
+        }
+        other => panic!("expected a MalformedPrimitive error, got {:?}", other.map(|_| ())),
+    }
+}
@@ -0,0 +1,127 @@
+use framework::mesh::{self, compute_tangents, index_format_for, Mesh, MeshVertex};
+use glam::{vec2, vec3, Vec3, Vec4};
+
+// Every generated triangle list must be in range, with unit normals and tangents perpendicular
+// to them, and wind counter-clockwise around the normals it was given
+fn check(name: &str, mesh: &Mesh<MeshVertex>) {
+    assert_eq!(mesh.indices.len() % 3, 0, "{name}");
+    assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()), "{name}: index out of range");
+    for vertex in &mesh.vertices {
+        let normal = Vec3::from(vertex.normal);
+        let tangent = Vec4::from(vertex.tangent);
+        assert!((normal.length() - 1.0).abs() < 1e-4, "{name}: normal {normal} isn't unit length");
+        assert!((tangent.truncate().length() - 1.0).abs() < 1e-4, "{name}: tangent {tangent} isn't unit length");
+        assert!(tangent.truncate().dot(normal).abs() < 1e-4, "{name}: tangent {tangent} isn't perpendicular to {normal}");
+        assert!(tangent.w.abs() == 1.0, "{name}: handedness {}", tangent.w);
+    }
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+        let [pa, pb, pc] = [a, b, c].map(|vertex| Vec3::from(vertex.position));
+        let face = (pb - pa).cross(pc - pa);
+        if face.length() < 1e-6 {
+            continue;
+        }
+        let normals = Vec3::from(a.normal) + Vec3::from(b.normal) + Vec3::from(c.normal);
+        assert!(face.dot(normals) > 0.0, "{name}: triangle {triangle:?} faces away from its normals");
+    }
+}
+
+fn counts(mesh: &Mesh<MeshVertex>) -> (usize, usize) {
+    (mesh.vertices.len(), mesh.indices.len())
+}
+
+#[test]
+fn cube_has_four_vertices_per_face() {
+    let cube = mesh::cube(2.0);
+    check("cube", &cube);
+    assert_eq!(counts(&cube), (24, 36));
+    assert!(cube.vertices.iter().all(|vertex| Vec3::from(vertex.position).abs() == Vec3::ONE));
+}
+
+#[test]
+fn plane_is_one_quad_facing_up() {
+    let plane = mesh::plane(4.0);
+    check("plane", &plane);
+    assert_eq!(counts(&plane), (4, 6));
+    assert!(plane.vertices.iter().all(|vertex| vertex.normal == [0.0, 1.0, 0.0] && vertex.position[1] == 0.0));
+
+    let grid = mesh::grid(4.0, 2.0, 4, 2);
+    check("grid", &grid);
+    assert_eq!(counts(&grid), (5 * 3, 4 * 2 * 6));
+}
+
+#[test]
+fn uv_sphere_drops_triangles_at_the_poles() {
+    let sphere = mesh::uv_sphere(2.0, 16, 8);
+    check("uv sphere", &sphere);
+    // A seam column of vertices, and fans instead of quads in the first and last bands
+    assert_eq!(counts(&sphere), (17 * 9, (16 * 8 * 2 - 2 * 16) * 3));
+    assert!(sphere.vertices.iter().all(|vertex| (Vec3::from(vertex.position).length() - 2.0).abs() < 1e-5));
+}
+
+#[test]
+fn icosphere_quadruples_triangles_per_subdivision() {
+    for (subdivisions, vertices) in [(0, 12), (1, 42), (2, 162)] {
+        let sphere = mesh::icosphere(1.0, subdivisions);
+        check("icosphere", &sphere);
+        assert_eq!(counts(&sphere), (vertices, 20 * 4usize.pow(subdivisions) * 3));
+        assert!(sphere.vertices.iter().all(|vertex| (Vec3::from(vertex.position).length() - 1.0).abs() < 1e-5));
+    }
+}
+
+#[test]
+fn cylinder_and_cone_have_capped_bases() {
+    let cylinder = mesh::cylinder(1.0, 2.0, 16);
+    check("cylinder", &cylinder);
+    // Two side rings, and two caps of a center and a ring each
+    assert_eq!(counts(&cylinder), (2 * 17 + 2 * 18, 16 * 6 + 2 * 16 * 3));
+
+    let cone = mesh::cone(1.0, 2.0, 16);
+    check("cone", &cone);
+    assert_eq!(counts(&cone), (2 * 17 + 18, 16 * 3 + 16 * 3));
+}
+
+#[test]
+fn torus_is_a_closed_grid() {
+    let torus = mesh::torus(2.0, 0.5, 16, 8);
+    check("torus", &torus);
+    assert_eq!(counts(&torus), (17 * 9, 16 * 8 * 6));
+    for vertex in &torus.vertices {
+        let position = Vec3::from(vertex.position);
+        let ring = vec3(position.x, 0.0, position.z).normalize() * 2.0;
+        assert!((position.distance(ring) - 0.5).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn picks_index_format_by_vertex_count() {
+    assert_eq!(index_format_for(0), wgpu::IndexFormat::Uint16);
+    assert_eq!(index_format_for(65535), wgpu::IndexFormat::Uint16);
+    // 65536 vertices would need index 0xFFFF, the strip restart value
+    assert_eq!(index_format_for(65536), wgpu::IndexFormat::Uint32);
+    assert_eq!(mesh::cube(1.0).index_format(), wgpu::IndexFormat::Uint16);
+}
+
+#[test]
+fn tangents_follow_increasing_u() {
+    // A plane facing up, with u along +x and v along +z
+    let plane = mesh::plane(1.0);
+    for vertex in &plane.vertices {
+        assert_eq!(vertex.tangent, [1.0, 0.0, 0.0, -1.0]);
+    }
+
+    // Mirroring the texture flips the handedness
+    let triangle = |u_direction: f32| {
+        let vertices = [vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)]
+            .into_iter()
+            .zip([vec2(0.0, 1.0), vec2(u_direction, 1.0), vec2(0.0, 0.0)])
+            .map(|(position, uv)| MeshVertex::new(position, Vec3::Z, uv))
+            .collect();
+        let mut mesh = Mesh::new(vertices, vec![0, 1, 2]);
+        compute_tangents(&mut mesh);
+        mesh.vertices[0].tangent
+    };
+    // v points down in textures, so an unmirrored layout is left-handed like the plane's
+    assert_eq!(triangle(1.0), [1.0, 0.0, 0.0, -1.0]);
+    assert_eq!(triangle(-1.0), [-1.0, 0.0, 0.0, 1.0]);
+}
@@ -0,0 +1,119 @@
+use framework::{mipmap::{mip_level_count, MipmapGenerator}, offscreen_device, TextureBuilder};
+
+const SIZE: u32 = 16;
+
+fn texels(layer: u32) -> Vec<u8> {
+    (0..SIZE * SIZE)
+        .flat_map(|i| {
+            let (x, y) = (i % SIZE, i / SIZE);
+            [(x * 16) as u8, (y * 16) as u8, ((x ^ y) * 16) as u8, 255 - (layer * 64) as u8]
+        })
+        .collect()
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    let value = if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 };
+    value * 255.0
+}
+
+// The average of every texel, which is what repeated 2x2 box filtering converges to for
+// power-of-two sizes. Color channels of sRGB data are averaged in linear space.
+fn box_filter_average(texels: &[u8], srgb: bool) -> [f32; 4] {
+    let count = (texels.len() / 4) as f32;
+    std::array::from_fn(|channel| {
+        let values = texels.iter().skip(channel).step_by(4);
+        if srgb && channel < 3 {
+            linear_to_srgb(values.map(|&v| srgb_to_linear(v)).sum::<f32>() / count)
+        } else {
+            values.map(|&v| v as f32).sum::<f32>() / count
+        }
+    })
+}
+
+// Copies the last (1x1) mip level of every layer back to the CPU
+fn read_smallest_mip(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Vec<[u8; 4]> {
+    let layers = texture.depth_or_array_layers();
+    let row = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (row * layers) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    for layer in 0..layers {
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: texture.mip_level_count() - 1,
+                origin: wgpu::Origin3d { x: 0, y: 0, z: layer },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout { offset: (row * layer) as u64, bytes_per_row: Some(row), rows_per_image: None },
+            },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        );
+    }
+    queue.submit(Some(encoder.finish()));
+
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let data = buffer.slice(..).get_mapped_range();
+    (0..layers as usize).map(|layer| data[layer * row as usize..][..4].try_into().unwrap()).collect()
+}
+
+fn check_smallest_mip(format: wgpu::TextureFormat, layers: u32) {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("Skipping mipmap test: no adapter available");
+        return;
+    };
+
+    let texture = TextureBuilder::new(SIZE, SIZE)
+        .array_layers(layers)
+        .format(format)
+        .full_mip_chain()
+        .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST)
+        .build_with_data(&device, &queue, &(0..layers).flat_map(texels).collect::<Vec<_>>());
+    assert_eq!(texture.mip_level_count(), 5);
+
+    MipmapGenerator::new(&device).generate_and_submit(&device, &queue, &texture);
+
+    for (layer, texel) in read_smallest_mip(&device, &queue, &texture).into_iter().enumerate() {
+        let expected = box_filter_average(&texels(layer as u32), format.is_srgb());
+        for channel in 0..4 {
+            // Every level is rounded to 8 bits, so allow a little drift over the chain
+            let difference = (texel[channel] as f32 - expected[channel]).abs();
+            assert!(difference <= 3.0, "layer {layer} channel {channel}: got {}, expected {}", texel[channel], expected[channel]);
+        }
+    }
+}
+
+#[test]
+fn counts_mip_levels() {
+    assert_eq!(mip_level_count(1, 1), 1);
+    assert_eq!(mip_level_count(16, 16), 5);
+    assert_eq!(mip_level_count(17, 3), 5);
+    assert_eq!(mip_level_count(256, 1024), 11);
+}
+
+#[test]
+fn linear_mips_match_box_filter() {
+    check_smallest_mip(wgpu::TextureFormat::Rgba8Unorm, 1);
+}
+
+#[test]
+fn srgb_mips_are_filtered_in_linear_space() {
+    check_smallest_mip(wgpu::TextureFormat::Rgba8UnormSrgb, 1);
+}
+
+#[test]
+fn array_layers_get_their_own_chains() {
+    check_smallest_mip(wgpu::TextureFormat::Rgba8UnormSrgb, 3);
+}
@@ -0,0 +1,115 @@
+use std::path::{Path, PathBuf};
+
+use framework::model::{ObjError, ObjModel};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+#[test]
+fn loads_meshes_split_by_material() {
+    let model = ObjModel::load(fixture("cube.obj")).unwrap();
+
+    assert_eq!(model.materials.len(), 2);
+    assert_eq!(model.meshes.len(), 2);
+    for (mesh, material) in model.meshes.iter().zip(["Red", "Blue"]) {
+        assert_eq!(mesh.name, "Cube");
+        assert_eq!(model.materials[mesh.material.unwrap()].name, material);
+        // Three quads, each corner with its own normal
+        assert_eq!(mesh.mesh.vertices.len(), 12);
+        assert_eq!(mesh.mesh.indices.len(), 18);
+        assert_eq!(mesh.mesh.index_format(), wgpu::IndexFormat::Uint16);
+    }
+}
+
+#[test]
+fn parses_material_library() {
+    let model = ObjModel::load(fixture("cube.obj")).unwrap();
+    let (red, blue) = (&model.materials[0], &model.materials[1]);
+
+    assert_eq!(red.diffuse, [0.8, 0.0, 0.0]);
+    assert_eq!(red.shininess, 32.0);
+    assert_eq!(red.diffuse_texture.as_deref(), Some(Path::new("textures/red.png")));
+    assert_eq!(blue.dissolve, 0.75);
+    assert_eq!(blue.normal_texture.as_deref(), Some(Path::new("textures/blue_normal.png")));
+}
+
+#[test]
+fn deduplicates_shared_corners() {
+    let model = ObjModel::load(fixture("quad.obj")).unwrap();
+    let mesh = &model.meshes[0].mesh;
+
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+    // Texture coordinates are flipped to point down
+    assert_eq!(mesh.vertices[3].uv, [0.0, 0.0]);
+    // Missing normals are generated from the faces
+    for vertex in &mesh.vertices {
+        assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+    }
+}
+
+#[test]
+fn picks_32_bit_indices_for_large_meshes() {
+    let triangles = 22_000;
+    let mut source = String::new();
+    for i in 0..triangles * 3 {
+        source += &format!("v {i} 0 0\n");
+    }
+    for i in 0..triangles {
+        source += &format!("f {} {} {}\n", 3 * i + 1, 3 * i + 2, 3 * i + 3);
+    }
+
+    let model = ObjModel::parse("large.obj", &source, |_| unreachable!()).unwrap();
+    assert_eq!(model.meshes[0].mesh.vertices.len(), 66_000);
+    assert_eq!(model.meshes[0].mesh.index_format(), wgpu::IndexFormat::Uint32);
+}
+
+#[test]
+fn reports_line_of_bad_index() {
+    let source = "v 0 0 0\nv 1 0 0\nf 1 2 3\n";
+    match ObjModel::parse("bad.obj", source, |_| unreachable!()) {
+        Err(ObjError::Parse { line, .. }) => assert_eq!(line, 3),
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn ignores_extra_vertex_values() {
+    // A w coordinate on the first vertex, colors on the others
+    let source = "v 0 0 0 1\nv 1 0 0 1 0 0\nv 0 1 0 0 1 0\nf 1 2 3\n";
+    let model = ObjModel::parse("colors.obj", source, |_| unreachable!()).unwrap();
+    let positions: Vec<[f32; 3]> = model.meshes[0].mesh.vertices.iter().map(|vertex| vertex.position).collect();
+    assert_eq!(positions, [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+}
+
+#[test]
+fn skips_unknown_materials_and_statements() {
+    let source = "vp 0.5\ncstype bezier\nshadow_obj shadow.obj\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl missing\nf 1 2 3\n";
+    let model = ObjModel::parse("lenient.obj", source, |_| unreachable!()).unwrap();
+    assert_eq!(model.meshes.len(), 1);
+    assert_eq!(model.meshes[0].material, None);
+    assert_eq!(model.meshes[0].mesh.indices.len(), 3);
+}
+
+#[test]
+fn skips_missing_material_libraries() {
+    let model = ObjModel::load(fixture("missing_library.obj")).unwrap();
+    assert!(model.materials.is_empty());
+    assert_eq!(model.meshes.len(), 1);
+    assert_eq!(model.meshes[0].material, None);
+    assert_eq!(model.meshes[0].mesh.indices.len(), 3);
+}
+
+#[test]
+fn library_names_may_contain_spaces() {
+    let source = "mtllib my materials.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl Red\nf 1 2 3\n";
+    let mut requested = Vec::new();
+    let model = ObjModel::parse("spaced.obj", source, |library| {
+        requested.push(library.to_string());
+        Ok("newmtl Red\nKd 1 0 0\n".to_string())
+    })
+    .unwrap();
+    assert_eq!(requested, ["my materials.mtl"]);
+    assert_eq!(model.meshes[0].material, Some(0));
+}
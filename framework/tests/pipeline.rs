@@ -0,0 +1,108 @@
+use framework::{offscreen_device, pipeline::{PipelineCache, RenderPipelineKey}, shader::ShaderSource};
+
+const SHADER: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+	return vec4(f32(index), 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+#ifdef RED
+	return vec4(1.0, 0.0, 0.0, 1.0);
+#else
+	return vec4(COLOR);
+#endif
+}
+";
+
+fn source() -> ShaderSource {
+    ShaderSource::embedded("test shader", SHADER).define("COLOR", "1.0")
+}
+
+#[test]
+fn keys_compare_by_value() {
+    let mut cache = PipelineCache::new();
+    let shader = cache.add_shader(source());
+    let key = || RenderPipelineKey::new(shader).target(wgpu::TextureFormat::Rgba8Unorm);
+
+    assert_eq!(key(), key());
+    assert_ne!(key(), key().sample_count(4));
+    assert_ne!(key(), key().define("RED", ""));
+    assert_ne!(key(), key().target(wgpu::TextureFormat::Rgba8Unorm));
+    let blended = RenderPipelineKey::new(shader).target(wgpu::ColorTargetState {
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+        write_mask: wgpu::ColorWrites::ALL,
+    });
+    assert_ne!(key(), blended);
+}
+
+#[test]
+fn reuses_pipelines() {
+    let Some((device, _queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let mut cache = PipelineCache::new();
+    let shader = cache.add_shader(source());
+    let key = RenderPipelineKey::new(shader).target(wgpu::TextureFormat::Rgba8Unorm);
+
+    let first = cache.get_or_create(&device, &key).global_id();
+    assert_eq!(cache.get_or_create(&device, &key).global_id(), first);
+    assert_eq!(cache.len(), 1);
+
+    // Another variant of the same shader, and another blend state, are new pipelines
+    let red = key.clone().define("RED", "");
+    assert_ne!(cache.get_or_create(&device, &red).global_id(), first);
+    let blended = RenderPipelineKey::new(shader).target(wgpu::ColorTargetState {
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+        write_mask: wgpu::ColorWrites::ALL,
+    });
+    cache.prepare(&device, &blended);
+    assert_eq!(cache.len(), 3);
+    assert!(cache.get(&blended).is_some());
+}
+
+#[test]
+fn replacing_a_shader_drops_its_pipelines() {
+    let Some((device, _queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let mut cache = PipelineCache::new();
+    let shader = cache.add_shader(source());
+    let other = cache.add_shader(source());
+    let key = RenderPipelineKey::new(shader).target(wgpu::TextureFormat::Rgba8Unorm);
+    let other_key = RenderPipelineKey::new(other).target(wgpu::TextureFormat::Rgba8Unorm);
+    let first = cache.get_or_create(&device, &key).global_id();
+    cache.prepare(&device, &other_key);
+
+    cache.replace_shader(shader, source().define("RED", ""));
+    assert!(cache.get(&key).is_none());
+    assert!(cache.get(&other_key).is_some());
+    assert_ne!(cache.get_or_create(&device, &key).global_id(), first);
+}
+
+#[test]
+fn surface_format_changes_drop_old_pipelines() {
+    let Some((device, _queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let mut cache = PipelineCache::new();
+    let shader = cache.add_shader(source());
+    let key = |format| RenderPipelineKey::new(shader).target(format);
+    let offscreen = key(wgpu::TextureFormat::Rgba16Float);
+
+    cache.set_surface_format(wgpu::TextureFormat::Bgra8UnormSrgb);
+    cache.prepare(&device, &key(wgpu::TextureFormat::Bgra8UnormSrgb));
+    cache.prepare(&device, &offscreen);
+    cache.set_surface_format(wgpu::TextureFormat::Bgra8UnormSrgb);
+    assert_eq!(cache.len(), 2);
+
+    cache.set_surface_format(wgpu::TextureFormat::Rgba8UnormSrgb);
+    assert!(cache.get(&key(wgpu::TextureFormat::Bgra8UnormSrgb)).is_none());
+    assert!(cache.get(&offscreen).is_some());
+}
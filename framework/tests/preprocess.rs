@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use framework::shader::{PreprocessError, Preprocessor};
+
+const COMMON: &str = "struct Light {\n\tcolor: vec3<f32>,\n}\n";
+
+fn temp_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("framework-preprocess-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn includes_files_once() {
+    let preprocessor = Preprocessor::new().add_file("common.wgsl", COMMON);
+    let shader = preprocessor
+        .process_source("shader.wgsl", "#include \"common.wgsl\"\n#include \"common.wgsl\"\nvar<private> light: Light;\n", None)
+        .unwrap();
+
+    assert_eq!(shader.source, format!("{COMMON}var<private> light: Light;\n"));
+    assert!(shader.validate().is_ok());
+    assert!(shader.dependencies.is_empty());
+}
+
+#[test]
+fn includes_the_framework_helpers() {
+    let source = "#include \"framework/fullscreen.wgsl\"\n@vertex\nfn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {\n\treturn fullscreen_vertex(index);\n}\n";
+    let shader = Preprocessor::new().process_source("shader.wgsl", source, None).unwrap();
+    shader.validate().unwrap();
+}
+
+#[test]
+fn defines_select_variants() {
+    let source = "#ifdef ARRAY\nconst LAYERS: u32 = COUNT;\n#else\nconst LAYERS: u32 = 1u;\n#endif\n#ifndef ARRAY\nconst SINGLE: bool = true;\n#endif\n";
+
+    let single = Preprocessor::new().process_source("shader.wgsl", source, None).unwrap();
+    assert_eq!(single.source, "const LAYERS: u32 = 1u;\nconst SINGLE: bool = true;\n");
+
+    let array = Preprocessor::new().define("ARRAY", "").define("COUNT", "6u").process_source("shader.wgsl", source, None).unwrap();
+    assert_eq!(array.source, "const LAYERS: u32 = 6u;\n");
+}
+
+#[test]
+fn substitutes_whole_words_only() {
+    let source = "#define SIZE 4\nconst SIZE_2: u32 = SIZE * 2u; // SIZE\n#undef SIZE\nconst B: u32 = SIZE;\n";
+    let shader = Preprocessor::new().process_source("shader.wgsl", source, None).unwrap();
+    assert_eq!(shader.source, "const SIZE_2: u32 = 4 * 2u; // SIZE\nconst B: u32 = SIZE;\n");
+}
+
+#[test]
+fn maps_lines_back_to_their_files() {
+    let source = "// The main file\n#include \"common.wgsl\"\n@compute @workgroup_size(1)\nfn main() {\n\tlet light = Light(1u);\n}\n";
+    let shader = Preprocessor::new().add_file("common.wgsl", COMMON).process_source("main.wgsl", source, None).unwrap();
+
+    assert_eq!(shader.location(1), Some(("main.wgsl", 1)));
+    assert_eq!(shader.location(2), Some(("common.wgsl", 1)));
+    assert_eq!(shader.location(5), Some(("main.wgsl", 3)));
+    assert_eq!(shader.location(100), None);
+
+    let diagnostic = shader.validate().unwrap_err();
+    assert!(diagnostic.contains("main.wgsl:5:"), "{diagnostic}");
+    assert!(diagnostic.contains("let light = Light(1u);"), "{diagnostic}");
+}
+
+#[test]
+fn errors_in_included_files_point_at_them() {
+    let common = "fn broken() -> f32 {\n\treturn 1u;\n}\n";
+    let shader = Preprocessor::new()
+        .add_file("common.wgsl", common)
+        .process_source("main.wgsl", "// The main file\n\n#include \"common.wgsl\"\n", None)
+        .unwrap();
+    let diagnostic = shader.validate().unwrap_err();
+    assert!(diagnostic.contains("common.wgsl:2:"), "{diagnostic}");
+}
+
+#[test]
+fn detects_include_cycles() {
+    let preprocessor = Preprocessor::new()
+        .add_file("a.wgsl", "#include \"b.wgsl\"\n")
+        .add_file("b.wgsl", "#include \"c.wgsl\"\n")
+        .add_file("c.wgsl", "#include \"a.wgsl\"\n");
+    let error = preprocessor.process("a.wgsl").unwrap_err();
+    assert_eq!(error, PreprocessError::Cycle { chain: vec!["a.wgsl".into(), "b.wgsl".into(), "c.wgsl".into(), "a.wgsl".into()] });
+}
+
+#[test]
+fn reports_bad_directives() {
+    let process = |source| Preprocessor::new().process_source("shader.wgsl", source, None).unwrap_err();
+
+    assert!(matches!(process("#include \"missing.wgsl\"\n"), PreprocessError::NotFound { include, .. } if include == "missing.wgsl"));
+    assert_eq!(process("\n#ifdef A\n").to_string(), "shader.wgsl:2: #ifdef without #endif");
+    assert_eq!(process("#endif\n").to_string(), "shader.wgsl:1: #endif without #ifdef");
+    assert_eq!(process("#pragma once\n").to_string(), "shader.wgsl:1: unknown directive #pragma");
+    // Directives in dropped blocks are skipped, but blocks still have to nest
+    assert!(Preprocessor::new().process_source("shader.wgsl", "#ifdef A\n#pragma once\n#endif\n", None).is_ok());
+}
+
+#[test]
+fn reads_includes_from_disk() {
+    let directory = temp_directory("disk");
+    std::fs::create_dir_all(directory.join("shared")).unwrap();
+    let main = directory.join("main.wgsl");
+    std::fs::write(&main, "#include \"shared/common.wgsl\"\nvar<private> light: Light;\n").unwrap();
+    std::fs::write(directory.join("shared/common.wgsl"), "#include \"more.wgsl\"\n").unwrap();
+    std::fs::write(directory.join("shared/more.wgsl"), COMMON).unwrap();
+
+    // Relative to the including file
+    let source = std::fs::read_to_string(&main).unwrap();
+    let shader = Preprocessor::new().process_source("main.wgsl", &source, Some(&main)).unwrap();
+    assert!(shader.validate().is_ok());
+    assert_eq!(shader.dependencies.len(), 3);
+    assert!(shader.dependencies.iter().any(|path| path.ends_with("shared/more.wgsl")));
+
+    // And through search paths
+    let shader = Preprocessor::new().add_search_path(&directory).process("main.wgsl").unwrap();
+    assert!(shader.source.starts_with(COMMON));
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
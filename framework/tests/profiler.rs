@@ -0,0 +1,103 @@
+use framework::{offscreen_device, profiler::GpuProfiler, RenderPassBuilder};
+
+fn target(device: &wgpu::Device) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d { width: 4, height: 4, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// Profiles a frame with a render pass and a compute pass, both empty
+fn profile_frame(device: &wgpu::Device, queue: &wgpu::Queue, profiler: &mut GpuProfiler, view: &wgpu::TextureView) {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    let scope = profiler.begin_scope("Clear");
+    RenderPassBuilder::new()
+        .clear(view, wgpu::Color::BLACK)
+        .timestamp_writes(profiler.render_timestamp_writes(scope))
+        .build(&mut encoder);
+    profiler.end_scope(scope);
+
+    let scope = profiler.begin_scope("Compute");
+    encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: profiler.compute_timestamp_writes(scope) });
+    profiler.end_scope(scope);
+
+    profiler.resolve(&mut encoder);
+    queue.submit(Some(encoder.finish()));
+    profiler.end_frame(device);
+}
+
+#[test]
+fn falls_back_to_cpu_timing() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let mut profiler = GpuProfiler::new(&device, &queue);
+    assert!(!profiler.has_gpu_timing());
+    assert!(profiler.results().is_empty());
+
+    let view = target(&device);
+    profile_frame(&device, &queue, &mut profiler, &view);
+    // Nothing to wait for, so the frame is reported straight away
+    assert_eq!(profiler.results_frame(), 1);
+    let names: Vec<&str> = profiler.results().iter().map(|timing| timing.name.as_str()).collect();
+    assert_eq!(names, ["Clear", "Compute"]);
+    assert!(profiler.results().iter().all(|timing| timing.gpu.is_none()));
+    assert!(profiler.results()[0].to_string().starts_with("Clear: "));
+}
+
+#[test]
+fn times_passes_on_the_gpu() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::TIMESTAMP_QUERY) else {
+        eprintln!("No adapter with timestamp queries available, skipping");
+        return;
+    };
+    let mut profiler = GpuProfiler::new(&device, &queue);
+    assert!(profiler.has_gpu_timing());
+
+    let view = target(&device);
+    for _ in 0..3 {
+        profile_frame(&device, &queue, &mut profiler, &view);
+    }
+    profiler.wait(&device);
+    let names: Vec<&str> = profiler.results().iter().map(|timing| timing.name.as_str()).collect();
+    assert_eq!(names, ["Clear", "Compute"]);
+    assert!(profiler.results().iter().all(|timing| timing.gpu.is_some()));
+}
+
+#[test]
+fn untimed_scopes_only_get_cpu_timing() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::TIMESTAMP_QUERY) else {
+        eprintln!("No adapter with timestamp queries available, skipping");
+        return;
+    };
+    let mut profiler = GpuProfiler::new(&device, &queue);
+    let view = target(&device);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    let untimed = profiler.begin_scope("Untimed");
+    profiler.end_scope(untimed);
+    let scope = profiler.begin_scope("Timed");
+    RenderPassBuilder::new()
+        .clear(&view, wgpu::Color::BLACK)
+        .timestamp_writes(profiler.render_timestamp_writes(scope))
+        .build(&mut encoder);
+    profiler.end_scope(scope);
+    profiler.resolve(&mut encoder);
+    queue.submit(Some(encoder.finish()));
+    profiler.end_frame(&device);
+    profiler.wait(&device);
+
+    let results = profiler.results();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].gpu.is_none());
+    assert!(results[1].gpu.is_some());
+}
@@ -0,0 +1,133 @@
+use std::{path::PathBuf, time::{Duration, Instant}};
+
+use framework::{offscreen_device, shader::{validate_wgsl, HotShader, Preprocessor, ShaderSource}};
+
+const VALID: &str = "@compute @workgroup_size(1) fn main() {}\n";
+const EDITED: &str = "@compute @workgroup_size(2) fn main() {}\n";
+const BROKEN: &str = "@compute @workgroup_size(1)\nfn main() {\n\tlet x: f32 = 1u;\n}\n";
+
+fn temp_shader(name: &str, source: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("framework-shader-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("shader.wgsl");
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+// Waits for the watcher to notice a write, which happens on another thread
+fn wait_for_change(shader: &HotShader) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if shader.is_changed() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn diagnostics_point_at_the_error() {
+    assert!(validate_wgsl(VALID, "valid.wgsl").is_ok());
+
+    let diagnostic = validate_wgsl(BROKEN, "broken.wgsl").unwrap_err();
+    assert!(diagnostic.contains("broken.wgsl:3"), "{diagnostic}");
+    assert!(diagnostic.contains("let x: f32 = 1u;"), "{diagnostic}");
+}
+
+#[test]
+fn validation_errors_are_reported_too() {
+    // Parses fine, but returns nothing from a function that promises a value
+    let diagnostic = validate_wgsl("fn value() -> f32 {}\n", "invalid.wgsl").unwrap_err();
+    assert!(diagnostic.contains("invalid.wgsl"), "{diagnostic}");
+}
+
+#[test]
+fn reloads_edits_and_keeps_the_old_module_on_errors() {
+    let Some((device, _queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let path = temp_shader("reload", VALID);
+    let mut shader = HotShader::new(&device, ShaderSource::embedded("test shader", VALID).watch(&path));
+    assert!(shader.reload(&device, |_| ()).is_none(), "nothing changed yet");
+
+    std::fs::write(&path, BROKEN).unwrap();
+    assert!(wait_for_change(&shader), "the watcher didn't see the edit");
+    assert!(shader.reload(&device, |_| ()).is_none());
+    assert_eq!(shader.text(), VALID);
+
+    std::fs::write(&path, EDITED).unwrap();
+    assert!(wait_for_change(&shader), "the watcher didn't see the edit");
+    assert_eq!(shader.reload(&device, |_| "rebuilt"), Some("rebuilt"));
+    assert_eq!(shader.text(), EDITED);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn failed_rebuilds_keep_the_old_module() {
+    let Some((device, _queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let path = temp_shader("rebuild", VALID);
+    let mut shader = HotShader::new(&device, ShaderSource::embedded("test shader", VALID).watch(&path));
+
+    std::fs::write(&path, EDITED).unwrap();
+    shader.mark_changed();
+    // The new shader is fine, but the pipeline asks for an entry point it doesn't have
+    let pipeline = shader.reload(&device, |module| {
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module,
+            entry_point: "missing",
+        })
+    });
+    assert!(pipeline.is_none());
+    assert_eq!(shader.text(), VALID);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn falls_back_to_the_embedded_copy() {
+    let Some((device, _queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let missing = std::env::temp_dir().join("framework-shader-missing/shader.wgsl");
+    let shader = HotShader::new(&device, ShaderSource::embedded("test shader", VALID).watch(missing));
+    assert_eq!(shader.text(), VALID);
+
+    let path = temp_shader("fallback", BROKEN);
+    let shader = HotShader::new(&device, ShaderSource::embedded("test shader", VALID).watch(&path));
+    assert_eq!(shader.text(), VALID);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn reloads_when_an_included_file_changes() {
+    let Some((device, _queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    const MAIN: &str = "#include \"size.wgsl\"\n@compute @workgroup_size(SIZE) fn main() {}\n";
+    let path = temp_shader("include", MAIN);
+    let size = path.with_file_name("size.wgsl");
+    std::fs::write(&size, "const SIZE: u32 = 1u;\n").unwrap();
+
+    let source = ShaderSource::embedded("test shader", MAIN)
+        .watch(&path)
+        .preprocess(Preprocessor::new().add_file("size.wgsl", "const SIZE: u32 = 1u;\n"));
+    let mut shader = HotShader::new(&device, source);
+    assert!(shader.text().contains("SIZE: u32 = 1u"));
+
+    std::fs::write(&size, "const SIZE: u32 = 2u;\n").unwrap();
+    assert!(wait_for_change(&shader), "the watcher didn't see the edit");
+    assert_eq!(shader.reload(&device, |_| "rebuilt"), Some("rebuilt"));
+    assert!(shader.text().contains("SIZE: u32 = 2u"));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,149 @@
+use framework::{offscreen_device, shapes::{Circle, CircleRenderer, Draw, LineCap}};
+use glam::{vec2, Affine2, Vec2};
+
+const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+fn positions(draw: &Draw) -> Vec<Vec2> {
+    draw.mesh().vertices.iter().map(|vertex| Vec2::from(vertex.position)).collect()
+}
+
+fn triangle_area(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b - a).perp_dot(c - a).abs() / 2.0
+}
+
+#[test]
+fn rect_has_a_fill_and_a_fringe() {
+    let mut draw = Draw::new();
+    draw.rect(vec2(10.0, 10.0), vec2(20.0, 10.0), RED);
+    let mesh = draw.mesh();
+
+    // An inner and an outer vertex per corner, two fill triangles and two per edge of fringe
+    assert_eq!(mesh.vertices.len(), 8);
+    assert_eq!(mesh.indices.len(), 6 + 4 * 6);
+    assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()));
+    assert!(mesh.vertices.iter().step_by(2).all(|vertex| vertex.color == RED));
+    assert!(mesh.vertices.iter().skip(1).step_by(2).all(|vertex| vertex.color[3] == 0.0));
+}
+
+#[test]
+fn higher_layers_come_last() {
+    let mut draw = Draw::new();
+    draw.set_layer(1);
+    draw.rect(Vec2::ZERO, vec2(10.0, 10.0), RED);
+    draw.set_layer(-1);
+    draw.rect(Vec2::ZERO, vec2(10.0, 10.0), BLUE);
+    let mesh = draw.mesh();
+
+    assert_eq!(mesh.vertices.len(), 16);
+    assert_eq!(mesh.vertices[0].color, BLUE);
+    assert_eq!(mesh.vertices[8].color, RED);
+    // The second layer's indices are offset past the first layer's vertices
+    assert!(mesh.indices[30..].iter().all(|&index| (8..16).contains(&index)));
+}
+
+#[test]
+fn transform_applies_to_following_shapes() {
+    let mut draw = Draw::new();
+    draw.set_transform(Affine2::from_scale_angle_translation(Vec2::splat(2.0), 0.0, vec2(100.0, 50.0)));
+    draw.rect(Vec2::ZERO, vec2(10.0, 5.0), RED);
+    let transformed = positions(&draw);
+    let (min, max) = transformed.iter().fold((Vec2::MAX, Vec2::MIN), |(min, max), &p| (min.min(p), max.max(p)));
+    // The fringe straddles the outline by half a pixel, unscaled
+    assert!(min.abs_diff_eq(vec2(99.5, 49.5), 1e-4), "{min}");
+    assert!(max.abs_diff_eq(vec2(120.5, 60.5), 1e-4), "{max}");
+
+    draw.clear();
+    draw.reset_transform();
+    draw.rect(Vec2::ZERO, vec2(10.0, 5.0), RED);
+    assert!(positions(&draw).iter().all(|p| p.x <= 10.5 && p.y <= 5.5));
+}
+
+#[test]
+fn zero_length_butt_line_draws_nothing() {
+    let mut draw = Draw::new();
+    draw.line(vec2(5.0, 5.0), vec2(5.0, 5.0), 4.0, LineCap::Butt, RED);
+    assert!(draw.is_empty());
+    assert!(draw.mesh().vertices.is_empty());
+
+    draw.line(vec2(5.0, 5.0), vec2(5.0, 5.0), 4.0, LineCap::Square, RED);
+    assert_eq!(draw.mesh().vertices.len(), 8);
+}
+
+#[test]
+fn fills_concave_polygons_without_overlap() {
+    // An L, starting at a corner that can't see the whole shape, so a fan would spill outside
+    let points = [vec2(20.0, 0.0), vec2(20.0, 10.0), vec2(10.0, 10.0), vec2(10.0, 20.0), vec2(0.0, 20.0), vec2(0.0, 0.0)];
+    for points in [points.to_vec(), points.iter().rev().copied().collect()] {
+        let mut draw = Draw::new();
+        draw.polygon(&points, RED);
+        let mesh = draw.mesh();
+        let inner: Vec<Vec2> = mesh.vertices.iter().step_by(2).map(|vertex| Vec2::from(vertex.position)).collect();
+
+        let fill = &mesh.indices[..3 * (points.len() - 2)];
+        let triangles: f32 = fill
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| Vec2::from(mesh.vertices[triangle[i] as usize].position));
+                triangle_area(a, b, c)
+            })
+            .sum();
+        let outline: f32 = (0..inner.len()).map(|i| inner[i].perp_dot(inner[(i + 1) % inner.len()])).sum::<f32>().abs() / 2.0;
+        assert!((triangles - outline).abs() < 1e-2, "triangles cover {triangles}, the outline {outline}");
+        // Only inner vertices are filled
+        assert!(fill.iter().all(|index| index % 2 == 0));
+    }
+}
+
+#[test]
+fn stroked_circle_edge_keeps_the_stroke_color() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    const SIZE: u32 = 64;
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+
+    let mut circles = CircleRenderer::new(&device, format, SIZE, SIZE);
+    // Centered on a texel corner, so texel (48, 32) sits exactly on the stroke's inner edge
+    circles.push(Circle::stroked(vec2(32.5, 32.5), 20.0, 4.0, [1.0; 4]));
+    circles.prepare(&device, &queue);
+
+    let target = framework::TextureBuilder::new(SIZE, SIZE)
+        .format(format)
+        .usage(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC)
+        .build(&device);
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (SIZE * SIZE * 4) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut rpass = framework::RenderPassBuilder::new()
+            .clear(&view, wgpu::Color::BLACK)
+            .build(&mut encoder);
+        circles.draw(&mut rpass);
+    }
+    encoder.copy_texture_to_buffer(
+        target.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &readback,
+            layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(SIZE * 4), rows_per_image: None },
+        },
+        wgpu::Extent3d { width: SIZE, height: SIZE, depth_or_array_layers: 1 },
+    );
+    queue.submit(Some(encoder.finish()));
+    readback.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let pixels = readback.slice(..).get_mapped_range().to_vec();
+    let red = |x: u32, y: u32| pixels[((y * SIZE + x) * 4) as usize];
+
+    // Half covered by a white stroke over black is mid grey, not darkened by the clear fill
+    assert!(red(48, 32).abs_diff(128) <= 3, "inner edge is {}", red(48, 32));
+    assert_eq!(red(50, 32), 255);
+    assert_eq!(red(32, 32), 0);
+}
@@ -0,0 +1,219 @@
+use framework::{offscreen_device, text::{Align, Font, TextLayout, TextRenderer, TextStyle}};
+use glam::Vec2;
+
+const SIZE: f32 = 32.0;
+
+fn advance(font: &Font, text: &str) -> f32 {
+    let characters: Vec<char> = text.chars().collect();
+    let kerning: f32 = characters.windows(2).map(|pair| font.kern(pair[0], pair[1], SIZE)).sum();
+    characters.iter().map(|&character| font.advance(character, SIZE)).sum::<f32>() + kerning
+}
+
+fn line_texts(layout: &TextLayout) -> Vec<String> {
+    (0..layout.lines.len()).map(|line| layout.line_text(line)).collect()
+}
+
+#[test]
+fn single_line_follows_advances() {
+    let font = Font::fallback();
+    let layout = TextLayout::new(&font, "Hello", &TextStyle::new(SIZE));
+
+    assert_eq!(layout.glyphs.len(), 5);
+    assert_eq!(layout.lines.len(), 1);
+    let ascent = font.line_metrics(SIZE).ascent;
+    assert!(layout.glyphs.iter().all(|glyph| glyph.baseline == ascent));
+    assert!(layout.glyphs.windows(2).all(|pair| pair[1].x > pair[0].x));
+    assert!((layout.size.x - advance(&font, "Hello")).abs() < 1e-3);
+    assert!((layout.size.y - font.line_metrics(SIZE).line_height()).abs() < 1e-3);
+}
+
+#[test]
+fn kerned_pairs_are_pulled_together() {
+    let font = Font::fallback();
+    let kern = font.kern('A', 'V', SIZE);
+    assert!(kern < 0.0, "the fallback font should kern AV");
+
+    let layout = TextLayout::new(&font, "AV", &TextStyle::new(SIZE));
+    assert!((layout.glyphs[1].x - (font.advance('A', SIZE) + kern)).abs() < 1e-3);
+}
+
+#[test]
+fn line_breaks_start_new_lines() {
+    let font = Font::fallback();
+    let layout = TextLayout::new(&font, "one\ntwo\r\n\nthree", &TextStyle::new(SIZE));
+
+    assert_eq!(line_texts(&layout), ["one", "two", "", "three"]);
+    let line_height = font.line_metrics(SIZE).line_height();
+    for pair in layout.lines.windows(2) {
+        assert!((pair[1].baseline - pair[0].baseline - line_height).abs() < 1e-3);
+    }
+    assert!((layout.size.y - 4.0 * line_height).abs() < 1e-3);
+}
+
+#[test]
+fn line_spacing_scales_line_height() {
+    let font = Font::fallback();
+    let layout = TextLayout::new(&font, "a\nb", &TextStyle::new(SIZE).line_spacing(1.5));
+    let line_height = font.line_metrics(SIZE).line_height();
+    assert!((layout.lines[1].baseline - layout.lines[0].baseline - 1.5 * line_height).abs() < 1e-3);
+}
+
+#[test]
+fn wraps_at_whitespace() {
+    let font = Font::fallback();
+    let max_width = advance(&font, "the quick").max(advance(&font, "brown fox")) + 1.0;
+    let layout = TextLayout::new(&font, "the quick brown fox jumps", &TextStyle::new(SIZE).max_width(max_width));
+
+    assert_eq!(line_texts(&layout), ["the quick", "brown fox", "jumps"]);
+    for line in &layout.lines {
+        assert!(line.width <= max_width);
+        // Every line starts at the left edge
+        assert_eq!(layout.glyphs[line.glyphs.start].x, 0.0);
+    }
+    // No characters are lost, only the line breaks are replaced
+    assert_eq!(layout.glyphs.len(), "the quick brown fox jumps".len());
+}
+
+#[test]
+fn splits_words_wider_than_the_line() {
+    let font = Font::fallback();
+    let max_width = advance(&font, "abcd") + 1.0;
+    let layout = TextLayout::new(&font, "abcdefghij", &TextStyle::new(SIZE).max_width(max_width));
+
+    assert_eq!(line_texts(&layout).concat(), "abcdefghij");
+    assert!(layout.lines.len() >= 3);
+    assert!(layout.lines.iter().all(|line| line.width <= max_width));
+}
+
+#[test]
+fn aligns_lines() {
+    let font = Font::fallback();
+    let width = 400.0;
+    let line_width = advance(&font, "centered");
+
+    let center = TextLayout::new(&font, "centered", &TextStyle::new(SIZE).max_width(width).align(Align::Center));
+    assert!((center.glyphs[0].x - (width - line_width) / 2.0).abs() < 1e-3);
+
+    let right = TextLayout::new(&font, "centered", &TextStyle::new(SIZE).max_width(width).align(Align::Right));
+    assert!((right.glyphs[0].x - (width - line_width)).abs() < 1e-3);
+
+    // Without a wrapping width lines align to the widest one
+    let unbounded = TextLayout::new(&font, "wide line\nnarrow", &TextStyle::new(SIZE).align(Align::Right));
+    let last = &unbounded.glyphs[unbounded.lines[1].glyphs.end - 1];
+    assert!((last.x + last.advance - unbounded.size.x).abs() < 1e-3);
+}
+
+#[test]
+fn empty_text_has_one_empty_line() {
+    let layout = TextLayout::new(&Font::fallback(), "", &TextStyle::new(SIZE));
+    assert_eq!(layout.lines.len(), 1);
+    assert!(layout.glyphs.is_empty());
+    assert_eq!(layout.size.x, 0.0);
+}
+
+#[test]
+fn renders_glyphs_inside_their_layout() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    const WIDTH: u32 = 128;
+    const HEIGHT: u32 = 64;
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+
+    let mut text = TextRenderer::new(&device, format, WIDTH, HEIGHT);
+    let position = Vec2::new(8.0, 8.0);
+    let layout = text.push_text("Hi!", position, &TextStyle::new(SIZE), [1.0; 4]);
+    text.prepare(&device, &queue);
+
+    let target = framework::TextureBuilder::new(WIDTH, HEIGHT)
+        .format(format)
+        .usage(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC)
+        .build(&device);
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (WIDTH * HEIGHT * 4) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut rpass = framework::RenderPassBuilder::new()
+            .clear(&view, wgpu::Color::BLACK)
+            .build(&mut encoder);
+        text.draw(&mut rpass);
+    }
+    encoder.copy_texture_to_buffer(
+        target.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &readback,
+            layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(WIDTH * 4), rows_per_image: None },
+        },
+        wgpu::Extent3d { width: WIDTH, height: HEIGHT, depth_or_array_layers: 1 },
+    );
+    queue.submit(Some(encoder.finish()));
+    readback.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let pixels = readback.slice(..).get_mapped_range().to_vec();
+
+    let (mut inside, mut outside) = (0, 0);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            if pixels[((y * WIDTH + x) * 4) as usize] == 0 {
+                continue;
+            }
+            let pixel = Vec2::new(x as f32, y as f32) - position;
+            if pixel.x >= -1.0 && pixel.y >= -1.0 && pixel.x <= layout.size.x + 1.0 && pixel.y <= layout.size.y + 1.0 {
+                inside += 1;
+            } else {
+                outside += 1;
+            }
+        }
+    }
+    assert!(inside > 50, "only {inside} pixels were drawn");
+    assert_eq!(outside, 0);
+}
+
+#[test]
+fn caches_glyphs_per_quarter_pixel() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let mut text = TextRenderer::new(&device, wgpu::TextureFormat::Rgba8Unorm, 128, 64);
+    for size in [16.0, 16.05, 16.1] {
+        text.push_text("a", Vec2::ZERO, &TextStyle::new(size), [1.0; 4]);
+    }
+    assert_eq!(text.cached_glyphs(), 1);
+    text.push_text("a", Vec2::ZERO, &TextStyle::new(16.25), [1.0; 4]);
+    assert_eq!(text.cached_glyphs(), 2);
+
+    // Glyphs that stop being drawn are dropped after a while
+    text.prepare(&device, &queue);
+    for _ in 0..200 {
+        text.push_text("a", Vec2::ZERO, &TextStyle::new(16.0), [1.0; 4]);
+        text.prepare(&device, &queue);
+    }
+    assert_eq!(text.cached_glyphs(), 1);
+}
+
+#[test]
+fn skips_glyphs_that_dont_fit_the_atlas() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let mut text = TextRenderer::new(&device, wgpu::TextureFormat::Rgba8Unorm, 128, 64).with_max_atlas_size(64);
+    let style = TextStyle::new(48.0);
+
+    let layout = text.layout("MWMW", &style);
+    assert!(text.push_layout(&layout, Vec2::ZERO, [1.0; 4]) > 0);
+    text.prepare(&device, &queue);
+
+    // Glyphs from earlier frames make room for new ones
+    let layout = text.layout("H", &style);
+    assert_eq!(text.push_layout(&layout, Vec2::ZERO, [1.0; 4]), 0);
+    text.prepare(&device, &queue);
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use framework::time::{Clock, FixedTimestep, FrameHistory};
+
+fn milliseconds(milliseconds: u64) -> Duration {
+    Duration::from_millis(milliseconds)
+}
+
+#[test]
+fn clock_accumulates_scaled_time() {
+    let mut clock = Clock::new();
+    clock.advance(milliseconds(10));
+    clock.advance(milliseconds(20));
+    assert_eq!(clock.frame(), 2);
+    assert_eq!(clock.elapsed_duration(), milliseconds(30));
+    assert_eq!(clock.delta_duration(), milliseconds(20));
+
+    clock.set_scale(0.5);
+    clock.advance(milliseconds(20));
+    assert_eq!(clock.delta_duration(), milliseconds(10));
+    assert_eq!(clock.real_delta(), milliseconds(20));
+    assert_eq!(clock.elapsed_duration(), milliseconds(40));
+}
+
+#[test]
+fn paused_clocks_still_measure_frames() {
+    let mut clock = Clock::new();
+    clock.advance(milliseconds(10));
+    clock.pause();
+    clock.advance(milliseconds(16));
+    assert_eq!(clock.delta(), 0.0);
+    assert_eq!(clock.elapsed_duration(), milliseconds(10));
+    assert_eq!(clock.history().latest(), Some(milliseconds(16)));
+
+    clock.toggle_pause();
+    clock.advance(milliseconds(16));
+    assert_eq!(clock.elapsed_duration(), milliseconds(26));
+}
+
+#[test]
+fn long_frames_are_capped() {
+    let mut clock = Clock::new().with_max_delta(milliseconds(100));
+    clock.advance(Duration::from_secs(5));
+    assert_eq!(clock.delta_duration(), milliseconds(100));
+    assert_eq!(clock.real_delta(), Duration::from_secs(5));
+}
+
+#[test]
+fn fixed_timestep_carries_leftover_time() {
+    let mut timestep = FixedTimestep::new(milliseconds(10));
+    assert_eq!(timestep.update(milliseconds(25)), 2);
+    assert!((timestep.alpha() - 0.5).abs() < 1e-6);
+    assert_eq!(timestep.update(milliseconds(5)), 1);
+    assert_eq!(timestep.alpha(), 0.0);
+    assert_eq!(timestep.update(milliseconds(4)), 0);
+}
+
+#[test]
+fn fixed_timestep_drops_what_it_cant_catch_up_on() {
+    let mut timestep = FixedTimestep::new(milliseconds(10)).max_steps(3);
+    assert_eq!(timestep.update(milliseconds(95)), 3);
+    assert_eq!(timestep.alpha(), 0.0);
+    assert_eq!(timestep.update(milliseconds(10)), 1);
+}
+
+#[test]
+fn history_keeps_the_latest_frames() {
+    let mut history = FrameHistory::new(3);
+    assert!(history.stats().is_none());
+    for frame_time in [5, 10, 15, 20] {
+        history.push(milliseconds(frame_time));
+    }
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.samples().collect::<Vec<_>>(), [milliseconds(10), milliseconds(15), milliseconds(20)]);
+}
+
+#[test]
+fn history_statistics() {
+    let mut history = FrameHistory::new(100);
+    for frame_time in 1..=100 {
+        history.push(milliseconds(frame_time));
+    }
+    let stats = history.stats().unwrap();
+    assert_eq!(stats.min, milliseconds(1));
+    assert_eq!(stats.max, milliseconds(100));
+    assert_eq!(stats.average, Duration::from_micros(50_500));
+    assert_eq!(stats.median, milliseconds(50));
+    assert_eq!(stats.p95, milliseconds(95));
+    assert_eq!(stats.p99, milliseconds(99));
+    assert_eq!(history.percentile(0.0), Some(milliseconds(1)));
+    assert_eq!(history.percentile(100.0), Some(milliseconds(100)));
+    assert!((stats.fps() - 1000.0 / 50.5).abs() < 1e-9);
+}
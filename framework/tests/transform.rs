@@ -0,0 +1,108 @@
+use encase::ShaderType;
+use framework::{
+    offscreen_device,
+    transform::{Transform, TransformBuffer, TransformInstance, TransformUniform},
+};
+use glam::{vec3, Mat3, Quat, Vec3};
+
+#[test]
+fn normal_matrix_keeps_normals_perpendicular_under_non_uniform_scale() {
+    let transform = Transform::from_translation(vec3(1.0, 2.0, 3.0))
+        .with_rotation(Quat::from_rotation_y(0.7))
+        .with_scale(vec3(2.0, 1.0, 0.5));
+    let model = Mat3::from_mat4(transform.model_matrix());
+    let normal_matrix = transform.normal_matrix();
+
+    // A sloped surface, given by a normal and two directions along it
+    let normal = vec3(1.0, 1.0, 1.0).normalize();
+    for tangent in [vec3(1.0, -1.0, 0.0), vec3(0.0, 1.0, -1.0)] {
+        let tangent = model * tangent;
+        assert!(tangent.dot(normal_matrix * normal).abs() < 1e-5);
+        // The model matrix itself would tilt the normal off the surface
+        assert!(tangent.dot(model * normal).abs() > 0.1);
+    }
+}
+
+#[test]
+fn normal_matrix_ignores_translation_and_uniform_scale_direction() {
+    let rotation = Quat::from_rotation_x(1.2);
+    let transform = Transform::from_rotation(rotation)
+        .with_translation(vec3(5.0, -3.0, 2.0))
+        .with_scale(Vec3::splat(3.0));
+    let normal = transform.normal_matrix() * Vec3::Y;
+    assert!(normal.normalize().abs_diff_eq(rotation * Vec3::Y, 1e-5));
+}
+
+#[test]
+fn transform_uniform_matches_wgsl_layout() {
+    // A `mat4x4` then a `mat3x3`, whose columns are padded to 16 bytes each
+    assert_eq!(TransformUniform::min_size().get(), 64 + 48);
+    let mut buffer = encase::UniformBuffer::new(Vec::new());
+    buffer.write(&Transform::IDENTITY.uniform()).unwrap();
+    assert_eq!(buffer.into_inner().len(), 112);
+}
+
+#[test]
+fn transform_instance_matches_vertex_layout() {
+    // Vertex attributes are tightly packed, so the `vec3` columns have no padding
+    assert_eq!(std::mem::size_of::<TransformInstance>(), 16 * 4 + 9 * 4);
+    let layout = TransformInstance::layout();
+    assert_eq!(layout.array_stride, 100);
+    assert_eq!(layout.step_mode, wgpu::VertexStepMode::Instance);
+
+    let offsets: Vec<u64> = layout.attributes.iter().map(|attribute| attribute.offset).collect();
+    assert_eq!(offsets, [0, 16, 32, 48, 64, 76, 88]);
+    let locations: Vec<u32> = layout.attributes.iter().map(|attribute| attribute.shader_location).collect();
+    assert_eq!(locations, (8..=14).collect::<Vec<_>>());
+    let last = layout.attributes.last().unwrap();
+    assert_eq!(last.offset + last.format.size(), layout.array_stride);
+
+    let transform = Transform::from_scale(vec3(2.0, 1.0, 1.0));
+    let instance = transform.instance();
+    assert_eq!(instance.model, transform.model_matrix().to_cols_array_2d());
+    assert_eq!(instance.normal, transform.normal_matrix().to_cols_array_2d());
+}
+
+#[test]
+fn transform_buffer_grows_and_places_each_transform_at_its_offset() {
+    let Some((device, queue)) = offscreen_device(wgpu::Features::empty()) else {
+        eprintln!("No adapter available, skipping");
+        return;
+    };
+    let alignment = device.limits().min_uniform_buffer_offset_alignment;
+    let mut transforms = TransformBuffer::new(&device, 2);
+    assert_eq!(transforms.capacity(), 2);
+
+    let written: Vec<Transform> = (0..3)
+        .map(|index| Transform::from_translation(vec3(index as f32, 1.0, 2.0)).with_scale(vec3(1.0, 2.0, 3.0)))
+        .collect();
+    assert!(transforms.write(&device, &queue, &written));
+    assert!(transforms.capacity() >= 3);
+    assert!(!transforms.write(&device, &queue, &written));
+
+    let size = transforms.buffer.size();
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_buffer_to_buffer(&transforms.buffer, 0, &staging, 0, size);
+    queue.submit(Some(encoder.finish()));
+    staging.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let bytes = staging.slice(..).get_mapped_range().to_vec();
+
+    let stride = TransformUniform::min_size().get() as usize;
+    for (index, transform) in written.iter().enumerate() {
+        let offset = transforms.offset(index);
+        assert_eq!(offset % alignment, 0);
+        assert!(index == 0 || offset as usize >= transforms.offset(index - 1) as usize + stride);
+
+        let mut expected = encase::UniformBuffer::new(Vec::new());
+        expected.write(&transform.uniform()).unwrap();
+        let offset = offset as usize;
+        assert_eq!(&bytes[offset..offset + stride], expected.into_inner().as_slice(), "transform {index}");
+    }
+}
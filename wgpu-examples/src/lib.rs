@@ -0,0 +1,19 @@
+/*
+   Every example in the repository, for running them by name from a single binary.
+   */
+use framework::app::AppEntry;
+
+/** The examples in the order they're listed, which is the order they're meant to be read in.
+ */
+pub const APPS: &[AppEntry] = &[
+    AppEntry::of::<hello_triangle::HelloTriangle>(),
+    AppEntry::of::<uniform_values::UniformValues>(),
+    AppEntry::of::<cube::Cube>(),
+    AppEntry::of::<circles::Circles>(),
+];
+
+/** The example called `name`, if there is one.
+ */
+pub fn find(name: &str) -> Option<&'static AppEntry> {
+    APPS.iter().find(|entry| entry.name == name)
+}
@@ -0,0 +1,42 @@
+use framework::args::{RunOptions, OPTIONS_HELP};
+use wgpu_examples::{find, APPS};
+
+fn usage() -> String {
+    let names: Vec<&str> = APPS.iter().map(|entry| entry.name).collect();
+    format!(
+        "Usage: wgpu-examples list\n       wgpu-examples <example> [options] [arguments]\n\nExamples: {}\n\n{OPTIONS_HELP}",
+        names.join(", ")
+    )
+}
+
+fn list() {
+    let width = APPS.iter().map(|entry| entry.name.len()).max().unwrap_or(0);
+    for entry in APPS {
+        println!("{:width$}  {}", entry.name, entry.description);
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("list") => list(),
+        Some("help" | "-h" | "--help") => println!("{}", usage()),
+        Some(name) => {
+            let Some(entry) = find(name) else {
+                eprintln!("No example called {name:?}\n\n{}", usage());
+                std::process::exit(2);
+            };
+            match RunOptions::parse(args) {
+                Ok(options) => (entry.run)(options),
+                Err(error) => {
+                    eprintln!("{error}\n\n{}", usage());
+                    std::process::exit(2);
+                }
+            }
+        }
+        None => {
+            eprintln!("{}", usage());
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use framework::{args::RunOptions, offscreen_device};
+use wgpu_examples::{find, APPS};
+
+#[test]
+fn names_are_unique() {
+    for (i, entry) in APPS.iter().enumerate() {
+        assert!(APPS[..i].iter().all(|other| other.name != entry.name), "{} is listed twice", entry.name);
+        assert_eq!(find(entry.name).map(|found| found.description), Some(entry.description));
+    }
+    assert!(find("missing").is_none());
+}
+
+#[test]
+fn every_example_saves_a_screenshot() {
+    // The examples create their own device, so this only checks that one can be created
+    if offscreen_device(wgpu::Features::empty()).is_none() {
+        eprintln!("No adapter available, skipping");
+        return;
+    }
+    let directory = std::env::temp_dir().join(format!("wgpu-examples-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+
+    for entry in APPS {
+        let path = directory.join(format!("{}.png", entry.name));
+        (entry.run)(RunOptions { headless: true, frames: Some(2), screenshot: Some(path.clone()), args: Vec::new() });
+
+        let screenshot = image::open(&path).unwrap_or_else(|e| panic!("Error in opening the screenshot of {}: {e}", entry.name));
+        assert!(screenshot.width() > 0 && screenshot.height() > 0, "{} saved an empty screenshot", entry.name);
+    }
+    std::fs::remove_dir_all(&directory).unwrap();
+}